@@ -0,0 +1,194 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use crate::version::{Version, VersionSupport};
+use crate::games::resolver::*;
+use crate::screen_state::ScreenState;
+use crate::memory::{Memory, PointerChain};
+use crate::vector3f::Vector3f;
+
+//Where Dark Souls 3 keeps what the dll reads and writes. Everything goes through Memory, so that the
+//pattern table and the offsets below can be tested against snapshots without the game.
+
+pub const PATTERN_TABLE: &str = include_str!("../../tables/darksoulsiii.json");
+
+//Every version SoulMemory's DarkSouls3Version covers, up to the last patch 1.15.2
+pub const SUPPORTED_VERSIONS: &[VersionSupport] =
+&[
+    VersionSupport { min_version: Version::new(1, 0, 0, 0), max_version: Version::new(1, 15, 2, 0), capabilities: &[EVENT_FLAGS, IN_GAME_TIME, LOADING_STATE, PLAYER_POSITION] },
+];
+
+#[derive(Default)]
+pub struct DarkSouls3Layout
+{
+    pub event_flag_man: PointerChain,
+    pub chr_dbg_flags: PointerChain,
+    game_data_man: PointerChain,
+    igt_offset: usize,
+    loading: PointerChain,
+    player_ins: PointerChain,
+    chr_physics_module: PointerChain,
+}
+
+impl DarkSouls3Layout
+{
+    pub fn resolve(resolver: &mut Resolver) -> Self
+    {
+        let layout = DarkSouls3Layout
+        {
+            event_flag_man: resolver.pointer(EVENT_FLAGS, "event_flag_man"),
+            //Not in the built in table, the ChrDbg pattern and flag layout haven't been checked against a snapshot of this game yet
+            //and the tests expect it to stay unavailable. Add a ChrDbgFlags symbol and a chr_dbg_flags pointer to the override file to use them.
+            chr_dbg_flags: resolver.pointer(CHR_DBG, "chr_dbg_flags"),
+            game_data_man: resolver.pointer(IN_GAME_TIME, "game_data_man"),
+            //IGT moved in GameDataMan after 1.05, same as SoulMemory's DarkSouls3Version
+            igt_offset: resolver.value(IN_GAME_TIME, "igt", 0xa4),
+            //SoulMemory reads this one at -1 with an instruction size of 7, moving the instruction end does the same
+            loading: resolver.pointer(LOADING_STATE, "loading"),
+            player_ins: resolver.pointer(LOADING_STATE, "player_ins"),
+            chr_physics_module: resolver.pointer(PLAYER_POSITION, "chr_physics_module"),
+        };
+        //Quitout stays off until there is a source for the quit request field in MenuMan, it shows up as unavailable in the diagnostics
+        resolver.fail(QUITOUT, "MenuMan", String::from("quit request field not known for this game yet"));
+        return layout;
+    }
+
+    ///The event flag manager that the game's flag functions take, 0 before it exists
+    pub fn event_flag_man(&self, memory: &dyn Memory) -> u64
+    {
+        return self.event_flag_man.resolve(memory).unwrap_or_default() as u64;
+    }
+
+    pub fn in_game_time(&self, memory: &dyn Memory) -> u32
+    {
+        return self.game_data_man.read_u32(memory, self.igt_offset).unwrap_or_default();
+    }
+
+    pub fn screen_state(&self, memory: &dyn Memory) -> ScreenState
+    {
+        if self.loading.read_u32(memory, 0).unwrap_or_default() != 0
+        {
+            return ScreenState::Loading;
+        }
+        if self.player_ins.read_u64(memory, 0x80).unwrap_or_default() == 0
+        {
+            return ScreenState::MainMenu;
+        }
+        return ScreenState::InGame;
+    }
+
+    pub fn position(&self, memory: &dyn Memory) -> Vector3f
+    {
+        let x = self.chr_physics_module.read_f32(memory, 0x80).unwrap_or_default();
+        let y = self.chr_physics_module.read_f32(memory, 0x84).unwrap_or_default();
+        let z = self.chr_physics_module.read_f32(memory, 0x88).unwrap_or_default();
+        return Vector3f::new(x, y, z);
+    }
+
+    pub fn set_position(&self, memory: &dyn Memory, position: &Vector3f)
+    {
+        self.chr_physics_module.write_f32(memory, 0x80, position.x);
+        self.chr_physics_module.write_f32(memory, 0x84, position.y);
+        self.chr_physics_module.write_f32(memory, 0x88, position.z);
+    }
+
+    pub fn rotation(&self, memory: &dyn Memory) -> f32
+    {
+        return self.chr_physics_module.read_f32(memory, 0x74).unwrap_or_default();
+    }
+
+    pub fn set_rotation(&self, memory: &dyn Memory, rotation: f32)
+    {
+        self.chr_physics_module.write_f32(memory, 0x74, rotation);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::games::dark_souls_3::*;
+    use crate::memory::fixture::Fixture;
+    use crate::memory::snapshot::SnapshotMemory;
+
+    fn resolve(fixture: Fixture) -> (SnapshotMemory, DarkSouls3Layout)
+    {
+        return fixture.resolve(SUPPORTED_VERSIONS, &[EVENT_FLAGS, IN_GAME_TIME, LOADING_STATE, PLAYER_POSITION], &[QUITOUT, CHR_DBG], DarkSouls3Layout::resolve);
+    }
+
+    #[test]
+    pub fn read_a_loaded_character()
+    {
+        let version = Version::new(1, 15, 2, 0);
+        let mut fixture = Fixture::new("darksoulsiii.exe", PATTERN_TABLE, version);
+
+        let game_data_man = fixture.object(0x100);
+        fixture.global("GameDataMan", game_data_man as u64);
+        fixture.write(game_data_man + 0xa4, &3_723_004u32.to_le_bytes());
+
+        //WorldChrMan->PlayerIns->ChrModules->ChrPhysicsModule
+        let world_chr_man = fixture.object(0x100);
+        let player_ins = fixture.object(0x100);
+        let chr_modules = fixture.object(0x100);
+        let chr_physics_module = fixture.object(0x100);
+        fixture.global("playerIns", world_chr_man as u64);
+        fixture.write(world_chr_man + 0x80, &(player_ins as u64).to_le_bytes());
+        fixture.write(player_ins + 0x40, &(chr_modules as u64).to_le_bytes());
+        fixture.write(chr_modules + 0x28, &(chr_physics_module as u64).to_le_bytes());
+        fixture.write(chr_physics_module + 0x74, &1.5f32.to_le_bytes());
+        fixture.write(chr_physics_module + 0x80, &[10.0f32.to_le_bytes(), (-2.5f32).to_le_bytes(), 300.25f32.to_le_bytes()].concat());
+
+        let event_flag_man = fixture.object(0x100);
+        fixture.global("SprjEventFlagMan", event_flag_man as u64);
+        fixture.global("Loading", 0);
+
+        let (memory, layout) = resolve(fixture);
+        assert_eq!(layout.event_flag_man(&memory), event_flag_man as u64);
+        assert_eq!(layout.in_game_time(&memory), 3_723_004);
+        assert_eq!(layout.screen_state(&memory), ScreenState::InGame);
+        assert_eq!(layout.position(&memory), Vector3f::new(10.0, -2.5, 300.25));
+        assert_eq!(layout.rotation(&memory), 1.5);
+    }
+
+    #[test]
+    pub fn loading_main_menu_and_old_versions()
+    {
+        let version = Version::new(1, 4, 0, 0);
+        let mut fixture = Fixture::new("darksoulsiii.exe", PATTERN_TABLE, version);
+
+        //Before 1.06 IGT sits at 0x9c
+        let game_data_man = fixture.object(0x100);
+        fixture.global("GameDataMan", game_data_man as u64);
+        fixture.write(game_data_man + 0x9c, &42u32.to_le_bytes());
+
+        //No PlayerIns on the main menu, and nothing at all behind the event flag manager yet
+        let world_chr_man = fixture.object(0x100);
+        fixture.global("playerIns", world_chr_man as u64);
+        fixture.global("SprjEventFlagMan", 0);
+        fixture.global("Loading", 0);
+
+        let (memory, layout) = resolve(fixture);
+        assert_eq!(layout.in_game_time(&memory), 42);
+        assert_eq!(layout.screen_state(&memory), ScreenState::MainMenu);
+        assert_eq!(layout.event_flag_man(&memory), 0);
+        assert_eq!(layout.position(&memory), Vector3f::default());
+
+        let mut fixture = Fixture::new("darksoulsiii.exe", PATTERN_TABLE, version);
+        fixture.global("Loading", 1);
+        let (memory, layout) = resolve(fixture);
+        assert_eq!(layout.screen_state(&memory), ScreenState::Loading);
+    }
+}
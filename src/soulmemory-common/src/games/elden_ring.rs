@@ -0,0 +1,276 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use crate::version::{Version, VersionSupport};
+use crate::games::resolver::*;
+use crate::screen_state::ScreenState;
+use crate::map_id::MapId;
+use crate::memory::{Memory, PointerChain};
+use crate::vector3f::Vector3f;
+
+//Where Elden Ring keeps what the dll reads and writes. Everything goes through Memory, so that the
+//pattern table and the offsets below can be tested against snapshots without the game.
+
+pub const PATTERN_TABLE: &str = include_str!("../../tables/eldenring.json");
+
+//Same versions as SoulMemory's EldenRing, 1.02 up to 1.16. 1.10 moved the file version to 2.0.0.0 and 1.12 is 2.2.0.0.
+//The versioned offsets in the table are checked per version by the tests below.
+pub const SUPPORTED_VERSIONS: &[VersionSupport] =
+&[
+    VersionSupport { min_version: Version::new(1, 2, 0, 0), max_version: Version::new(2, 6, 0, 0), capabilities: &[EVENT_FLAGS, IN_GAME_TIME, LOADING_STATE, PLAYER_POSITION] },
+];
+
+#[derive(Default)]
+pub struct EldenRingLayout
+{
+    pub virtual_memory_flag: PointerChain,
+    pub chr_dbg_flags: PointerChain,
+    fd4_time: PointerChain,
+    menu_man_imp: PointerChain,
+    screen_state_offset: usize,
+    player_ins: PointerChain,
+    map_id_offset: usize,
+    //PlayerIns->ChrModules->ChrPhysicsModule, positions are relative to the map the player is in
+    chr_physics_module: PointerChain,
+}
+
+impl EldenRingLayout
+{
+    pub fn resolve(resolver: &mut Resolver) -> Self
+    {
+        let layout = EldenRingLayout
+        {
+            virtual_memory_flag: resolver.pointer(EVENT_FLAGS, "virtual_memory_flag"),
+            //Not in the built in table, the ChrDbg pattern and flag layout haven't been checked against a snapshot of this game yet
+            //and the tests expect it to stay unavailable. Add a ChrDbgFlags symbol and a chr_dbg_flags pointer to the override file to use them.
+            chr_dbg_flags: resolver.pointer(CHR_DBG, "chr_dbg_flags"),
+            fd4_time: resolver.pointer(IN_GAME_TIME, "fd4_time"),
+            menu_man_imp: resolver.pointer(LOADING_STATE, "menu_man_imp"),
+            //The screen state moved in 1.03 and 1.12
+            screen_state_offset: resolver.value(LOADING_STATE, "screen_state", 0x730),
+            //PlayerIns moved in WorldChrMan in 1.07, and the map id in PlayerIns in 1.04 and 1.08
+            player_ins: resolver.pointer(PLAYER_POSITION, "player_ins"),
+            map_id_offset: resolver.value(PLAYER_POSITION, "map_id", 0x6d0),
+            chr_physics_module: resolver.pointer(PLAYER_POSITION, "chr_physics_module"),
+        };
+        //Quitout stays off until there is a source for the quit request field in CSMenuManImp, it shows up as unavailable in the diagnostics
+        resolver.fail(QUITOUT, "MenuManImp", String::from("quit request field not known for this game yet"));
+        return layout;
+    }
+
+    ///The flag manager that the game's flag functions take, 0 until a character is loaded
+    pub fn event_flag_man(&self, memory: &dyn Memory) -> u64
+    {
+        return self.virtual_memory_flag.resolve(memory).unwrap_or_default() as u64;
+    }
+
+    ///soulmods patches the IGT so it matches the real time spent in game
+    pub fn in_game_time(&self, memory: &dyn Memory) -> u32
+    {
+        return self.fd4_time.read_u32(memory, 0xa0).unwrap_or_default();
+    }
+
+    pub fn screen_state(&self, memory: &dyn Memory) -> ScreenState
+    {
+        return match self.menu_man_imp.read_u32(memory, self.screen_state_offset)
+        {
+            Some(0) => ScreenState::InGame,
+            Some(256) => ScreenState::MainMenu,
+            //1 while loading, other values only show up briefly between screens
+            Some(_) => ScreenState::Loading,
+            None => ScreenState::MainMenu,
+        };
+    }
+
+    pub fn position(&self, memory: &dyn Memory) -> Vector3f
+    {
+        let x = self.chr_physics_module.read_f32(memory, 0x70).unwrap_or_default();
+        let y = self.chr_physics_module.read_f32(memory, 0x74).unwrap_or_default();
+        let z = self.chr_physics_module.read_f32(memory, 0x78).unwrap_or_default();
+        return Vector3f::new(x, y, z);
+    }
+
+    pub fn set_position(&self, memory: &dyn Memory, position: &Vector3f)
+    {
+        self.chr_physics_module.write_f32(memory, 0x70, position.x);
+        self.chr_physics_module.write_f32(memory, 0x74, position.y);
+        self.chr_physics_module.write_f32(memory, 0x78, position.z);
+    }
+
+    //The orientation is a quaternion, the player only ever rotates around the vertical axis
+    pub fn rotation(&self, memory: &dyn Memory) -> f32
+    {
+        let y = self.chr_physics_module.read_f32(memory, 0x54).unwrap_or_default();
+        let w = self.chr_physics_module.read_f32(memory, 0x5c).unwrap_or_default();
+        return 2.0 * y.atan2(w);
+    }
+
+    pub fn set_rotation(&self, memory: &dyn Memory, rotation: f32)
+    {
+        self.chr_physics_module.write_f32(memory, 0x50, 0.0);
+        self.chr_physics_module.write_f32(memory, 0x54, (rotation / 2.0).sin());
+        self.chr_physics_module.write_f32(memory, 0x58, 0.0);
+        self.chr_physics_module.write_f32(memory, 0x5c, (rotation / 2.0).cos());
+    }
+
+    pub fn map_id(&self, memory: &dyn Memory) -> MapId
+    {
+        return MapId(self.player_ins.read_u32(memory, self.map_id_offset).unwrap_or_default());
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::games::elden_ring::*;
+    use crate::memory::fixture::Fixture;
+    use crate::memory::snapshot::SnapshotMemory;
+
+    fn resolve(fixture: Fixture) -> (SnapshotMemory, EldenRingLayout)
+    {
+        return fixture.resolve(SUPPORTED_VERSIONS, &[EVENT_FLAGS, IN_GAME_TIME, LOADING_STATE, PLAYER_POSITION], &[QUITOUT, CHR_DBG], EldenRingLayout::resolve);
+    }
+
+    //WorldChrMan->PlayerIns->ChrModules->ChrPhysicsModule, returns PlayerIns and ChrPhysicsModule
+    fn player(fixture: &mut Fixture, player_ins_offset: usize) -> (usize, usize)
+    {
+        let world_chr_man = fixture.object(player_ins_offset + 8);
+        let player_ins = fixture.object(0x800);
+        let chr_modules = fixture.object(0x100);
+        let chr_physics_module = fixture.object(0x100);
+        fixture.global("WorldChrMan", world_chr_man as u64);
+        fixture.write(world_chr_man + player_ins_offset, &(player_ins as u64).to_le_bytes());
+        fixture.write(player_ins + 0x190, &(chr_modules as u64).to_le_bytes());
+        fixture.write(chr_modules + 0x68, &(chr_physics_module as u64).to_le_bytes());
+        return (player_ins, chr_physics_module);
+    }
+
+    #[test]
+    pub fn read_a_loaded_character()
+    {
+        let version = Version::new(2, 6, 0, 0);
+        let mut fixture = Fixture::new("eldenring.exe", PATTERN_TABLE, version);
+
+        let fd4_time = fixture.object(0x100);
+        fixture.global("FD4Time", fd4_time as u64);
+        fixture.write(fd4_time + 0xa0, &86_400_000u32.to_le_bytes());
+
+        let menu_man_imp = fixture.object(0x800);
+        fixture.global("MenuManImp", menu_man_imp as u64);
+        fixture.write(menu_man_imp + 0x730, &0u32.to_le_bytes());
+
+        let (player_ins, chr_physics_module) = player(&mut fixture, 0x1e508);
+        fixture.write(player_ins + 0x6d0, &MapId::new(60, 42, 36, 0).0.to_le_bytes());
+        fixture.write(chr_physics_module + 0x54, &[(0.5f32).sin().to_le_bytes(), 0.0f32.to_le_bytes(), (0.5f32).cos().to_le_bytes()].concat());
+        fixture.write(chr_physics_module + 0x70, &[(-120.5f32).to_le_bytes(), 4.0f32.to_le_bytes(), 77.75f32.to_le_bytes()].concat());
+
+        //Same as SoulMemory, the instruction size stops 5 bytes short of the instruction and the pointer adds them back
+        let virtual_memory_flag = fixture.object(0x100);
+        let global = fixture.global("VirtualMemoryFlag", 0);
+        fixture.write(global + 5, &(virtual_memory_flag as u64).to_le_bytes());
+
+        let (memory, layout) = resolve(fixture);
+        assert_eq!(layout.event_flag_man(&memory), virtual_memory_flag as u64);
+        assert_eq!(layout.in_game_time(&memory), 86_400_000);
+        assert_eq!(layout.screen_state(&memory), ScreenState::InGame);
+        assert_eq!(layout.position(&memory), Vector3f::new(-120.5, 4.0, 77.75));
+        assert!((layout.rotation(&memory) - 1.0).abs() < 1e-6);
+        assert_eq!(layout.map_id(&memory).to_string(), "m60_42_36_00");
+    }
+
+    #[test]
+    pub fn offsets_before_1_07()
+    {
+        let version = Version::new(1, 2, 3, 0);
+        let mut fixture = Fixture::new("eldenring.exe", PATTERN_TABLE, version);
+
+        //The main menu, with the screen state where 1.02 keeps it
+        let menu_man_imp = fixture.object(0x800);
+        fixture.global("MenuManImp", menu_man_imp as u64);
+        fixture.write(menu_man_imp + 0x718, &256u32.to_le_bytes());
+
+        let (player_ins, chr_physics_module) = player(&mut fixture, 0x18468);
+        fixture.write(player_ins + 0x6c8, &MapId::new(10, 0, 0, 0).0.to_le_bytes());
+        fixture.write(chr_physics_module + 0x70, &[1.0f32.to_le_bytes(), 2.0f32.to_le_bytes(), 3.0f32.to_le_bytes()].concat());
+
+        fixture.global("FD4Time", 0);
+        fixture.global("VirtualMemoryFlag", 0);
+
+        let (memory, layout) = resolve(fixture);
+        assert_eq!(layout.screen_state(&memory), ScreenState::MainMenu);
+        assert_eq!(layout.position(&memory), Vector3f::new(1.0, 2.0, 3.0));
+        assert_eq!(layout.map_id(&memory).to_string(), "m10_00_00_00");
+
+        //Nothing behind the flag manager or the clock yet
+        assert_eq!(layout.event_flag_man(&memory), 0);
+        assert_eq!(layout.in_game_time(&memory), 0);
+    }
+
+    #[test]
+    pub fn screen_state_per_version()
+    {
+        //From SoulMemory's EldenRing, 2.0.0.0 is 1.10 and 2.2.0.0 is 1.12
+        for (version, screen_state_offset) in
+        [
+            (Version::new(1, 2, 0, 0), 0x718),
+            (Version::new(1, 3, 0, 0), 0x728),
+            (Version::new(1, 6, 0, 0), 0x728),
+            (Version::new(2, 0, 1, 0), 0x728),
+            (Version::new(2, 2, 0, 0), 0x730),
+            (Version::new(2, 6, 0, 0), 0x730),
+        ]
+        {
+            let mut fixture = Fixture::new("eldenring.exe", PATTERN_TABLE, version);
+            let menu_man_imp = fixture.object(0x800);
+            fixture.global("MenuManImp", menu_man_imp as u64);
+            //Loading everywhere except at the offset of this version
+            fixture.write(menu_man_imp + 0x718, &[1u8; 0x20]);
+            fixture.write(menu_man_imp + screen_state_offset, &256u32.to_le_bytes());
+            fixture.global("WorldChrMan", 0);
+            fixture.global("FD4Time", 0);
+            fixture.global("VirtualMemoryFlag", 0);
+
+            let (memory, layout) = resolve(fixture);
+            assert_eq!(layout.screen_state(&memory), ScreenState::MainMenu, "{}", version);
+        }
+    }
+
+    #[test]
+    pub fn player_offsets_per_version()
+    {
+        //Version, PlayerIns in WorldChrMan, map id in PlayerIns, from SoulMemory's EldenRing
+        for (version, player_ins_offset, map_id_offset) in
+        [
+            (Version::new(1, 3, 0, 0), 0x18468, 0x6c8),
+            (Version::new(1, 6, 0, 0), 0x18468, 0x6c0),
+            (Version::new(1, 7, 0, 0), 0x1e508, 0x6c0),
+            (Version::new(1, 9, 0, 0), 0x1e508, 0x6d0),
+            (Version::new(2, 6, 0, 0), 0x1e508, 0x6d0),
+        ]
+        {
+            let mut fixture = Fixture::new("eldenring.exe", PATTERN_TABLE, version);
+            let (player_ins, _) = player(&mut fixture, player_ins_offset);
+            fixture.write(player_ins + map_id_offset, &MapId::new(61, 44, 41, 0).0.to_le_bytes());
+            fixture.global("MenuManImp", 0);
+            fixture.global("FD4Time", 0);
+            fixture.global("VirtualMemoryFlag", 0);
+
+            let (memory, layout) = resolve(fixture);
+            assert_eq!(layout.map_id(&memory).to_string(), "m61_44_41_00", "{}", version);
+        }
+    }
+}
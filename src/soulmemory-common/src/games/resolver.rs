@@ -0,0 +1,145 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use log::warn;
+use crate::report::ResolutionReport;
+use crate::scanner::ScanReport;
+use crate::tables::PatternTable;
+use crate::version::{Version, VersionCheck, VersionSupport};
+use crate::memory::{scan_symbols, Memory, PointerChain};
+
+//Capabilities that pointers, values and functions are resolved for, same names as the server uses
+pub const EVENT_FLAGS: &str = "event flags";
+pub const IN_GAME_TIME: &str = "in game time";
+pub const LOADING_STATE: &str = "loading state";
+pub const QUITOUT: &str = "quitout";
+pub const PLAYER_POSITION: &str = "player position";
+pub const CHR_DBG: &str = "chr dbg flags";
+pub const EMEVD_LOGGER: &str = "emevd logger";
+pub const AI_TOGGLE: &str = "ai toggle";
+
+///For games that scan without a pattern table, a scan that fails is recorded in the report and disables its capability.
+///The default that is returned instead is never read, since the capability is unavailable.
+pub fn resolve_scan<T: Default>(report: &mut ResolutionReport, capability: &str, name: &str, scan: Result<T, String>) -> T
+{
+    return match scan
+    {
+        Ok(value) => value,
+        Err(e) =>
+        {
+            warn!("{} unavailable, {}: {}", capability, name, e);
+            report.fail(capability, name, e);
+            T::default()
+        }
+    };
+}
+
+///Resolves what a game needs from its pattern table, one capability at a time. Anything that can't be resolved
+///is recorded in the report and disables its capability, instead of failing the whole game.
+pub struct Resolver<'a>
+{
+    table: &'a PatternTable<Version>,
+    version: Option<&'a Version>,
+    scan: ScanReport,
+    report: ResolutionReport,
+}
+
+impl<'a> Resolver<'a>
+{
+    ///Scan for every symbol in the table at once, only fails when the game's memory can't be read at all.
+    ///Capabilities that the game's supported versions say don't work on this version are unavailable up front.
+    pub fn scan(table: &'a PatternTable<Version>, version: Option<&'a Version>, supported: &[VersionSupport], memory: &dyn Memory) -> Result<Self, String>
+    {
+        let scan = scan_symbols(memory, version, &table.symbols)?;
+        let mut report = ResolutionReport::new(&table.symbols, version, &scan);
+
+        let check = VersionCheck::new(version.copied(), supported);
+        for capability in supported.iter().flat_map(|s| s.capabilities.iter())
+        {
+            if !check.is_supported(capability) && report.is_available(capability)
+            {
+                report.fail(capability, "version", format!("not supported on version {}", check.version.unwrap_or_default()));
+            }
+        }
+        return Ok(Resolver { table, version, scan, report });
+    }
+
+    ///A default pointer chain when it can't be resolved, check is_available before reading it
+    pub fn pointer(&mut self, capability: &str, name: &str) -> PointerChain
+    {
+        return match self.table.pointer(&self.scan, name, self.version)
+        {
+            Ok((address, offsets)) => PointerChain::new(address, offsets),
+            Err(e) =>
+            {
+                self.report.fail(capability, name, e);
+                PointerChain::default()
+            }
+        };
+    }
+
+    pub fn value(&mut self, capability: &str, name: &str, default: usize) -> usize
+    {
+        return match self.table.value(name, self.version)
+        {
+            Ok(value) => value as usize,
+            Err(e) =>
+            {
+                self.report.fail(capability, name, e);
+                default
+            }
+        };
+    }
+
+    ///Address of a symbol, like a function to call or hook. 0 when it can't be resolved, check is_available before using it.
+    pub fn address(&mut self, capability: &str, name: &str) -> usize
+    {
+        return match self.scan.address(name)
+        {
+            Ok(address) => address,
+            Err(e) =>
+            {
+                self.report.fail(capability, name, e);
+                0
+            }
+        };
+    }
+
+    ///Turn a capability off that the table can't back yet, it shows up as unavailable in the diagnostics
+    pub fn fail(&mut self, capability: &str, name: &str, error: String)
+    {
+        self.report.fail(capability, name, error);
+    }
+
+    pub fn is_available(&self, capability: &str) -> bool
+    {
+        return self.report.is_available(capability);
+    }
+
+    pub fn finish(self) -> ResolutionReport
+    {
+        for symbol in self.report.symbols.iter().filter(|s| s.match_count > 1)
+        {
+            warn!("{} matched {} times, using the first match", symbol.name, symbol.match_count);
+        }
+        for failure in &self.report.failures
+        {
+            warn!("{} unavailable, {}: {}", failure.capability, failure.name, failure.error);
+        }
+        return self.report;
+    }
+}
@@ -0,0 +1,203 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use crate::version::{Version, VersionSupport};
+use crate::games::resolver::*;
+use crate::screen_state::ScreenState;
+use crate::memory::{Memory, PointerChain};
+use crate::vector3f::Vector3f;
+
+//Where Sekiro keeps what the dll reads and writes. Everything goes through Memory, so that the
+//pattern table and the offsets below can be tested against snapshots without the game.
+
+pub const PATTERN_TABLE: &str = include_str!("../../tables/sekiro.json");
+
+//1.02 up to the last patch 1.06
+pub const SUPPORTED_VERSIONS: &[VersionSupport] =
+&[
+    VersionSupport { min_version: Version::new(1, 2, 0, 0), max_version: Version::new(1, 6, 0, 0), capabilities: &[EVENT_FLAGS, IN_GAME_TIME, LOADING_STATE, QUITOUT, PLAYER_POSITION, CHR_DBG, EMEVD_LOGGER] },
+];
+
+#[derive(Default)]
+pub struct SekiroLayout
+{
+    pub event_flag_man: PointerChain,
+    pub position: PointerChain,
+    pub chr_dbg_flags: PointerChain,
+    pub menu_man: PointerChain,
+    igt: PointerChain,
+    world_chr_man: PointerChain,
+    fade_system: PointerChain,
+}
+
+impl SekiroLayout
+{
+    pub fn resolve(resolver: &mut Resolver) -> Self
+    {
+        let layout = SekiroLayout
+        {
+            event_flag_man: resolver.pointer(EVENT_FLAGS, "event_flag_man"),
+            position: resolver.pointer(PLAYER_POSITION, "position"),
+            chr_dbg_flags: resolver.pointer(CHR_DBG, "chr_dbg_flags"),
+            menu_man: resolver.pointer(LOADING_STATE, "menu_man"),
+            igt: resolver.pointer(IN_GAME_TIME, "igt"),
+            world_chr_man: resolver.pointer(LOADING_STATE, "world_chr_man"),
+            fade_system: resolver.pointer(LOADING_STATE, "fade_system"),
+        };
+        //Quitout writes through the same pointer, resolving it for quitout as well records a failure for both
+        resolver.pointer(QUITOUT, "menu_man");
+        return layout;
+    }
+
+    ///The event flag manager that the game's flag functions take, 0 before it exists
+    pub fn event_flag_man(&self, memory: &dyn Memory) -> u64
+    {
+        return self.event_flag_man.resolve(memory).unwrap_or_default() as u64;
+    }
+
+    pub fn in_game_time(&self, memory: &dyn Memory) -> u32
+    {
+        return self.igt.read_u32(memory, 0x9c).unwrap_or_default();
+    }
+
+    pub fn screen_state(&self, memory: &dyn Memory) -> ScreenState
+    {
+        //The quit request that request_quitout writes
+        if self.menu_man.read_u32(memory, 0x23c).unwrap_or_default() != 0
+        {
+            return ScreenState::Quitout;
+        }
+        if self.fade_system.read_u32(memory, 0x2dc).unwrap_or_default() != 0
+        {
+            return ScreenState::Loading;
+        }
+        if self.world_chr_man.read_u64(memory, 0x88).unwrap_or_default() == 0
+        {
+            return ScreenState::MainMenu;
+        }
+        return ScreenState::InGame;
+    }
+
+    pub fn position(&self, memory: &dyn Memory) -> Vector3f
+    {
+        let x = self.position.read_f32(memory, 0x80).unwrap_or_default();
+        let y = self.position.read_f32(memory, 0x84).unwrap_or_default();
+        let z = self.position.read_f32(memory, 0x88).unwrap_or_default();
+        return Vector3f::new(x, y, z);
+    }
+
+    pub fn set_position(&self, memory: &dyn Memory, position: &Vector3f)
+    {
+        self.position.write_f32(memory, 0x80, position.x);
+        self.position.write_f32(memory, 0x84, position.y);
+        self.position.write_f32(memory, 0x88, position.z);
+    }
+
+    ///False when the menu isn't loaded
+    pub fn request_quitout(&self, memory: &dyn Memory) -> bool
+    {
+        return self.menu_man.write_u32(memory, 0x23c, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::games::sekiro::*;
+    use crate::memory::fixture::Fixture;
+    use crate::memory::snapshot::SnapshotMemory;
+
+    fn resolve(fixture: Fixture) -> (SnapshotMemory, SekiroLayout)
+    {
+        return fixture.resolve(SUPPORTED_VERSIONS, &[EVENT_FLAGS, IN_GAME_TIME, LOADING_STATE, QUITOUT, PLAYER_POSITION, CHR_DBG, EMEVD_LOGGER], &[], SekiroLayout::resolve);
+    }
+
+    struct Game
+    {
+        fixture: Fixture,
+        menu_man: usize,
+        fade_system: usize,
+        world_chr_man: usize,
+        chr_physics_module: usize,
+        chr_dbg_flags: usize,
+    }
+
+    fn game() -> Game
+    {
+        let mut fixture = Fixture::new("sekiro.exe", PATTERN_TABLE, Version::new(1, 6, 0, 0));
+
+        let igt = fixture.object(0x100);
+        fixture.global("Igt", igt as u64);
+        fixture.write(igt + 0x9c, &5_025_500u32.to_le_bytes());
+
+        let menu_man = fixture.object(0x300);
+        fixture.global("MenuMan", menu_man as u64);
+
+        //FadeManImp->FadeSystem
+        let fade_man = fixture.object(0x100);
+        let fade_system = fixture.object(0x300);
+        fixture.global("FadeManImp", fade_man as u64);
+        fixture.write(fade_man + 0x8, &(fade_system as u64).to_le_bytes());
+
+        //WorldChrManImp->PlayerIns->ChrPhysicsModule
+        let world_chr_man = fixture.object(0x100);
+        let player_ins = fixture.object(0x100);
+        let chr_physics_module = fixture.object(0x100);
+        fixture.global("WorldChrManImp", world_chr_man as u64);
+        fixture.write(world_chr_man + 0x48, &(player_ins as u64).to_le_bytes());
+        fixture.write(player_ins + 0x28, &(chr_physics_module as u64).to_le_bytes());
+
+        fixture.global("SprjEventFlagMan", 0);
+        //The ChrDbg flags are bytes in the static itself, not behind a pointer
+        let chr_dbg_flags = fixture.global("ChrDbgFlags", 0);
+        return Game { fixture, menu_man, fade_system, world_chr_man, chr_physics_module, chr_dbg_flags };
+    }
+
+    #[test]
+    pub fn read_a_loaded_character()
+    {
+        let mut game = game();
+        game.fixture.write(game.world_chr_man + 0x88, &1u64.to_le_bytes());
+        game.fixture.write(game.chr_physics_module + 0x80, &[(-44.0f32).to_le_bytes(), 12.5f32.to_le_bytes(), 0.125f32.to_le_bytes()].concat());
+        //Player exterminate
+        game.fixture.write(game.chr_dbg_flags + 1, &[1]);
+
+        let (memory, layout) = resolve(game.fixture);
+        assert_eq!(layout.in_game_time(&memory), 5_025_500);
+        assert_eq!(layout.screen_state(&memory), ScreenState::InGame);
+        assert_eq!(layout.position(&memory), Vector3f::new(-44.0, 12.5, 0.125));
+        assert_eq!(layout.event_flag_man(&memory), 0);
+        assert_eq!(layout.chr_dbg_flags.read_u8(&memory, 0), Some(0));
+        assert_eq!(layout.chr_dbg_flags.read_u8(&memory, 1), Some(1));
+    }
+
+    #[test]
+    pub fn screen_states()
+    {
+        let state = |setup: fn(&mut Game)|
+        {
+            let mut game = game();
+            setup(&mut game);
+            let (memory, layout) = resolve(game.fixture);
+            return layout.screen_state(&memory);
+        };
+
+        assert_eq!(state(|_| {}), ScreenState::MainMenu);
+        assert_eq!(state(|game| game.fixture.write(game.fade_system + 0x2dc, &1u32.to_le_bytes())), ScreenState::Loading);
+        assert_eq!(state(|game| game.fixture.write(game.menu_man + 0x23c, &1u32.to_le_bytes())), ScreenState::Quitout);
+    }
+}
@@ -0,0 +1,99 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+///Map the player is in, packed the way the games store it: area, block, region and index, one byte each.
+///Stored as mAA_BB_CC_DD, the name of the map files.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[serde(try_from = "String", into = "String")]
+pub struct MapId(pub u32);
+
+impl MapId
+{
+    pub fn new(area: u8, block: u8, region: u8, index: u8) -> Self
+    {
+        MapId(u32::from_be_bytes([area, block, region, index]))
+    }
+
+    pub fn area(&self) -> u8
+    {
+        return self.0.to_be_bytes()[0];
+    }
+}
+
+impl Display for MapId
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let [area, block, region, index] = self.0.to_be_bytes();
+        write!(f, "m{:02}_{:02}_{:02}_{:02}", area, block, region, index)
+    }
+}
+
+impl FromStr for MapId
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let parts = s.strip_prefix('m')
+            .map(|rest| rest.split('_').map(|p| p.parse::<u8>()).collect::<Result<Vec<u8>, _>>())
+            .and_then(|parts| parts.ok())
+            .filter(|parts| parts.len() == 4)
+            .ok_or_else(|| format!("invalid map id '{}', expected mAA_BB_CC_DD", s))?;
+        return Ok(MapId::new(parts[0], parts[1], parts[2], parts[3]));
+    }
+}
+
+impl TryFrom<String> for MapId
+{
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error>
+    {
+        return value.parse();
+    }
+}
+
+impl From<MapId> for String
+{
+    fn from(value: MapId) -> Self
+    {
+        return value.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::map_id::*;
+
+    #[test]
+    pub fn map_id_round_trip()
+    {
+        let map_id = MapId::new(60, 42, 36, 0);
+        assert_eq!(map_id.to_string(), "m60_42_36_00");
+        assert_eq!("m60_42_36_00".parse::<MapId>(), Ok(map_id));
+        assert_eq!(map_id.area(), 60);
+        assert_eq!(serde_json::to_string(&map_id).unwrap(), "\"m60_42_36_00\"");
+        assert!("m60_42".parse::<MapId>().is_err());
+        assert!("60_42_36_00".parse::<MapId>().is_err());
+    }
+}
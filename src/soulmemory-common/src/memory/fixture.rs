@@ -0,0 +1,119 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::collections::HashMap;
+use crate::games::resolver::Resolver;
+use crate::scanner::Extract;
+use crate::tables::PatternTable;
+use crate::version::{Version, VersionSupport};
+use crate::memory::snapshot::SnapshotMemory;
+
+//Snapshots that look like a game to the resolver, for testing a game's reads without the game. Every symbol of a
+//pattern table is planted in the main module image with its wildcards zeroed, relative symbols can be pointed at
+//a static of their own, and heap objects are plain regions next to the image.
+
+const MODULE_BASE: usize = 0x140000000;
+const STRIDE: usize = 0x100;
+const STATIC_SIZE: usize = 0x10;
+const HEAP_BASE: usize = 0x7ff000000000;
+
+pub(crate) struct Fixture
+{
+    module_name: String,
+    table: PatternTable<Version>,
+    version: Version,
+    image: Vec<u8>,
+    statics: usize,
+    symbols: HashMap<String, (usize, Extract)>,
+    regions: Vec<(usize, Vec<u8>)>,
+}
+
+impl Fixture
+{
+    ///Plants the first variant of every symbol in a game's table json that applies to the version
+    pub fn new(module_name: &str, table: &str, version: Version) -> Self
+    {
+        let table = PatternTable::<Version>::parse(table).unwrap();
+        let statics = STRIDE * (table.symbols.len() + 1);
+        let mut image = vec![0u8; statics + STATIC_SIZE * table.symbols.len()];
+        let mut symbols = HashMap::new();
+
+        for (i, symbol) in table.symbols.iter().enumerate()
+        {
+            let variant = symbol.variants.iter().find(|v| v.applies_to(Some(&version))).unwrap();
+            let offset = STRIDE * (i + 1);
+            for (j, token) in variant.pattern.to_string().split(' ').enumerate()
+            {
+                image[offset + j] = u8::from_str_radix(token, 16).unwrap_or(0);
+            }
+            symbols.insert(symbol.name.clone(), (offset, variant.extract));
+        }
+        return Fixture { module_name: module_name.to_string(), table, version, image, statics, symbols, regions: Vec::new() };
+    }
+
+    ///Give a relative symbol a static of its own holding the value, usually the address of an object.
+    ///Returns the address of the static.
+    pub fn global(&mut self, symbol: &str, value: u64) -> usize
+    {
+        let (offset, extract) = self.symbols[symbol];
+        let Extract::Relative { operand_offset, instruction_size } = extract else { panic!("{} is not a relative symbol", symbol) };
+
+        let index = self.symbols.keys().filter(|name| name.as_str() < symbol).count();
+        let address = MODULE_BASE + self.statics + STATIC_SIZE * index;
+        let relative = (address as i64 - (MODULE_BASE + offset + instruction_size) as i64) as i32;
+        self.image[offset + operand_offset..offset + operand_offset + 4].copy_from_slice(&relative.to_le_bytes());
+        self.write(address, &value.to_le_bytes());
+        return address;
+    }
+
+    ///A zeroed heap object, returns its address
+    pub fn object(&mut self, size: usize) -> usize
+    {
+        let address = HEAP_BASE + 0x100000 * self.regions.len();
+        self.regions.push((address, vec![0u8; size]));
+        return address;
+    }
+
+    ///Write into the image or an object
+    pub fn write(&mut self, address: usize, bytes: &[u8])
+    {
+        let (base, data) = std::iter::once((MODULE_BASE, &mut self.image))
+            .chain(self.regions.iter_mut().map(|(base, data)| (*base, data)))
+            .find(|(base, data)| address >= *base && address + bytes.len() <= *base + data.len())
+            .unwrap_or_else(|| panic!("0x{:x} is not in the fixture", address));
+        data[address - base..address - base + bytes.len()].copy_from_slice(bytes);
+    }
+
+    ///Snapshot the fixture and resolve a layout from it the way the game does. Asserts that the capabilities in
+    ///available resolved and the ones in unavailable were turned off.
+    pub fn resolve<T>(self, supported: &[VersionSupport], available: &[&str], unavailable: &[&str], layout: fn(&mut Resolver) -> T) -> (SnapshotMemory, T)
+    {
+        let memory = SnapshotMemory::from_regions(&self.module_name, true, MODULE_BASE, self.image, self.regions);
+        let mut resolver = Resolver::scan(&self.table, Some(&self.version), supported, &memory).unwrap();
+        let layout = layout(&mut resolver);
+        let report = resolver.finish();
+        for capability in available
+        {
+            assert!(report.is_available(capability), "{} on {}\n{}", capability, self.version, report.to_text());
+        }
+        for capability in unavailable
+        {
+            assert!(!report.is_available(capability), "{} on {}", capability, self.version);
+        }
+        return (memory, layout);
+    }
+}
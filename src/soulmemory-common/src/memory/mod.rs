@@ -0,0 +1,241 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::scanner::{self, Pattern, ScanReport, Symbol};
+
+pub mod snapshot;
+#[cfg(test)]
+pub(crate) mod fixture;
+
+//Access to game memory that doesn't care where the bytes come from: the live process the dll is
+//injected in, or a snapshot file captured from it. Game logic written against Memory can run in tests
+//on any platform, against fixtures.
+
+///A loaded module, the image is what patterns are scanned in
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ModuleInfo
+{
+    pub name: String,
+    pub base: usize,
+    pub size: usize,
+}
+
+pub trait Memory
+{
+    ///Fill the buffer from the given address, false when any of it is not readable
+    fn read(&self, address: usize, buffer: &mut [u8]) -> bool;
+
+    ///Write the buffer to the given address, false when any of it is not writable. Snapshots are read only.
+    fn write(&self, _address: usize, _buffer: &[u8]) -> bool
+    {
+        return false;
+    }
+
+    ///The executable of the game
+    fn main_module(&self) -> Option<ModuleInfo>;
+
+    fn is_64_bit(&self) -> bool;
+
+    ///Copy of the main module's image, None when it can't be read
+    fn main_module_image(&self) -> Option<(ModuleInfo, Vec<u8>)>
+    {
+        let module = self.main_module()?;
+        let mut image = vec![0u8; module.size];
+        if !self.read(module.base, &mut image)
+        {
+            return None;
+        }
+        return Some((module, image));
+    }
+
+    fn read_u8(&self, address: usize) -> Option<u8>
+    {
+        let mut buffer = [0u8; 1];
+        return self.read(address, &mut buffer).then_some(buffer[0]);
+    }
+
+    fn read_u32(&self, address: usize) -> Option<u32>
+    {
+        let mut buffer = [0u8; 4];
+        return self.read(address, &mut buffer).then(|| u32::from_le_bytes(buffer));
+    }
+
+    fn read_i32(&self, address: usize) -> Option<i32>
+    {
+        return self.read_u32(address).map(|v| v as i32);
+    }
+
+    fn read_u64(&self, address: usize) -> Option<u64>
+    {
+        let mut buffer = [0u8; 8];
+        return self.read(address, &mut buffer).then(|| u64::from_le_bytes(buffer));
+    }
+
+    fn read_f32(&self, address: usize) -> Option<f32>
+    {
+        return self.read_u32(address).map(f32::from_bits);
+    }
+
+    ///Pointer sized read
+    fn read_pointer(&self, address: usize) -> Option<usize>
+    {
+        return if self.is_64_bit() { self.read_u64(address).map(|v| v as usize) } else { self.read_u32(address).map(|v| v as usize) };
+    }
+}
+
+///A static address followed by a chain of offsets, every offset is added and then dereferenced.
+///Same semantics as mem_rs pointers and SoulMemory's CreatePointer: an empty chain is the static address itself.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct PointerChain
+{
+    pub base: usize,
+    pub offsets: Vec<usize>,
+}
+
+impl PointerChain
+{
+    pub fn new(base: usize, offsets: Vec<usize>) -> Self
+    {
+        PointerChain { base, offsets }
+    }
+
+    ///Follow the chain, None when a link can't be read or is null
+    pub fn resolve(&self, memory: &dyn Memory) -> Option<usize>
+    {
+        let mut address = self.base;
+        for offset in &self.offsets
+        {
+            address = memory.read_pointer(address + offset)?;
+            if address == 0
+            {
+                return None;
+            }
+        }
+        return Some(address);
+    }
+
+    pub fn read_u8(&self, memory: &dyn Memory, offset: usize) -> Option<u8>
+    {
+        return memory.read_u8(self.resolve(memory)? + offset);
+    }
+
+    pub fn read_u32(&self, memory: &dyn Memory, offset: usize) -> Option<u32>
+    {
+        return memory.read_u32(self.resolve(memory)? + offset);
+    }
+
+    pub fn read_u64(&self, memory: &dyn Memory, offset: usize) -> Option<u64>
+    {
+        return memory.read_u64(self.resolve(memory)? + offset);
+    }
+
+    pub fn read_f32(&self, memory: &dyn Memory, offset: usize) -> Option<f32>
+    {
+        return memory.read_f32(self.resolve(memory)? + offset);
+    }
+
+    ///Fill the buffer from the end of the chain, false when the chain or the memory can't be read
+    pub fn read_bytes(&self, memory: &dyn Memory, offset: usize, buffer: &mut [u8]) -> bool
+    {
+        return self.resolve(memory).is_some_and(|address| memory.read(address + offset, buffer));
+    }
+
+    pub fn write_u8(&self, memory: &dyn Memory, offset: usize, value: u8) -> bool
+    {
+        return self.resolve(memory).is_some_and(|address| memory.write(address + offset, &[value]));
+    }
+
+    pub fn write_u32(&self, memory: &dyn Memory, offset: usize, value: u32) -> bool
+    {
+        return self.resolve(memory).is_some_and(|address| memory.write(address + offset, &value.to_le_bytes()));
+    }
+
+    pub fn write_f32(&self, memory: &dyn Memory, offset: usize, value: f32) -> bool
+    {
+        return self.write_u32(memory, offset, value.to_bits());
+    }
+}
+
+///Find the first match of a pattern like "48 8b 05 ? ? ? ?" in a buffer
+pub fn find_pattern(buffer: &[u8], pattern: &str) -> Option<usize>
+{
+    return Pattern::parse(pattern).ok()?.find(buffer);
+}
+
+///Scan the main module for every symbol in one pass, the report lists all symbols that are missing
+pub fn scan_symbols<V: PartialOrd>(memory: &dyn Memory, version: Option<&V>, symbols: &[Symbol<V>]) -> Result<ScanReport, String>
+{
+    let (module, image) = memory.main_module_image().ok_or_else(|| String::from("main module is not readable"))?;
+    return Ok(scanner::scan(&image, module.base, version, symbols));
+}
+
+///Scan the main module for an instruction with a rip relative operand and follow it, like mem_rs' scan_rel
+pub fn scan_rel(memory: &dyn Memory, pattern: &str, address_offset: usize, instruction_size: usize, offsets: Vec<usize>) -> Result<PointerChain, String>
+{
+    let (module, image) = memory.main_module_image().ok_or_else(|| String::from("main module is not readable"))?;
+    let index = find_pattern(&image, pattern).ok_or_else(|| format!("pattern not found: {}", pattern))?;
+    let operand = image.get(index + address_offset..index + address_offset + 4).ok_or_else(|| format!("operand out of range: {}", pattern))?;
+    let relative = i32::from_le_bytes(operand.try_into().unwrap());
+    let base = (module.base + index + instruction_size).wrapping_add_signed(relative as isize);
+    return Ok(PointerChain::new(base, offsets));
+}
+
+///Scan the main module for a pattern and use the address of the match, like mem_rs' scan_abs
+pub fn scan_abs(memory: &dyn Memory, pattern: &str, scan_offset: usize, offsets: Vec<usize>) -> Result<PointerChain, String>
+{
+    let (module, image) = memory.main_module_image().ok_or_else(|| String::from("main module is not readable"))?;
+    let index = find_pattern(&image, pattern).ok_or_else(|| format!("pattern not found: {}", pattern))?;
+    return Ok(PointerChain::new(module.base + index + scan_offset, offsets));
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::memory::*;
+    use crate::memory::snapshot::SnapshotMemory;
+
+    #[test]
+    pub fn find_pattern_with_wildcards()
+    {
+        let buffer = [0x00, 0x48, 0x8b, 0x05, 0x10, 0x20, 0x30, 0x40, 0x48];
+        assert_eq!(find_pattern(&buffer, "48 8b 05 ? ? ? ?"), Some(1));
+        assert_eq!(find_pattern(&buffer, "8b ? 10"), Some(2));
+        assert_eq!(find_pattern(&buffer, "48 8b 06"), None);
+    }
+
+    #[test]
+    pub fn scan_rel_and_follow_a_pointer_chain()
+    {
+        //mov rax, [rip + 0x100] at 0x1010 points at the static at 0x1117, which holds a pointer to an object
+        let module_base = 0x1000;
+        let mut image = vec![0u8; 0x200];
+        image[0x10..0x17].copy_from_slice(&[0x48, 0x8b, 0x05, 0x00, 0x01, 0x00, 0x00]);
+        image[0x117..0x11f].copy_from_slice(&0x5000u64.to_le_bytes());
+
+        let mut heap = vec![0u8; 0x100];
+        heap[0x9c..0xa0].copy_from_slice(&123456u32.to_le_bytes());
+
+        let memory = SnapshotMemory::from_regions("game.exe", true, module_base, image, vec![(0x5000, heap)]);
+        let game_data_man = scan_rel(&memory, "48 8b 05 ? ? ? ?", 3, 7, vec![0]).unwrap();
+        assert_eq!(game_data_man.base, 0x1117);
+        assert_eq!(game_data_man.resolve(&memory), Some(0x5000));
+        assert_eq!(game_data_man.read_u32(&memory, 0x9c), Some(123456));
+
+        //Reads outside the captured regions fail instead of returning garbage
+        assert_eq!(game_data_man.read_u32(&memory, 0x100), None);
+        assert!(scan_abs(&memory, "de ad be ef", 0, Vec::new()).is_err());
+    }
+}
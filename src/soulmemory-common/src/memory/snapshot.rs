@@ -0,0 +1,224 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fs;
+use std::path::Path;
+use crate::memory::{Memory, ModuleInfo};
+
+//Snapshot files hold the main module image and any number of other regions, typically heap objects that
+//pointer chains lead to. Little endian binary layout:
+//  "SMSNAP01"                      magic
+//  u8                              1 for 64 bit processes, 0 for 32 bit
+//  u32 + utf8                      main module name
+//  u64 u64                         main module base and size
+//  u32                             region count
+//  per region: u64 u64 + bytes     base, size and the bytes, the first region is the module image
+
+const MAGIC: &[u8; 8] = b"SMSNAP01";
+
+///Memory backend that serves reads from captured regions, for running game logic without the game
+pub struct SnapshotMemory
+{
+    module: ModuleInfo,
+    is_64_bit: bool,
+    //Sorted by base address
+    regions: Vec<(usize, Vec<u8>)>,
+}
+
+impl SnapshotMemory
+{
+    pub fn from_regions(module_name: &str, is_64_bit: bool, module_base: usize, image: Vec<u8>, mut regions: Vec<(usize, Vec<u8>)>) -> Self
+    {
+        let module = ModuleInfo { name: module_name.to_string(), base: module_base, size: image.len() };
+        regions.push((module_base, image));
+        regions.sort_by_key(|(base, _)| *base);
+        SnapshotMemory { module, is_64_bit, regions }
+    }
+
+    ///Copy the main module and the given (address, size) ranges out of another backend, usually the live process
+    pub fn capture(memory: &dyn Memory, ranges: &[(usize, usize)]) -> Result<Self, String>
+    {
+        let (module, image) = memory.main_module_image().ok_or_else(|| String::from("main module is not readable"))?;
+        let mut regions = Vec::new();
+        for (address, size) in ranges
+        {
+            let mut buffer = vec![0u8; *size];
+            if !memory.read(*address, &mut buffer)
+            {
+                return Err(format!("0x{:x}..0x{:x} is not readable", address, address + size));
+            }
+            regions.push((*address, buffer));
+        }
+        return Ok(Self::from_regions(&module.name, memory.is_64_bit(), module.base, image, regions));
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String>
+    {
+        let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        return Self::parse(&bytes).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String>
+    {
+        if let Some(directory) = path.parent()
+        {
+            fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+        return fs::write(path, self.to_bytes()).map_err(|e| e.to_string());
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(self.is_64_bit as u8);
+        bytes.extend_from_slice(&(self.module.name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.module.name.as_bytes());
+        bytes.extend_from_slice(&(self.module.base as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.module.size as u64).to_le_bytes());
+
+        //Module image first, so that a reader can stop after it when only patterns matter
+        let mut regions: Vec<&(usize, Vec<u8>)> = self.regions.iter().collect();
+        regions.sort_by_key(|(base, data)| (*base != self.module.base || data.len() != self.module.size, *base));
+
+        bytes.extend_from_slice(&(regions.len() as u32).to_le_bytes());
+        for (base, data) in regions
+        {
+            bytes.extend_from_slice(&(*base as u64).to_le_bytes());
+            bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(data);
+        }
+        return bytes;
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, String>
+    {
+        let mut reader = Reader { bytes, position: 0 };
+        if reader.take(MAGIC.len())? != MAGIC
+        {
+            return Err(String::from("not a memory snapshot"));
+        }
+
+        let is_64_bit = reader.take(1)?[0] == 1;
+        let name_length = reader.u32()? as usize;
+        let name = String::from_utf8(reader.take(name_length)?.to_vec()).map_err(|e| e.to_string())?;
+        let module = ModuleInfo { name, base: reader.u64()? as usize, size: reader.u64()? as usize };
+
+        let count = reader.u32()?;
+        let mut regions = Vec::new();
+        for _ in 0..count
+        {
+            let base = reader.u64()? as usize;
+            let size = reader.u64()? as usize;
+            regions.push((base, reader.take(size)?.to_vec()));
+        }
+        regions.sort_by_key(|(base, _)| *base);
+
+        if !regions.iter().any(|(base, data)| *base == module.base && data.len() == module.size)
+        {
+            return Err(String::from("the main module image is missing"));
+        }
+        return Ok(SnapshotMemory { module, is_64_bit, regions });
+    }
+}
+
+impl Memory for SnapshotMemory
+{
+    fn read(&self, address: usize, buffer: &mut [u8]) -> bool
+    {
+        //Last region that starts at or before the address
+        let index = match self.regions.partition_point(|(base, _)| *base <= address)
+        {
+            0 => return false,
+            i => i - 1,
+        };
+
+        let (base, data) = &self.regions[index];
+        let start = address - base;
+        match data.get(start..start + buffer.len())
+        {
+            Some(bytes) =>
+            {
+                buffer.copy_from_slice(bytes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn main_module(&self) -> Option<ModuleInfo>
+    {
+        return Some(self.module.clone());
+    }
+
+    fn is_64_bit(&self) -> bool
+    {
+        return self.is_64_bit;
+    }
+}
+
+struct Reader<'a>
+{
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a>
+{
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String>
+    {
+        let bytes = self.bytes.get(self.position..self.position + count).ok_or_else(|| format!("truncated at byte {}", self.position))?;
+        self.position += count;
+        return Ok(bytes);
+    }
+
+    fn u32(&mut self) -> Result<u32, String>
+    {
+        return Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()));
+    }
+
+    fn u64(&mut self) -> Result<u64, String>
+    {
+        return Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::memory::snapshot::*;
+
+    #[test]
+    pub fn file_round_trip_and_capture()
+    {
+        let memory = SnapshotMemory::from_regions("game.exe", false, 0x400000, vec![1, 2, 3, 4], vec![(0x1000, vec![0xff; 16])]);
+        let parsed = SnapshotMemory::parse(&memory.to_bytes()).unwrap();
+        assert_eq!(parsed.main_module(), Some(ModuleInfo { name: String::from("game.exe"), base: 0x400000, size: 4 }));
+        assert!(!parsed.is_64_bit());
+        assert_eq!(parsed.read_u32(0x400000), Some(0x04030201));
+        assert_eq!(parsed.read_pointer(0x1000), Some(0xffffffff));
+        assert_eq!(parsed.read_u32(0x100e), None);
+
+        let captured = SnapshotMemory::capture(&parsed, &[(0x1004, 4)]).unwrap();
+        assert_eq!(captured.read_u8(0x1004), Some(0xff));
+        assert_eq!(captured.read_u8(0x1000), None);
+        assert!(SnapshotMemory::capture(&parsed, &[(0x2000, 4)]).is_err());
+
+        assert!(SnapshotMemory::parse(b"SMSNAP01").is_err());
+        assert!(SnapshotMemory::parse(b"nonsense").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use serde::{Deserialize, Serialize};
+use crate::scanner::{ScanReport, Symbol};
+
+//What happened when a game's patterns were resolved, for the diagnostics panel, the server and bug reports.
+//Symbols are scanned all at once, then every pointer, value and function is resolved for the capability that
+//needs it. A failure disables that capability only, the rest of the game keeps working.
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SymbolResolution
+{
+    pub name: String,
+    //The pattern that matched, or every pattern that was tried
+    pub patterns: Vec<String>,
+    pub variant: Option<usize>,
+    pub address: Option<usize>,
+    pub match_count: usize,
+    pub success: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ResolutionFailure
+{
+    pub capability: String,
+    //Pointer, value or symbol that could not be resolved
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ResolutionReport
+{
+    pub symbols: Vec<SymbolResolution>,
+    pub failures: Vec<ResolutionFailure>,
+}
+
+impl ResolutionReport
+{
+    pub fn new<V: PartialOrd>(symbols: &[Symbol<V>], version: Option<&V>, scan: &ScanReport) -> Self
+    {
+        let symbols = symbols.iter().map(|symbol| match scan.get(&symbol.name)
+        {
+            Some(found) => SymbolResolution
+            {
+                name: symbol.name.clone(),
+                patterns: vec![found.pattern.clone()],
+                variant: Some(found.variant),
+                address: Some(found.address),
+                match_count: found.match_count,
+                success: true,
+            },
+            None => SymbolResolution
+            {
+                name: symbol.name.clone(),
+                patterns: symbol.variants.iter().filter(|v| v.applies_to(version)).map(|v| v.pattern.to_string()).collect(),
+                variant: None,
+                address: None,
+                match_count: 0,
+                success: false,
+            },
+        }).collect();
+
+        return ResolutionReport { symbols, failures: Vec::new() };
+    }
+
+    pub fn fail(&mut self, capability: &str, name: &str, error: String)
+    {
+        self.failures.push(ResolutionFailure { capability: capability.to_string(), name: name.to_string(), error });
+    }
+
+    pub fn is_available(&self, capability: &str) -> bool
+    {
+        return !self.failures.iter().any(|f| f.capability == capability);
+    }
+
+    ///Capabilities that failed, in the order they failed
+    pub fn unavailable(&self) -> Vec<String>
+    {
+        let mut capabilities: Vec<String> = Vec::new();
+        for failure in &self.failures
+        {
+            if !capabilities.contains(&failure.capability)
+            {
+                capabilities.push(failure.capability.clone());
+            }
+        }
+        return capabilities;
+    }
+
+    pub fn is_complete(&self) -> bool
+    {
+        return self.failures.is_empty() && self.symbols.iter().all(|s| s.success);
+    }
+
+    ///Plain text version, to paste into a bug report
+    pub fn to_text(&self) -> String
+    {
+        let mut text = String::new();
+        for symbol in &self.symbols
+        {
+            match symbol.address
+            {
+                Some(address) => text.push_str(&format!("ok      {} at 0x{:x}, variant {}, {} match(es): {}\n", symbol.name, address, symbol.variant.unwrap_or(0), symbol.match_count, symbol.patterns.join(" | "))),
+                None => text.push_str(&format!("missing {}: {}\n", symbol.name, symbol.patterns.join(" | "))),
+            }
+        }
+        for failure in &self.failures
+        {
+            text.push_str(&format!("failed  {} ({}): {}\n", failure.capability, failure.name, failure.error));
+        }
+        return text;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::report::*;
+    use crate::scanner::{scan, PatternVariant};
+
+    #[test]
+    pub fn report_matches_misses_and_failed_capabilities()
+    {
+        let buffer = [0x48, 0x8b, 0x05, 0x10, 0x00, 0x00, 0x00, 0x48, 0x8b, 0x05];
+        let symbols: Vec<Symbol<u32>> = vec!
+        [
+            Symbol::new("WorldChrMan", vec![PatternVariant::relative("48 8b 05 ? ? ? ?", 3, 7).unwrap()]),
+            Symbol::new("FD4Time", vec![PatternVariant::absolute("aa bb", 0).unwrap().versions(Some(2), None), PatternVariant::absolute("cc dd", 0).unwrap().versions(None, Some(1))]),
+        ];
+
+        let mut report = ResolutionReport::new(&symbols, Some(&1), &scan(&buffer, 0x1000, Some(&1), &symbols));
+        assert_eq!(report.symbols[0].address, Some(0x1017));
+        assert_eq!(report.symbols[0].match_count, 1);
+        assert_eq!(report.symbols[1].patterns, vec![String::from("cc dd")]);
+        assert!(!report.symbols[1].success);
+
+        report.fail("in game time", "fd4_time", String::from("symbol not found: FD4Time"));
+        assert!(!report.is_available("in game time"));
+        assert!(report.is_available("event flags"));
+        assert_eq!(report.unavailable(), vec![String::from("in game time")]);
+        assert!(report.to_text().ends_with("failed  in game time (fd4_time): symbol not found: FD4Time\n"));
+    }
+}
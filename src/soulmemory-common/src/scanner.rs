@@ -0,0 +1,343 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fmt;
+use std::fmt::Display;
+
+//Array of bytes scanner. Every symbol has a list of pattern variants: the first one is preferred, later ones are
+//fallbacks, and a variant can be limited to a range of game versions. All variants of all symbols are matched in a
+//single pass over the buffer, so that a game patch reports every broken symbol at once.
+
+///A pattern like "48 8b 05 ? ? ? ?", None is a wildcard
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Pattern
+{
+    bytes: Vec<Option<u8>>,
+}
+
+impl Pattern
+{
+    pub fn parse(pattern: &str) -> Result<Self, String>
+    {
+        let mut bytes = Vec::new();
+        for token in pattern.split_whitespace()
+        {
+            if token.chars().all(|c| c == '?')
+            {
+                bytes.push(None);
+                continue;
+            }
+            let byte = u8::from_str_radix(token, 16).map_err(|_| format!("invalid byte '{}' in pattern '{}'", token, pattern))?;
+            bytes.push(Some(byte));
+        }
+
+        if bytes.iter().all(|b| b.is_none())
+        {
+            return Err(format!("pattern '{}' has no bytes to match", pattern));
+        }
+        return Ok(Pattern { bytes });
+    }
+
+    pub fn len(&self) -> usize
+    {
+        return self.bytes.len();
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        return self.bytes.is_empty();
+    }
+
+    pub fn matches_at(&self, buffer: &[u8], index: usize) -> bool
+    {
+        return match buffer.get(index..index + self.bytes.len())
+        {
+            Some(window) => window.iter().zip(&self.bytes).all(|(b, p)| p.is_none_or(|p| p == *b)),
+            None => false,
+        };
+    }
+
+    ///Index of the first match
+    pub fn find(&self, buffer: &[u8]) -> Option<usize>
+    {
+        return (0..buffer.len()).find(|i| self.matches_at(buffer, *i));
+    }
+
+    //First byte that is not a wildcard, used to index the pattern in a single pass scan
+    fn anchor(&self) -> (usize, u8)
+    {
+        return self.bytes.iter().enumerate().find_map(|(i, b)| b.map(|b| (i, b))).unwrap();
+    }
+}
+
+impl Display for Pattern
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let tokens: Vec<String> = self.bytes.iter().map(|b| b.map(|b| format!("{:02x}", b)).unwrap_or_else(|| String::from("?"))).collect();
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+///How the address of a symbol is taken from a match
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Extract
+{
+    ///The address of the match plus an offset
+    Absolute { offset: isize },
+    ///An instruction with a 32 bit relative operand, like mov rax, [rip + x]: the address the operand points at
+    Relative { operand_offset: usize, instruction_size: usize },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatternVariant<V>
+{
+    pub pattern: Pattern,
+    pub extract: Extract,
+    //Inclusive, None is unbounded
+    pub min_version: Option<V>,
+    pub max_version: Option<V>,
+}
+
+impl<V: PartialOrd> PatternVariant<V>
+{
+    pub fn new(pattern: &str, extract: Extract) -> Result<Self, String>
+    {
+        return Ok(PatternVariant { pattern: Pattern::parse(pattern)?, extract, min_version: None, max_version: None });
+    }
+
+    pub fn absolute(pattern: &str, offset: isize) -> Result<Self, String>
+    {
+        return Self::new(pattern, Extract::Absolute { offset });
+    }
+
+    pub fn relative(pattern: &str, operand_offset: usize, instruction_size: usize) -> Result<Self, String>
+    {
+        return Self::new(pattern, Extract::Relative { operand_offset, instruction_size });
+    }
+
+    pub fn versions(mut self, min_version: Option<V>, max_version: Option<V>) -> Self
+    {
+        self.min_version = min_version;
+        self.max_version = max_version;
+        return self;
+    }
+
+    pub fn applies_to(&self, version: Option<&V>) -> bool
+    {
+        return in_version_range(version, self.min_version.as_ref(), self.max_version.as_ref());
+    }
+}
+
+///Inclusive on both ends, a missing bound is unbounded and an unknown version is in every range
+pub fn in_version_range<V: PartialOrd>(version: Option<&V>, min_version: Option<&V>, max_version: Option<&V>) -> bool
+{
+    let version = match version
+    {
+        Some(version) => version,
+        None => return true,
+    };
+    return min_version.is_none_or(|min| version >= min) && max_version.is_none_or(|max| version <= max);
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Symbol<V>
+{
+    pub name: String,
+    //In order of preference
+    pub variants: Vec<PatternVariant<V>>,
+}
+
+impl<V> Symbol<V>
+{
+    pub fn new(name: &str, variants: Vec<PatternVariant<V>>) -> Self
+    {
+        Symbol { name: name.to_string(), variants }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SymbolMatch
+{
+    pub name: String,
+    //Index of the variant that matched
+    pub variant: usize,
+    pub pattern: String,
+    //Where the pattern matched, relative to the start of the buffer
+    pub offset: usize,
+    //Extracted address, including the base address of the buffer
+    pub address: usize,
+    //How often the variant matched, more than one means the pattern is ambiguous and the first match was used
+    pub match_count: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ScanReport
+{
+    pub found: Vec<SymbolMatch>,
+    //Symbols without a matching variant, in the order they were given
+    pub missing: Vec<String>,
+}
+
+impl ScanReport
+{
+    pub fn get(&self, name: &str) -> Option<&SymbolMatch>
+    {
+        return self.found.iter().find(|m| m.name == name);
+    }
+
+    pub fn address(&self, name: &str) -> Result<usize, String>
+    {
+        return self.get(name).map(|m| m.address).ok_or_else(|| format!("symbol not found: {}", name));
+    }
+
+    pub fn is_complete(&self) -> bool
+    {
+        return self.missing.is_empty();
+    }
+}
+
+///Scan a buffer that starts at base for every symbol at once
+pub fn scan<V: PartialOrd>(buffer: &[u8], base: usize, version: Option<&V>, symbols: &[Symbol<V>]) -> ScanReport
+{
+    //Variants that apply to this version, indexed by the first byte they match on
+    let mut by_anchor: Vec<Vec<(usize, usize, usize)>> = vec![Vec::new(); 256];
+    for (s, symbol) in symbols.iter().enumerate()
+    {
+        for (v, variant) in symbol.variants.iter().enumerate()
+        {
+            if variant.applies_to(version)
+            {
+                let (anchor_index, anchor_byte) = variant.pattern.anchor();
+                by_anchor[anchor_byte as usize].push((s, v, anchor_index));
+            }
+        }
+    }
+
+    //(first match, match count) per symbol and variant
+    let mut matches: Vec<Vec<(Option<usize>, usize)>> = symbols.iter().map(|s| vec![(None, 0); s.variants.len()]).collect();
+    for (i, byte) in buffer.iter().enumerate()
+    {
+        for (s, v, anchor_index) in &by_anchor[*byte as usize]
+        {
+            if i < *anchor_index
+            {
+                continue;
+            }
+            let start = i - anchor_index;
+            if symbols[*s].variants[*v].pattern.matches_at(buffer, start)
+            {
+                let entry = &mut matches[*s][*v];
+                entry.0.get_or_insert(start);
+                entry.1 += 1;
+            }
+        }
+    }
+
+    let mut report = ScanReport::default();
+    for (s, symbol) in symbols.iter().enumerate()
+    {
+        let found = symbol.variants.iter().enumerate().find_map(|(v, variant)|
+        {
+            let (offset, match_count) = matches[s][v];
+            let offset = offset?;
+            let address = extract(buffer, base, offset, variant.extract)?;
+            Some(SymbolMatch { name: symbol.name.clone(), variant: v, pattern: variant.pattern.to_string(), offset, address, match_count })
+        });
+
+        match found
+        {
+            Some(found) => report.found.push(found),
+            None => report.missing.push(symbol.name.clone()),
+        }
+    }
+    return report;
+}
+
+fn extract(buffer: &[u8], base: usize, offset: usize, extract: Extract) -> Option<usize>
+{
+    return match extract
+    {
+        Extract::Absolute { offset: extra } => (base + offset).checked_add_signed(extra),
+        Extract::Relative { operand_offset, instruction_size } =>
+        {
+            let operand = buffer.get(offset + operand_offset..offset + operand_offset + 4)?;
+            let relative = i32::from_le_bytes(operand.try_into().unwrap());
+            (base + offset + instruction_size).checked_add_signed(relative as isize)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::scanner::*;
+
+    #[test]
+    pub fn parse_patterns()
+    {
+        let pattern = Pattern::parse("48 8B 05 ? ?? 00").unwrap();
+        assert_eq!(pattern.len(), 6);
+        assert_eq!(pattern.to_string(), "48 8b 05 ? ? 00");
+        assert_eq!(pattern.find(&[0x00, 0x48, 0x8b, 0x05, 0xaa, 0xbb, 0x00]), Some(1));
+        assert!(Pattern::parse("48 zz").is_err());
+        assert!(Pattern::parse("? ?").is_err());
+    }
+
+    #[test]
+    pub fn scan_many_symbols_in_one_pass()
+    {
+        //0x10: mov rax, [rip + 0x20], 0x30: a function prologue that shows up twice
+        let mut buffer = vec![0xccu8; 0x80];
+        buffer[0x10..0x17].copy_from_slice(&[0x48, 0x8b, 0x05, 0x20, 0x00, 0x00, 0x00]);
+        buffer[0x30..0x34].copy_from_slice(&[0x40, 0x53, 0x48, 0x83]);
+        buffer[0x50..0x54].copy_from_slice(&[0x40, 0x53, 0x48, 0x83]);
+
+        let symbols: Vec<Symbol<u32>> = vec!
+        [
+            Symbol::new("GameDataMan", vec![PatternVariant::relative("48 8b 05 ? ? ? ?", 3, 7).unwrap()]),
+            Symbol::new("get_event_flag", vec![PatternVariant::absolute("? 53 48 83", -0x10).unwrap()]),
+            Symbol::new("broken", vec![PatternVariant::absolute("de ad be ef", 0).unwrap()]),
+        ];
+
+        let report = scan(&buffer, 0x1000, None, &symbols);
+        assert_eq!(report.address("GameDataMan"), Ok(0x1000 + 0x17 + 0x20));
+        assert_eq!(report.get("get_event_flag").map(|m| (m.offset, m.address, m.match_count)), Some((0x30, 0x1020, 2)));
+        assert_eq!(report.missing, vec![String::from("broken")]);
+        assert!(!report.is_complete());
+    }
+
+    #[test]
+    pub fn fallbacks_and_version_variants()
+    {
+        let buffer = [0x8b, 0x83, 0x64, 0x02, 0x00, 0x00];
+        let symbols: Vec<Symbol<u32>> = vec!
+        [
+            Symbol::new("fps", vec!
+            [
+                PatternVariant::absolute("8b 83 6c 02 00 00", 0).unwrap().versions(None, Some(1)),
+                PatternVariant::absolute("8b 83 64 02 00 00", 0).unwrap().versions(Some(2), None),
+            ]),
+        ];
+
+        assert_eq!(scan(&buffer, 0, Some(&2), &symbols).get("fps").map(|m| m.variant), Some(1));
+        //The only variant for version 1 doesn't match
+        assert_eq!(scan(&buffer, 0, Some(&1), &symbols).missing, vec![String::from("fps")]);
+        //Without a version every variant is tried, in order
+        assert_eq!(scan(&buffer, 0, None, &symbols).get("fps").map(|m| m.variant), Some(1));
+    }
+}
@@ -0,0 +1,357 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use serde::Deserialize;
+use crate::scanner::{in_version_range, Extract, Pattern, PatternVariant, ScanReport, Symbol};
+
+//Patterns, pointer chains and plain offsets for one game, kept in json files instead of code so that a game patch
+//can be fixed with an override file next to the dll, without waiting for a rebuild.
+//
+//{
+//  "symbols": [{ "name": "WorldChrMan", "variants": [{ "pattern": "48 8b 05 ? ? ? ?", "operand_offset": 3, "instruction_size": 7 }] }],
+//  "pointers": [{ "name": "player_ins", "symbol": "WorldChrMan", "offsets": ["0x0", "0x1e508"], "min_version": "1.6" }],
+//  "values": [{ "name": "screen_state", "value": "0x728" }]
+//}
+//
+//Variants without an operand offset use the address of the match plus "offset". Pointers and values can have several
+//entries with the same name, the first one whose version range contains the game version is used. Numbers can be
+//written as json numbers or as strings, "0x" strings are hexadecimal.
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawNumber
+{
+    Number(i64),
+    Text(String),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawVariant
+{
+    pattern: String,
+    offset: Option<RawNumber>,
+    operand_offset: Option<RawNumber>,
+    instruction_size: Option<RawNumber>,
+    min_version: Option<String>,
+    max_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSymbol
+{
+    name: String,
+    variants: Vec<RawVariant>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPointer
+{
+    name: String,
+    symbol: String,
+    #[serde(default)]
+    offsets: Vec<RawNumber>,
+    min_version: Option<String>,
+    max_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawValue
+{
+    name: String,
+    value: RawNumber,
+    min_version: Option<String>,
+    max_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTable
+{
+    #[serde(default)]
+    symbols: Vec<RawSymbol>,
+    #[serde(default)]
+    pointers: Vec<RawPointer>,
+    #[serde(default)]
+    values: Vec<RawValue>,
+}
+
+///A static address from a symbol, followed by a chain of offsets
+#[derive(Debug, PartialEq, Clone)]
+pub struct PointerEntry<V>
+{
+    pub name: String,
+    pub symbol: String,
+    pub offsets: Vec<usize>,
+    pub min_version: Option<V>,
+    pub max_version: Option<V>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValueEntry<V>
+{
+    pub name: String,
+    pub value: i64,
+    pub min_version: Option<V>,
+    pub max_version: Option<V>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatternTable<V>
+{
+    pub symbols: Vec<Symbol<V>>,
+    pub pointers: Vec<PointerEntry<V>>,
+    pub values: Vec<ValueEntry<V>>,
+}
+
+impl<V: PartialOrd + FromStr> PatternTable<V>
+{
+    ///Parse and validate a table, errors name the entry that is wrong
+    pub fn parse(json: &str) -> Result<Self, String>
+    {
+        let raw = serde_json::from_str::<RawTable>(json).map_err(|e| e.to_string())?;
+        return Self::from_raw(raw, &[]);
+    }
+
+    ///Load the table that is built into the dll, replaced entry by entry with the override file when it exists
+    pub fn load(builtin: &str, override_path: &Path) -> Result<Self, String>
+    {
+        let table = Self::parse(builtin).map_err(|e| format!("built in pattern table: {}", e))?;
+        if !override_path.exists()
+        {
+            return Ok(table);
+        }
+
+        //Pointers in the override file may use symbols that are only in the built in table
+        let known_symbols: Vec<&str> = table.symbols.iter().map(|s| s.name.as_str()).collect();
+        let overrides = fs::read_to_string(override_path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| serde_json::from_str::<RawTable>(&json).map_err(|e| e.to_string()))
+            .and_then(|raw| Self::from_raw(raw, &known_symbols))
+            .map_err(|e| format!("{}: {}", override_path.display(), e))?;
+        return Ok(table.merge(overrides));
+    }
+
+    fn from_raw(raw: RawTable, known_symbols: &[&str]) -> Result<Self, String>
+    {
+        let mut symbols: Vec<Symbol<V>> = Vec::new();
+        for raw_symbol in raw.symbols
+        {
+            if symbols.iter().any(|s| s.name == raw_symbol.name)
+            {
+                return Err(format!("symbol '{}' is defined twice", raw_symbol.name));
+            }
+            if raw_symbol.variants.is_empty()
+            {
+                return Err(format!("symbol '{}' has no variants", raw_symbol.name));
+            }
+
+            let mut variants = Vec::new();
+            for (index, raw_variant) in raw_symbol.variants.into_iter().enumerate()
+            {
+                variants.push(parse_variant(raw_variant).map_err(|e| format!("symbol '{}' variant {}: {}", raw_symbol.name, index, e))?);
+            }
+            symbols.push(Symbol { name: raw_symbol.name, variants });
+        }
+
+        let mut pointers = Vec::new();
+        for raw_pointer in raw.pointers
+        {
+            if !symbols.iter().any(|s| s.name == raw_pointer.symbol) && !known_symbols.contains(&raw_pointer.symbol.as_str())
+            {
+                return Err(format!("pointer '{}': unknown symbol '{}'", raw_pointer.name, raw_pointer.symbol));
+            }
+
+            let (min_version, max_version) = parse_version_range(raw_pointer.min_version, raw_pointer.max_version).map_err(|e| format!("pointer '{}': {}", raw_pointer.name, e))?;
+            let mut offsets = Vec::new();
+            for offset in &raw_pointer.offsets
+            {
+                let offset = parse_number(offset).and_then(|o| usize::try_from(o).map_err(|_| format!("negative offset {}", o))).map_err(|e| format!("pointer '{}': {}", raw_pointer.name, e))?;
+                offsets.push(offset);
+            }
+            pointers.push(PointerEntry { name: raw_pointer.name, symbol: raw_pointer.symbol, offsets, min_version, max_version });
+        }
+
+        let mut values = Vec::new();
+        for raw_value in raw.values
+        {
+            let (min_version, max_version) = parse_version_range(raw_value.min_version, raw_value.max_version).map_err(|e| format!("value '{}': {}", raw_value.name, e))?;
+            let value = parse_number(&raw_value.value).map_err(|e| format!("value '{}': {}", raw_value.name, e))?;
+            values.push(ValueEntry { name: raw_value.name, value, min_version, max_version });
+        }
+
+        return Ok(PatternTable { symbols, pointers, values });
+    }
+}
+
+impl<V: PartialOrd> PatternTable<V>
+{
+    ///Where the override file for a game is, next to the dll: soulmods.eldenring.json
+    pub fn override_path(dll_path: &Path, game: &str) -> PathBuf
+    {
+        let stem = dll_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        return dll_path.with_file_name(format!("{}.{}.json", stem, game));
+    }
+
+    ///Every name in the overrides replaces all entries with that name
+    pub fn merge(mut self, overrides: PatternTable<V>) -> Self
+    {
+        self.symbols.retain(|s| !overrides.symbols.iter().any(|o| o.name == s.name));
+        self.symbols.extend(overrides.symbols);
+        self.pointers.retain(|p| !overrides.pointers.iter().any(|o| o.name == p.name));
+        self.pointers.extend(overrides.pointers);
+        self.values.retain(|v| !overrides.values.iter().any(|o| o.name == v.name));
+        self.values.extend(overrides.values);
+        return self;
+    }
+
+    ///The static address and offsets of a pointer, for process.create_pointer
+    pub fn pointer(&self, report: &ScanReport, name: &str, version: Option<&V>) -> Result<(usize, Vec<usize>), String>
+    {
+        let entry = self.pointers.iter()
+            .find(|p| p.name == name && in_version_range(version, p.min_version.as_ref(), p.max_version.as_ref()))
+            .ok_or_else(|| format!("no pointer '{}' for this version", name))?;
+        return Ok((report.address(&entry.symbol)?, entry.offsets.clone()));
+    }
+
+    pub fn value(&self, name: &str, version: Option<&V>) -> Result<i64, String>
+    {
+        return self.values.iter()
+            .find(|v| v.name == name && in_version_range(version, v.min_version.as_ref(), v.max_version.as_ref()))
+            .map(|v| v.value)
+            .ok_or_else(|| format!("no value '{}' for this version", name));
+    }
+}
+
+fn parse_variant<V: PartialOrd + FromStr>(raw: RawVariant) -> Result<PatternVariant<V>, String>
+{
+    let pattern = Pattern::parse(&raw.pattern)?;
+    let extract = match (&raw.offset, &raw.operand_offset, &raw.instruction_size)
+    {
+        (None, Some(operand_offset), Some(instruction_size)) =>
+        {
+            let operand_offset = parse_number(operand_offset)? as usize;
+            if operand_offset + 4 > pattern.len()
+            {
+                return Err(format!("operand offset {} is outside of the pattern", operand_offset));
+            }
+            Extract::Relative { operand_offset, instruction_size: parse_number(instruction_size)? as usize }
+        }
+        (offset, None, None) => Extract::Absolute { offset: offset.as_ref().map(parse_number).transpose()?.unwrap_or(0) as isize },
+        _ => return Err(String::from("use either offset, or operand_offset with instruction_size")),
+    };
+
+    let (min_version, max_version) = parse_version_range(raw.min_version, raw.max_version)?;
+    return Ok(PatternVariant { pattern, extract, min_version, max_version });
+}
+
+fn parse_version_range<V: PartialOrd + FromStr>(min_version: Option<String>, max_version: Option<String>) -> Result<(Option<V>, Option<V>), String>
+{
+    let parse = |version: Option<String>| version.map(|v| v.parse::<V>().map_err(|_| format!("invalid version '{}'", v))).transpose();
+    let min_version = parse(min_version)?;
+    let max_version = parse(max_version)?;
+    if let (Some(min), Some(max)) = (&min_version, &max_version) && min > max
+    {
+        return Err(String::from("min_version is higher than max_version"));
+    }
+    return Ok((min_version, max_version));
+}
+
+fn parse_number(number: &RawNumber) -> Result<i64, String>
+{
+    let text = match number
+    {
+        RawNumber::Number(number) => return Ok(*number),
+        RawNumber::Text(text) => text.trim(),
+    };
+
+    let (negative, digits) = match text.strip_prefix('-')
+    {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let value = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => digits.parse::<i64>(),
+    };
+    return value.map(|v| if negative { -v } else { v }).map_err(|_| format!("invalid number '{}'", text));
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::tables::*;
+
+    const TABLE: &str = r#"{
+        "symbols":
+        [
+            { "name": "WorldChrMan", "variants": [{ "pattern": "48 8b 05 ? ? ? ?", "operand_offset": 3, "instruction_size": 7 }] },
+            { "name": "fps", "variants": [{ "pattern": "8b 83 64 02", "offset": "-0x10", "min_version": "2" }, { "pattern": "8b 83 6c 02" }] }
+        ],
+        "pointers":
+        [
+            { "name": "player_ins", "symbol": "WorldChrMan", "offsets": ["0x0", "0x1e508"], "min_version": "6" },
+            { "name": "player_ins", "symbol": "WorldChrMan", "offsets": [0, 99432] }
+        ],
+        "values": [{ "name": "screen_state", "value": "0x728" }]
+    }"#;
+
+    #[test]
+    pub fn parse_and_pick_entries_by_version()
+    {
+        let table = PatternTable::<u32>::parse(TABLE).unwrap();
+        assert_eq!(table.symbols[1].variants[0].extract, Extract::Absolute { offset: -0x10 });
+        assert_eq!(table.symbols[1].variants[0].min_version, Some(2));
+
+        let report = ScanReport { found: vec![crate::scanner::SymbolMatch { name: String::from("WorldChrMan"), variant: 0, pattern: String::new(), offset: 0, address: 0x1000, match_count: 1 }], missing: Vec::new() };
+        assert_eq!(table.pointer(&report, "player_ins", Some(&7)), Ok((0x1000, vec![0, 0x1e508])));
+        assert_eq!(table.pointer(&report, "player_ins", Some(&5)), Ok((0x1000, vec![0, 0x18468])));
+        assert_eq!(table.value("screen_state", None), Ok(0x728));
+        assert!(table.value("missing", None).is_err());
+    }
+
+    #[test]
+    pub fn validation_errors_name_the_entry()
+    {
+        let error = |json: &str| PatternTable::<u32>::parse(json).unwrap_err();
+        assert_eq!(error(r#"{"symbols": [{"name": "a", "variants": [{"pattern": "zz"}]}]}"#), "symbol 'a' variant 0: invalid byte 'zz' in pattern 'zz'");
+        assert_eq!(error(r#"{"symbols": [{"name": "a", "variants": [{"pattern": "48 8b", "operand_offset": 3, "instruction_size": 7}]}]}"#), "symbol 'a' variant 0: operand offset 3 is outside of the pattern");
+        assert_eq!(error(r#"{"pointers": [{"name": "p", "symbol": "nope"}]}"#), "pointer 'p': unknown symbol 'nope'");
+        assert_eq!(error(r#"{"values": [{"name": "v", "value": "0x", "min_version": "3", "max_version": "2"}]}"#), "value 'v': min_version is higher than max_version");
+        assert!(error(r#"{"symbol": []}"#).contains("unknown field"));
+    }
+
+    #[test]
+    pub fn overrides_replace_entries_by_name()
+    {
+        let table = PatternTable::<u32>::parse(TABLE).unwrap();
+        let overrides = PatternTable::<u32>::parse(r#"{"values": [{ "name": "screen_state", "value": 1 }, { "name": "new", "value": 2 }]}"#).unwrap();
+        let merged = table.merge(overrides);
+        assert_eq!(merged.value("screen_state", None), Ok(1));
+        assert_eq!(merged.value("new", None), Ok(2));
+        assert_eq!(merged.symbols.len(), 2);
+
+        assert_eq!(PatternTable::<u32>::override_path(Path::new("C:/tools/soulmods.dll"), "eldenring"), PathBuf::from("C:/tools/soulmods.eldenring.json"));
+    }
+}
@@ -16,8 +16,9 @@
 
 use std::fmt;
 use std::fmt::Display;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Vector3f
 {
     pub x: f32,
@@ -47,4 +48,10 @@ impl Vector3f
     {
         Vector3f{ x, y, z}
     }
+
+    pub fn distance(&self, other: &Vector3f) -> f32
+    {
+        let (dx, dy, dz) = (self.x - other.x, self.y - other.y, self.z - other.z);
+        return (dx * dx + dy * dy + dz * dz).sqrt();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,481 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+//The file version of a game's executable, read straight from the PE version resource instead of through
+//GetFileVersionInfoW, so that it works without windows and can be tested against any exe on disk.
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Version
+{
+    pub major: u16,
+    pub minor: u16,
+    pub build: u16,
+    pub revision: u16,
+}
+
+impl Display for Version
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.major, self.minor, self.build, self.revision)
+    }
+}
+
+//"1.6.0.0", missing parts are 0 so "1.6" is the same version
+impl FromStr for Version
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let mut parts = [0u16; 4];
+        let split: Vec<&str> = s.trim().split('.').collect();
+        if split.len() > 4
+        {
+            return Err(format!("invalid version '{}'", s));
+        }
+        for (i, part) in split.iter().enumerate()
+        {
+            parts[i] = part.parse::<u16>().map_err(|_| format!("invalid version '{}'", s))?;
+        }
+        return Ok(Version { major: parts[0], minor: parts[1], build: parts[2], revision: parts[3] });
+    }
+}
+
+//Serialized as "1.6.0.0" for the server
+impl Serialize for Version
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        return serializer.serialize_str(&self.to_string());
+    }
+}
+
+impl<'de> Deserialize<'de> for Version
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        let s = String::deserialize(deserializer)?;
+        return Version::from_str(&s).map_err(serde::de::Error::custom);
+    }
+}
+
+const RT_VERSION: u32 = 16;
+const VS_FIXEDFILEINFO_SIGNATURE: u32 = 0xfeef04bd;
+
+impl Version
+{
+    pub const fn new(major: u16, minor: u16, build: u16, revision: u16) -> Self
+    {
+        Version { major, minor, build, revision }
+    }
+
+    ///File version of an exe or dll, the default version when it can't be read
+    pub fn from_file_version_info(path: PathBuf) -> Self
+    {
+        return Version::from_file(&path).unwrap_or_default();
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String>
+    {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        return Version::from_pe(&mut BufReader::new(file)).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    ///Read the file version from the VS_FIXEDFILEINFO in a PE image's version resource.
+    ///Only the headers and the version resource are read, not the whole image.
+    pub fn from_pe<R: Read + Seek>(reader: &mut R) -> Result<Self, String>
+    {
+        let dos_header = read_at(reader, 0, 0x40)?;
+        if &dos_header[0..2] != b"MZ"
+        {
+            return Err(String::from("not a PE image, missing MZ header"));
+        }
+
+        let pe_offset = u32_at(&dos_header, 0x3c)? as u64;
+        let pe_header = read_at(reader, pe_offset, 24)?;
+        if &pe_header[0..4] != b"PE\0\0"
+        {
+            return Err(String::from("not a PE image, missing PE header"));
+        }
+        let section_count = u16_at(&pe_header, 6)? as usize;
+        let optional_header_size = u16_at(&pe_header, 20)? as usize;
+
+        //The data directories sit at a different offset in 32 and 64 bit images
+        let optional_header = read_at(reader, pe_offset + 24, optional_header_size)?;
+        let data_directories = match u16_at(&optional_header, 0)?
+        {
+            0x10b => 96,
+            0x20b => 112,
+            magic => return Err(format!("unknown optional header magic 0x{:x}", magic)),
+        };
+        if u32_at(&optional_header, data_directories - 4)? <= 2
+        {
+            return Err(String::from("no resource directory"));
+        }
+        let resource_rva = u32_at(&optional_header, data_directories + 2 * 8)?;
+        let resource_size = u32_at(&optional_header, data_directories + 2 * 8 + 4)? as usize;
+        if resource_rva == 0 || resource_size == 0
+        {
+            return Err(String::from("no resource directory"));
+        }
+
+        let section_table = read_at(reader, pe_offset + 24 + optional_header_size as u64, section_count * 40)?;
+        let file_offset = |rva: u32| -> Result<u64, String>
+        {
+            for section in section_table.chunks(40)
+            {
+                let virtual_size = u32_at(section, 8)?;
+                let virtual_address = u32_at(section, 12)?;
+                let raw_size = u32_at(section, 16)?;
+                let raw_pointer = u32_at(section, 20)?;
+                if rva >= virtual_address && rva < virtual_address + virtual_size.max(raw_size)
+                {
+                    return Ok((raw_pointer + (rva - virtual_address)) as u64);
+                }
+            }
+            return Err(format!("rva 0x{:x} is not in any section", rva));
+        };
+
+        //Resource type -> name -> language, the first name and language are used
+        let resources = read_at(reader, file_offset(resource_rva)?, resource_size)?;
+        let names = resource_entry(&resources, 0, Some(RT_VERSION))?.ok_or("no version resource")?;
+        let languages = resource_entry(&resources, subdirectory(names)?, None)?.ok_or("empty version resource")?;
+        let data_entry = resource_entry(&resources, subdirectory(languages)?, None)?.ok_or("empty version resource")?;
+        if data_entry & 0x80000000 != 0
+        {
+            return Err(String::from("unexpected resource directory, expected version data"));
+        }
+        let data_rva = u32_at(&resources, data_entry as usize)?;
+        let data_size = u32_at(&resources, data_entry as usize + 4)? as usize;
+        let version_info = read_at(reader, file_offset(data_rva)?, data_size)?;
+
+        //VS_VERSIONINFO: length, value length and type, the "VS_VERSION_INFO" key, then VS_FIXEDFILEINFO aligned to 4 bytes
+        let mut offset = 6;
+        while u16_at(&version_info, offset)? != 0
+        {
+            offset += 2;
+        }
+        let fixed_file_info = (offset + 2 + 3) & !3;
+        if u32_at(&version_info, fixed_file_info)? != VS_FIXEDFILEINFO_SIGNATURE
+        {
+            return Err(String::from("version resource has no fixed file info"));
+        }
+
+        let file_version_ms = u32_at(&version_info, fixed_file_info + 8)?;
+        let file_version_ls = u32_at(&version_info, fixed_file_info + 12)?;
+        return Ok(Version
+        {
+            major: (file_version_ms >> 16) as u16,
+            minor: file_version_ms as u16,
+            build: (file_version_ls >> 16) as u16,
+            revision: file_version_ls as u16,
+        });
+    }
+}
+
+impl PartialEq for Version
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        return self.major == other.major && self.minor == other.minor && self.build == other.build && self.revision == other.revision;
+    }
+}
+
+impl PartialOrd for Version
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+    {
+        if self.major > other.major
+        {
+            return Some(Ordering::Greater);
+        }
+        else if self.major < other.major
+        {
+            return Some(Ordering::Less);
+        }
+
+        if self.minor > other.minor
+        {
+            return Some(Ordering::Greater);
+        }
+        else if self.minor < other.minor
+        {
+            return Some(Ordering::Less);
+        }
+
+        if self.build > other.build
+        {
+            return Some(Ordering::Greater);
+        }
+        else if self.build < other.build
+        {
+            return Some(Ordering::Less);
+        }
+
+        if self.revision > other.revision
+        {
+            return Some(Ordering::Greater);
+        }
+        else if self.revision < other.revision
+        {
+            return Some(Ordering::Less);
+        }
+
+        return Some(Ordering::Equal);
+    }
+}
+
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, size: usize) -> Result<Vec<u8>, String>
+{
+    let mut buffer = vec![0u8; size];
+    reader.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+    reader.read_exact(&mut buffer).map_err(|_| format!("truncated image, can't read 0x{:x} bytes at 0x{:x}", size, offset))?;
+    return Ok(buffer);
+}
+
+fn u16_at(buffer: &[u8], offset: usize) -> Result<u16, String>
+{
+    return buffer.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]])).ok_or_else(|| format!("truncated data at 0x{:x}", offset));
+}
+
+fn u32_at(buffer: &[u8], offset: usize) -> Result<u32, String>
+{
+    return buffer.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).ok_or_else(|| format!("truncated data at 0x{:x}", offset));
+}
+
+//OffsetToData of the entry with the given id in the resource directory at offset, or of its first entry
+fn resource_entry(resources: &[u8], offset: usize, id: Option<u32>) -> Result<Option<u32>, String>
+{
+    let count = u16_at(resources, offset + 12)? as usize + u16_at(resources, offset + 14)? as usize;
+    for i in 0..count
+    {
+        let entry = offset + 16 + i * 8;
+        let name = u32_at(resources, entry)?;
+        if id.is_none() || id == Some(name)
+        {
+            return Ok(Some(u32_at(resources, entry + 4)?));
+        }
+    }
+    return Ok(None);
+}
+
+fn subdirectory(offset_to_data: u32) -> Result<usize, String>
+{
+    if offset_to_data & 0x80000000 == 0
+    {
+        return Err(String::from("unexpected resource data, expected a directory"));
+    }
+    return Ok((offset_to_data & 0x7fffffff) as usize);
+}
+
+///Versions a game's patterns and offsets were verified against, both inclusive, with the capabilities that work on them
+pub struct VersionSupport
+{
+    pub min_version: Version,
+    pub max_version: Version,
+    pub capabilities: &'static [&'static str],
+}
+
+///The detected version checked against a game's supported versions, for the overlay and the server
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct VersionCheck
+{
+    pub version: Option<Version>,
+    //Capabilities known to work on the detected version, None when the version is not in any supported range
+    pub capabilities: Option<Vec<String>>,
+    pub warnings: Vec<String>,
+}
+
+impl VersionCheck
+{
+    pub fn new(version: Option<Version>, supported: &[VersionSupport]) -> Self
+    {
+        let mut check = VersionCheck { version, capabilities: None, warnings: Vec::new() };
+        let version = match version
+        {
+            Some(version) => version,
+            None =>
+            {
+                check.warnings.push(String::from("the game version could not be detected"));
+                return check;
+            }
+        };
+
+        //Games that don't declare their versions are never warned about
+        if supported.is_empty()
+        {
+            return check;
+        }
+
+        match supported.iter().find(|s| version >= s.min_version && version <= s.max_version)
+        {
+            Some(support) =>
+            {
+                let mut missing: Vec<&str> = Vec::new();
+                for capability in supported.iter().flat_map(|s| s.capabilities.iter())
+                {
+                    if !support.capabilities.contains(capability) && !missing.contains(capability)
+                    {
+                        missing.push(capability);
+                    }
+                }
+                if !missing.is_empty()
+                {
+                    check.warnings.push(format!("not supported on version {}: {}", version, missing.join(", ")));
+                }
+                check.capabilities = Some(support.capabilities.iter().map(|c| c.to_string()).collect());
+            }
+            None =>
+            {
+                let latest = supported.iter().map(|s| s.max_version).fold(supported[0].max_version, |a, b| if b > a { b } else { a });
+                let oldest = supported.iter().map(|s| s.min_version).fold(supported[0].min_version, |a, b| if b < a { b } else { a });
+                if version > latest
+                {
+                    check.warnings.push(format!("version {} is newer than the latest supported version {}, patterns may not match", version, latest));
+                }
+                else if version < oldest
+                {
+                    check.warnings.push(format!("version {} is older than the oldest supported version {}", version, oldest));
+                }
+                else
+                {
+                    check.warnings.push(format!("version {} is not a supported version", version));
+                }
+            }
+        }
+        return check;
+    }
+
+    ///Untested versions are tried anyway, only capabilities known not to work on a supported version are turned off
+    pub fn is_supported(&self, capability: &str) -> bool
+    {
+        return match &self.capabilities
+        {
+            Some(capabilities) => capabilities.iter().any(|c| c == capability),
+            None => true,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::io::Cursor;
+    use crate::version::*;
+
+    //A 64 bit image with a single .rsrc section that only holds a version resource
+    fn pe_image(file_version_ms: u32, file_version_ls: u32) -> Vec<u8>
+    {
+        let mut image = vec![0u8; 0x300];
+        let mut put = |offset: usize, bytes: &[u8]| image[offset..offset + bytes.len()].copy_from_slice(bytes);
+
+        put(0, b"MZ");
+        put(0x3c, &0x40u32.to_le_bytes());
+        put(0x40, b"PE\0\0");
+        put(0x46, &1u16.to_le_bytes());
+        put(0x54, &240u16.to_le_bytes());
+        put(0x58, &0x20bu16.to_le_bytes());
+        put(0x58 + 108, &16u32.to_le_bytes());
+        put(0x58 + 112 + 16, &0x1000u32.to_le_bytes());
+        put(0x58 + 112 + 20, &0x100u32.to_le_bytes());
+
+        let section = 0x58 + 240;
+        put(section, b".rsrc\0\0\0");
+        put(section + 8, &0x100u32.to_le_bytes());
+        put(section + 12, &0x1000u32.to_le_bytes());
+        put(section + 16, &0x100u32.to_le_bytes());
+        put(section + 20, &0x200u32.to_le_bytes());
+
+        //Type, name and language directories, then the data entry and VS_VERSIONINFO
+        put(0x200 + 14, &1u16.to_le_bytes());
+        put(0x200 + 16, &16u32.to_le_bytes());
+        put(0x200 + 20, &0x80000018u32.to_le_bytes());
+        put(0x218 + 14, &1u16.to_le_bytes());
+        put(0x218 + 16, &1u32.to_le_bytes());
+        put(0x218 + 20, &0x80000030u32.to_le_bytes());
+        put(0x230 + 14, &1u16.to_le_bytes());
+        put(0x230 + 16, &0x409u32.to_le_bytes());
+        put(0x230 + 20, &0x48u32.to_le_bytes());
+        put(0x248, &0x1058u32.to_le_bytes());
+        put(0x24c, &0x5cu32.to_le_bytes());
+
+        let key: Vec<u8> = "VS_VERSION_INFO\0".encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+        put(0x258, &0x5cu16.to_le_bytes());
+        put(0x25a, &52u16.to_le_bytes());
+        put(0x25e, &key);
+        put(0x280, &VS_FIXEDFILEINFO_SIGNATURE.to_le_bytes());
+        put(0x288, &file_version_ms.to_le_bytes());
+        put(0x28c, &file_version_ls.to_le_bytes());
+        return image;
+    }
+
+    #[test]
+    pub fn read_version_from_pe_image()
+    {
+        let version = Version::from_pe(&mut Cursor::new(pe_image(0x0002_0006, 0x0000_0001))).unwrap();
+        assert_eq!(version, Version::new(2, 6, 0, 1));
+        assert_eq!(version.to_string(), "2.6.0.1");
+
+        let mut image = pe_image(0x0002_0006, 0);
+        image[0x200 + 16] = 3; //RT_ICON instead of RT_VERSION
+        assert_eq!(Version::from_pe(&mut Cursor::new(image)), Err(String::from("no version resource")));
+        assert!(Version::from_pe(&mut Cursor::new(vec![0u8; 0x10])).is_err());
+    }
+
+    #[test]
+    pub fn parse_and_compare()
+    {
+        assert_eq!(Version::from_str("1.6").unwrap(), Version::new(1, 6, 0, 0));
+        assert!(Version::from_str("1.2.3.4.5").is_err());
+        assert!(Version::new(2, 0, 0, 0) > Version::new(1, 16, 2, 0));
+        assert_eq!(serde_json::to_string(&Version::new(1, 15, 2, 0)).unwrap(), "\"1.15.2.0\"");
+    }
+
+    #[test]
+    pub fn check_against_supported_versions()
+    {
+        let supported =
+        [
+            VersionSupport { min_version: Version::new(1, 2, 0, 0), max_version: Version::new(1, 9, 1, 0), capabilities: &["event flags"] },
+            VersionSupport { min_version: Version::new(2, 0, 0, 0), max_version: Version::new(2, 6, 0, 0), capabilities: &["event flags", "in game time"] },
+        ];
+
+        let check = VersionCheck::new(Some(Version::new(2, 2, 0, 0)), &supported);
+        assert!(check.warnings.is_empty());
+        assert!(check.is_supported("in game time"));
+
+        let check = VersionCheck::new(Some(Version::new(1, 5, 0, 0)), &supported);
+        assert_eq!(check.warnings, vec![String::from("not supported on version 1.5.0.0: in game time")]);
+        assert!(!check.is_supported("in game time"));
+
+        let check = VersionCheck::new(Some(Version::new(2, 7, 0, 0)), &supported);
+        assert_eq!(check.warnings, vec![String::from("version 2.7.0.0 is newer than the latest supported version 2.6.0.0, patterns may not match")]);
+        assert!(check.is_supported("in game time"));
+
+        assert_eq!(VersionCheck::new(Some(Version::new(1, 9, 5, 0)), &supported).warnings, vec![String::from("version 1.9.5.0 is not a supported version")]);
+        assert_eq!(VersionCheck::new(None, &supported).warnings.len(), 1);
+        assert!(VersionCheck::new(Some(Version::new(9, 0, 0, 0)), &[]).warnings.is_empty());
+    }
+}
@@ -14,11 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use chrono::Local;
+use log::{info, warn};
 use windows::Win32::Foundation::HINSTANCE;
-use imgui::{Condition, Context, Ui};
+use imgui::{Condition, Context, Ui, WindowFlags};
 use crate::widgets::widget::Widget;
-use crate::util::server::Server;
+use crate::util::server::{ClientId, EventFlagReading, Request, Response, Server};
+use crate::games::traits::buffered_event_flags::EventFlagValue;
 use crate::widgets::ai_toggle_widget::AiToggleWidget;
 use crate::widgets::basic_position_widget::PlayerPositionWidget;
 use crate::widgets::chr_dbg_flags_widget::ChrDbgFlagsWidget;
@@ -26,14 +32,56 @@ use crate::widgets::event_flag_widget::EventFlagWidget;
 use crate::widgets::misc_widget::MiscWidget;
 use crate::games::*;
 use crate::widgets::emevd_logger_widget::EmevdLoggerWidget;
+use crate::widgets::great_rune_widget::GreatRuneWidget;
+use crate::widgets::check_tracker_widget::CheckTrackerWidget;
+use crate::widgets::splits_widget::SplitsWidget;
+use crate::widgets::livesplit_widget::LiveSplitWidget;
+use crate::widgets::in_game_time_widget::InGameTimeWidget;
+use crate::widgets::trace_widget::TraceWidget;
+use crate::widgets::diagnostics_widget::DiagnosticsWidget;
+use crate::event_flags::names::EventFlagNames;
+use crate::event_flags::journal::EventFlagJournal;
+use crate::util::config::Config;
+use crate::util::{game_key, game_version, DATA_DIRECTORY};
+use crate::memory::Memory;
+use crate::memory::live::LiveMemory;
+use crate::memory::snapshot::SnapshotMemory;
+use soulmemory_common::version::VersionCheck;
+use crate::triggers::engine::{FiredTrigger, TriggerEngine};
+use crate::triggers::rule::Action;
+use crate::splits::splitter::{SplitInput, Splitter};
+use crate::splits::livesplit::LiveSplitOutput;
+use crate::games::traits::buffered_event_flags::EventFlag;
+use crate::games::traits::loading_state::ScreenStateTracker;
+use crate::positions::trace::TraceRecorder;
 
 pub struct App
 {
     pub game: Box<dyn Game>,
     pub hmodule: HINSTANCE,
-    #[allow(dead_code)]
     server: Server,
+    event_flag_names: Arc<EventFlagNames>,
+    event_flag_journal: Option<EventFlagJournal>,
     widgets: Vec<Box<dyn Widget>>,
+    triggers_path: PathBuf,
+    triggers: TriggerEngine,
+    //Overlay alerts raised by triggers and when they disappear
+    alerts: Vec<(String, Instant)>,
+    splits_path: PathBuf,
+    //Shared with the splits widget
+    splitter: Arc<Mutex<Splitter>>,
+    //Shared with the livesplit widget, which configures the connection
+    livesplit: Arc<Mutex<LiveSplitOutput>>,
+    screen_state: ScreenStateTracker,
+    //Shared with the trace widget, which starts and stops recordings
+    trace: Arc<Mutex<TraceRecorder>>,
+    config_path: PathBuf,
+    //Last config that was loaded or written to disk
+    config: Config,
+    //Off when a broken config file couldn't be moved aside, so that it isn't overwritten with defaults
+    config_writable: bool,
+    //Detected game version checked against the versions the game supports
+    version_check: VersionCheck,
 }
 
 impl App
@@ -81,31 +129,437 @@ impl App
             _                           => panic!("unsupported process: {}", process_name.to_lowercase()),
         };
 
+        let event_flag_names = Arc::new(EventFlagNames::load(process_name));
+        let splits_path = Splitter::path(process_name);
+        let splitter = Arc::new(Mutex::new(Self::load_splitter(&splits_path)));
+        let livesplit = Arc::new(Mutex::new(LiveSplitOutput::new()));
+        let trace = Arc::new(Mutex::new(TraceRecorder::new()));
+        let version_check = VersionCheck::new(game_version(), game.supported_versions());
+        let game_version = version_check.version.map(|version| version.to_string()).unwrap_or_default();
+        info!("game version: {}", game_version);
+        for warning in &version_check.warnings
+        {
+            warn!("{}", warning);
+        }
+
         //get drawable widgets
         //let widgets = game.get_widgets();
 
-        App
+        let mut app = App
         {
             game,
             hmodule,
             server: Server::new(String::from("127.0.0.1:54345")),
+            event_flag_names: event_flag_names.clone(),
+            event_flag_journal: Some(EventFlagJournal::new(Path::new(DATA_DIRECTORY).join("journal"), process_name, &game_version)),
             widgets: vec!
             {
-                Box::new(EventFlagWidget::new()),
+                Box::new(DiagnosticsWidget::new(version_check.clone())),
+                Box::new(EventFlagWidget::new(event_flag_names)),
                 Box::new(AiToggleWidget::new()),
-                Box::new(PlayerPositionWidget::new()),
+                Box::new(PlayerPositionWidget::new(process_name)),
+                Box::new(TraceWidget::new(trace.clone(), process_name)),
                 Box::new(ChrDbgFlagsWidget::new()),
                 Box::new(MiscWidget::new()),
+                Box::new(InGameTimeWidget::new()),
                 Box::new(EmevdLoggerWidget::new()),
+                Box::new(GreatRuneWidget::new()),
+                Box::new(CheckTrackerWidget::new(process_name)),
+                Box::new(SplitsWidget::new(splitter.clone())),
+                Box::new(LiveSplitWidget::new(livesplit.clone())),
+            },
+            triggers_path: TriggerEngine::path(process_name),
+            triggers: TriggerEngine::new(Vec::new()),
+            alerts: Vec::new(),
+            splits_path,
+            splitter,
+            livesplit,
+            screen_state: ScreenStateTracker::new(),
+            trace,
+            config_path: Config::path(process_name),
+            config: Config::default(),
+            config_writable: true,
+            version_check,
+        };
+
+        app.load_config();
+        app.load_triggers();
+        app
+    }
+
+    fn load_triggers(&mut self) -> usize
+    {
+        match TriggerEngine::load(&self.triggers_path)
+        {
+            Ok(triggers) =>
+            {
+                info!("loaded {} trigger rules from {}", triggers.len(), self.triggers_path.display());
+                self.triggers = triggers;
+            }
+            Err(e) => warn!("failed to load trigger rules from {}: {}", self.triggers_path.display(), e),
+        }
+        return self.triggers.len();
+    }
+
+    fn load_splitter(path: &Path) -> Splitter
+    {
+        match Splitter::load(path)
+        {
+            Ok(splitter) =>
+            {
+                info!("loaded {} splits from {}", splitter.splits().len(), path.display());
+                splitter
             }
+            Err(e) =>
+            {
+                warn!("failed to load splits from {}: {}", path.display(), e);
+                Splitter::new(Vec::new())
+            }
+        }
+    }
+
+    //Poll the loading state and tell every client when it changes, returns whether the game is loading
+    fn update_screen_state(&mut self) -> Option<bool>
+    {
+        let state = self.game.loading_state()?.get_screen_state();
+        if let Some(change) = self.screen_state.update(state, Local::now())
+        {
+            self.triggers.on_screen_state(change.to);
+            self.server.broadcast(Response::ScreenStateChanged { change });
+        }
+        return Some(state.is_loading());
+    }
+
+    fn update_splits(&mut self, event_flags: &[EventFlag], loading: Option<bool>)
+    {
+        let position = self.game.player_position().map(|p| p.get_position());
+        let mut splitter = self.splitter.lock().unwrap();
+        splitter.update(&SplitInput { event_flags, position, loading });
+
+        let events = splitter.take_events();
+        let game_time_ms = self.game.in_game_time().map(|igt| igt.get_in_game_time_milliseconds());
+        self.livesplit.lock().unwrap().update(&events, game_time_ms, loading);
+
+        for event in events
+        {
+            self.server.broadcast(Response::SplitEvent { event });
+        }
+    }
+
+    fn update_trace(&mut self, loading: Option<bool>)
+    {
+        //Positions are meaningless during loads
+        if loading == Some(true)
+        {
+            return;
+        }
+
+        if let Some(position) = self.game.player_position()
+        {
+            self.trace.lock().unwrap().sample(position.get_position(), position.get_map_id(), Instant::now());
+        }
+    }
+
+    fn run_triggers(&mut self, fired: Vec<FiredTrigger>)
+    {
+        for trigger in fired
+        {
+            for action in trigger.actions
+            {
+                match action
+                {
+                    Action::Log { message } => info!("trigger {}: {}", trigger.rule, message),
+                    Action::Notify { message } => self.server.broadcast(Response::Trigger { rule: trigger.rule.clone(), message }),
+                    Action::Alert { message, seconds } => self.alerts.push((message, Instant::now() + Duration::from_secs_f32(seconds.max(0.0f32)))),
+                    Action::SetEventFlag { flag, state } =>
+                    {
+                        let result = match self.game.event_flags()
+                        {
+                            Some(event_flags) => event_flags.set_event_flag_state(flag, state),
+                            None => Err(String::from("event flags are not supported for this game")),
+                        };
+                        if let Err(e) = result
+                        {
+                            warn!("trigger {} failed to set event flag {}: {}", trigger.rule, flag, e);
+                        }
+                    }
+                    Action::AiToggleMode { mode } =>
+                    {
+                        match GameExt::get_game_mut::<DarkSoulsRemastered>(self.game.deref_mut())
+                        {
+                            Some(dsr) => dsr.ai_timer_toggle_mode = mode,
+                            None => warn!("trigger {}: ai toggle mode is only supported in Dark Souls Remastered", trigger.rule),
+                        }
+                    }
+                    Action::TasPress { buttons, frames } =>
+                    {
+                        match GameExt::get_game_mut::<DarkSoulsRemastered>(self.game.deref_mut())
+                        {
+                            Some(dsr) => dsr.tas_inputs.press(buttons, frames),
+                            None => warn!("trigger {}: tas input is only supported in Dark Souls Remastered", trigger.rule),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn load_config(&mut self)
+    {
+        self.config = match Config::load(&self.config_path)
+        {
+            Ok(config) => config,
+            Err(e) =>
+            {
+                warn!("{}, using defaults and not saving changes", e);
+                self.config_writable = false;
+                Config::default()
+            }
+        };
+        for w in &mut self.widgets
+        {
+            w.load_config(&mut self.game, &self.config);
+        }
+    }
+
+    //Collect the config from all widgets and write it when a widget reports a change
+    fn save_config_if_changed(&mut self)
+    {
+        //Every widget is asked so that each one clears its changed state
+        let changed = self.widgets.iter_mut().fold(false, |changed, w| w.take_config_changed() || changed);
+        if !changed || !self.config_writable
+        {
+            return;
+        }
+
+        let mut config = Config::default();
+        for w in &self.widgets
+        {
+            w.save_config(&mut self.game, &mut config);
+        }
+
+        if config != self.config
+        {
+            if let Err(e) = config.save(&self.config_path)
+            {
+                warn!("failed to save config to {}: {}", self.config_path.display(), e);
+            }
+            self.config = config;
         }
     }
 
     pub fn refresh(&mut self) -> Result<(), String>
     {
-        self.game.refresh()?;
-        Ok(())
+        let result = self.game.refresh();
+        let event_flags = self.dispatch_event_flags();
+        let loading = self.update_screen_state();
+        self.update_splits(&event_flags, loading);
+        self.update_trace(loading);
+
+        //Keep answering clients, even when the game is not attached (yet)
+        self.handle_server_requests();
+
+        result
     }
+
+    //Drain the game's buffered flags once and hand them to every consumer, so that none of them steal flags from the others
+    fn dispatch_event_flags(&mut self) -> Vec<EventFlag>
+    {
+        let event_flags = match self.game.event_flags()
+        {
+            Some(mut buffered_event_flags) => buffered_event_flags.get_buffered_flags(),
+            None => return Vec::new(),
+        };
+
+        //Rules are edge triggered, they are only evaluated when one of their flags is in the stream
+        if event_flags.is_empty()
+        {
+            return event_flags;
+        }
+
+        for w in &mut self.widgets
+        {
+            w.on_event_flags(&event_flags);
+        }
+        self.server.publish_event_flags(&event_flags, &self.event_flag_names);
+        if let Some(journal) = &mut self.event_flag_journal
+        {
+            journal.write(&event_flags);
+        }
+
+        //Flags a rule hasn't seen in the stream yet are read from the game
+        let fired =
+        {
+            let game_flags = self.game.event_flags();
+            self.triggers.process(&event_flags, |flag, bits| match (&game_flags, bits)
+            {
+                (Some(game_flags), None) => Some(EventFlagValue::State(game_flags.get_event_flag_state(flag))),
+                (Some(game_flags), Some(bits)) => game_flags.get_event_flag_quantity(flag, bits).map(EventFlagValue::Quantity),
+                (None, _) => None,
+            })
+        };
+        self.run_triggers(fired);
+        return event_flags;
+    }
+
+    fn handle_server_requests(&mut self)
+    {
+        while let Some(incoming) = self.server.get_request()
+        {
+            let response = self.handle_request(incoming.client, incoming.request);
+            self.server.respond(incoming.client, incoming.id, response);
+        }
+    }
+
+    fn handle_request(&mut self, client: ClientId, request: Request) -> Response
+    {
+        match request
+        {
+            Request::Ping => Response::Pong,
+            Request::SubscribeEventFlags { filter } => Response::Subscribed { subscription: self.server.subscribe_event_flags(client, filter) },
+            Request::Unsubscribe { subscription } =>
+            {
+                if self.server.unsubscribe(client, subscription)
+                {
+                    Response::Unsubscribed { subscription }
+                }
+                else
+                {
+                    Response::Error { message: format!("unknown subscription {}", subscription) }
+                }
+            }
+            Request::GetEventFlags { flags } =>
+            {
+                match self.game.event_flags()
+                {
+                    Some(event_flags) => Response::EventFlags
+                    {
+                        flags: flags.iter().map(|f| Self::reading(&self.event_flag_names, *f, EventFlagValue::State(event_flags.get_event_flag_state(*f)))).collect(),
+                    },
+                    None => Self::unsupported("event flags"),
+                }
+            }
+            Request::GetEventFlagQuantities { flags } =>
+            {
+                let event_flags = match self.game.event_flags()
+                {
+                    Some(event_flags) => event_flags,
+                    None => return Self::unsupported("event flags"),
+                };
+
+                let readings: Option<Vec<EventFlagReading>> = flags.iter()
+                    .map(|q| event_flags.get_event_flag_quantity(q.flag, q.bit_count).map(|quantity| Self::reading(&self.event_flag_names, q.flag, EventFlagValue::Quantity(quantity))))
+                    .collect();
+
+                match readings
+                {
+                    Some(flags) => Response::EventFlags { flags },
+                    None => Self::unsupported("event flag quantities"),
+                }
+            }
+            Request::SetEventFlag { flag, state } =>
+            {
+                match self.game.event_flags()
+                {
+                    Some(event_flags) => match event_flags.set_event_flag_state(flag, state)
+                    {
+                        Ok(()) => Response::EventFlagSet { flag, state },
+                        Err(message) => Response::Error { message },
+                    },
+                    None => Self::unsupported("event flags"),
+                }
+            }
+            Request::SearchEventFlagNames { query } => Response::EventFlagNames
+            {
+                names: self.event_flag_names.search(&query).into_iter().cloned().collect(),
+            },
+            Request::ReloadTriggers => Response::TriggersLoaded { rules: self.load_triggers() },
+            Request::GetGreatRunes =>
+            {
+                match GameExt::get_game_ref::<EldenRing>(self.game.deref())
+                {
+                    Some(elden_ring) => Response::GreatRunes { runes: elden_ring.great_runes() },
+                    None => Self::unsupported("great runes"),
+                }
+            }
+            Request::GetScreenState =>
+            {
+                match self.game.loading_state()
+                {
+                    Some(loading_state) => Response::ScreenState { state: loading_state.get_screen_state(), quitouts: self.screen_state.quitouts() },
+                    None => Self::unsupported("loading state"),
+                }
+            }
+            Request::Quitout =>
+            {
+                match self.game.quitout()
+                {
+                    Some(quitout) => match quitout.request_quitout()
+                    {
+                        Ok(()) => Response::QuitoutRequested,
+                        Err(message) => Response::Error { message },
+                    },
+                    None => Self::unsupported("quitout"),
+                }
+            }
+            Request::GetInGameTime =>
+            {
+                match self.game.in_game_time()
+                {
+                    Some(in_game_time) => Response::InGameTime { milliseconds: in_game_time.get_in_game_time_milliseconds() },
+                    None => Self::unsupported("in game time"),
+                }
+            }
+            Request::ReloadSplits =>
+            {
+                let mut splitter = self.splitter.lock().unwrap();
+                *splitter = Self::load_splitter(&self.splits_path);
+                Response::Splits { state: splitter.state() }
+            }
+            Request::GetSplits | Request::StartRun | Request::Split | Request::SkipSplit | Request::UndoSplit | Request::ResetRun =>
+            {
+                let mut splitter = self.splitter.lock().unwrap();
+                match request
+                {
+                    Request::StartRun => splitter.start(),
+                    Request::Split => splitter.split(),
+                    Request::SkipSplit => splitter.skip(),
+                    Request::UndoSplit => splitter.undo(),
+                    Request::ResetRun => splitter.reset(),
+                    _ => {}
+                }
+                //Events are broadcast on the next refresh
+                Response::Splits { state: splitter.state() }
+            }
+            Request::CaptureMemorySnapshot { regions } =>
+            {
+                let ranges: Vec<(usize, usize)> = regions.iter().map(|r| (r.address, r.size)).collect();
+                let saved = SnapshotMemory::capture(&LiveMemory::new(), &ranges).and_then(|snapshot|
+                {
+                    let name = format!("{}_{}.smsnap", game_key(&snapshot.main_module().unwrap().name), Local::now().format("%Y%m%d-%H%M%S"));
+                    let path = Path::new(DATA_DIRECTORY).join("snapshots").join(name);
+                    snapshot.save(&path).map(|_| path)
+                });
+                match saved
+                {
+                    Ok(path) => Response::MemorySnapshotSaved { path: path.display().to_string() },
+                    Err(message) => Response::Error { message },
+                }
+            }
+            Request::GetDiagnostics => Response::Diagnostics { version: self.version_check.clone(), report: self.game.resolution_report().cloned() },
+        }
+    }
+
+    fn reading(names: &EventFlagNames, flag: u32, value: EventFlagValue) -> EventFlagReading
+    {
+        EventFlagReading { flag, value, name: names.name(flag).map(String::from) }
+    }
+
+    fn unsupported(capability: &str) -> Response
+    {
+        Response::Unsupported { capability: String::from(capability) }
+    }
+
     pub fn render_initialize(&mut self, _context: &mut Context)
     {
         return;
@@ -195,6 +649,28 @@ impl App
         //style.tab_rounding                            = 4f32;
     }
 
+    fn render_alerts(&mut self, ui: &Ui)
+    {
+        let now = Instant::now();
+        self.alerts.retain(|(_, expires)| *expires > now);
+        if self.alerts.is_empty()
+        {
+            return;
+        }
+
+        ui.window("alerts")
+            .flags(WindowFlags::NO_DECORATION | WindowFlags::ALWAYS_AUTO_RESIZE | WindowFlags::NO_INPUTS | WindowFlags::NO_FOCUS_ON_APPEARING)
+            .position([600.0f32, 50.0f32], Condition::Always)
+            .bg_alpha(0.6f32)
+            .build(||
+        {
+            for (message, _) in &self.alerts
+            {
+                ui.text_colored([1.0f32, 0.85f32, 0.0f32, 1.0f32], message);
+            }
+        });
+    }
+
     pub fn render(&mut self, ui: &mut Ui)
     {
         ui.window("soulmemory-rs")
@@ -208,6 +684,8 @@ impl App
                 w.render(&mut self.game, ui);
             }
         });
+        self.render_alerts(ui);
+        self.save_config_if_changed();
         //ui.show_demo_window(&mut true);
     }
 }
@@ -224,7 +702,21 @@ impl Default for App
             game: Box::new(MockGame::new()),
             hmodule: HINSTANCE(std::ptr::null_mut()),
             server: Server::default(),
+            event_flag_names: Arc::new(EventFlagNames::new()),
+            event_flag_journal: None,
+            triggers_path: PathBuf::new(),
+            triggers: TriggerEngine::new(Vec::new()),
+            alerts: Vec::new(),
+            splits_path: PathBuf::new(),
+            splitter: Arc::new(Mutex::new(Splitter::new(Vec::new()))),
+            livesplit: Arc::new(Mutex::new(LiveSplitOutput::new())),
+            screen_state: ScreenStateTracker::new(),
+            trace: Arc::new(Mutex::new(TraceRecorder::new())),
             widgets: Vec::new(),
+            config_path: PathBuf::new(),
+            config: Config::default(),
+            config_writable: true,
+            version_check: VersionCheck::default(),
         }
     }
 }
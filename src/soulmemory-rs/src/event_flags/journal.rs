@@ -0,0 +1,317 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use chrono::Local;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use crate::games::traits::buffered_event_flags::{EventFlag, EventFlagValue};
+use crate::util::game_key;
+
+//Every event flag of a session is written to <journal directory>/<game>_<session>_<part>.csv and .jsonl.
+//A new part is started every MAX_ENTRIES_PER_PART entries, only the newest MAX_SESSIONS sessions per game are kept.
+
+const MAX_ENTRIES_PER_PART: usize = 100_000;
+const MAX_SESSIONS: usize = 20;
+const CSV_HEADER: &str = "session,process,game_version,monotonic_ms,time,flag,value";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct JournalEntry
+{
+    pub session: String,
+    pub process: String,
+    pub game_version: String,
+    //Milliseconds since the start of the session, from a monotonic clock
+    pub monotonic_ms: u64,
+    pub time: String,
+    pub flag: u32,
+    pub value: EventFlagValue,
+}
+
+impl JournalEntry
+{
+    pub fn to_csv_line(&self) -> String
+    {
+        return format!("{},{},{},{},{},{},{}", self.session, self.process, self.game_version, self.monotonic_ms, self.time, self.flag, self.value);
+    }
+
+    pub fn from_csv_line(line: &str) -> Result<JournalEntry, String>
+    {
+        let columns: Vec<&str> = line.split(',').collect();
+        if columns.len() != 7
+        {
+            return Err(format!("expected 7 columns, got {}", columns.len()));
+        }
+
+        let value = match columns[6]
+        {
+            "true" => EventFlagValue::State(true),
+            "false" => EventFlagValue::State(false),
+            quantity => EventFlagValue::Quantity(quantity.parse::<i32>().map_err(|e| format!("invalid value '{}': {}", quantity, e))?),
+        };
+
+        return Ok(JournalEntry
+        {
+            session: columns[0].to_string(),
+            process: columns[1].to_string(),
+            game_version: columns[2].to_string(),
+            monotonic_ms: columns[3].parse::<u64>().map_err(|e| format!("invalid monotonic_ms '{}': {}", columns[3], e))?,
+            time: columns[4].to_string(),
+            flag: columns[5].parse::<u32>().map_err(|e| format!("invalid flag '{}': {}", columns[5], e))?,
+            value,
+        });
+    }
+}
+
+struct JournalPart
+{
+    csv: BufWriter<File>,
+    jsonl: BufWriter<File>,
+    entries: usize,
+}
+
+pub struct EventFlagJournal
+{
+    directory: PathBuf,
+    game: String,
+    process_name: String,
+    game_version: String,
+    session: String,
+    session_start: Instant,
+    part_index: u32,
+    part: Option<JournalPart>,
+    //Set after a write error, so a missing or read-only directory doesn't flood the log every frame
+    disabled: bool,
+}
+
+impl EventFlagJournal
+{
+    pub fn new(directory: PathBuf, process_name: &str, game_version: &str) -> Self
+    {
+        EventFlagJournal
+        {
+            directory,
+            game: game_key(process_name),
+            process_name: process_name.to_string(),
+            game_version: game_version.to_string(),
+            session: Local::now().format("%Y%m%d-%H%M%S").to_string(),
+            session_start: Instant::now(),
+            part_index: 0,
+            part: None,
+            disabled: false,
+        }
+    }
+
+    pub fn session(&self) -> &str
+    {
+        return &self.session;
+    }
+
+    pub fn write(&mut self, event_flags: &[EventFlag])
+    {
+        if self.disabled || event_flags.is_empty()
+        {
+            return;
+        }
+
+        if let Err(e) = self.try_write(event_flags)
+        {
+            warn!("event flag journal disabled after write error: {}", e);
+            self.disabled = true;
+        }
+    }
+
+    fn try_write(&mut self, event_flags: &[EventFlag]) -> Result<(), String>
+    {
+        for event_flag in event_flags
+        {
+            if self.part.as_ref().is_none_or(|p| p.entries >= MAX_ENTRIES_PER_PART)
+            {
+                self.open_next_part()?;
+            }
+
+            let entry = JournalEntry
+            {
+                session: self.session.clone(),
+                process: self.process_name.clone(),
+                game_version: self.game_version.clone(),
+                monotonic_ms: event_flag.instant.saturating_duration_since(self.session_start).as_millis() as u64,
+                time: event_flag.time.to_rfc3339(),
+                flag: event_flag.flag,
+                value: event_flag.value,
+            };
+
+            let part = self.part.as_mut().unwrap();
+            writeln!(part.csv, "{}", entry.to_csv_line()).map_err(|e| e.to_string())?;
+            writeln!(part.jsonl, "{}", serde_json::to_string(&entry).unwrap()).map_err(|e| e.to_string())?;
+            part.entries += 1;
+        }
+
+        //Flush every batch, the game can be closed or crash at any moment
+        let part = self.part.as_mut().unwrap();
+        part.csv.flush().map_err(|e| e.to_string())?;
+        part.jsonl.flush().map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    fn open_next_part(&mut self) -> Result<(), String>
+    {
+        if self.part_index == 0
+        {
+            fs::create_dir_all(&self.directory).map_err(|e| e.to_string())?;
+            prune_sessions(&self.directory, &self.game, MAX_SESSIONS - 1);
+        }
+
+        let base = format!("{}_{}_{:03}", self.game, self.session, self.part_index);
+        let csv_path = self.directory.join(format!("{}.csv", base));
+        let mut csv = BufWriter::new(File::create(&csv_path).map_err(|e| e.to_string())?);
+        writeln!(csv, "{}", CSV_HEADER).map_err(|e| e.to_string())?;
+        let jsonl = BufWriter::new(File::create(self.directory.join(format!("{}.jsonl", base))).map_err(|e| e.to_string())?);
+
+        info!("event flag journal writing to {}", csv_path.display());
+        self.part = Some(JournalPart { csv, jsonl, entries: 0 });
+        self.part_index += 1;
+        return Ok(());
+    }
+}
+
+///Sessions in the journal directory for a game, oldest first
+pub fn list_sessions(directory: &Path, game: &str) -> Vec<String>
+{
+    let prefix = format!("{}_", game);
+    let mut sessions: Vec<String> = match fs::read_dir(directory)
+    {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.file_name().to_str().map(String::from))
+            .filter(|name| name.ends_with(".jsonl"))
+            .filter_map(|name| name.strip_prefix(&prefix).and_then(|rest| rest.rsplit_once('_')).map(|(session, _)| session.to_string()))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    sessions.sort();
+    sessions.dedup();
+    return sessions;
+}
+
+///Load every entry of a session back, in the order it was written
+pub fn read_session(directory: &Path, game: &str, session: &str) -> Result<Vec<JournalEntry>, String>
+{
+    let mut part_paths: Vec<PathBuf> = fs::read_dir(directory)
+        .map_err(|e| e.to_string())?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "jsonl"))
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(&format!("{}_{}_", game, session))))
+        .collect();
+    part_paths.sort();
+
+    if part_paths.is_empty()
+    {
+        return Err(format!("session {} not found in {}", session, directory.display()));
+    }
+
+    let mut entries = Vec::new();
+    for path in part_paths
+    {
+        let jsonl = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        entries.extend(parse_jsonl(&jsonl).map_err(|e| format!("{}: {}", path.display(), e))?);
+    }
+    return Ok(entries);
+}
+
+pub fn parse_jsonl(jsonl: &str) -> Result<Vec<JournalEntry>, String>
+{
+    return jsonl.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| serde_json::from_str::<JournalEntry>(line).map_err(|e| format!("line {}: {}", index + 1, e)))
+        .collect();
+}
+
+pub fn parse_csv(csv: &str) -> Result<Vec<JournalEntry>, String>
+{
+    return csv.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && *line != CSV_HEADER)
+        .map(|(index, line)| JournalEntry::from_csv_line(line).map_err(|e| format!("line {}: {}", index + 1, e)))
+        .collect();
+}
+
+fn prune_sessions(directory: &Path, game: &str, keep: usize)
+{
+    let sessions = list_sessions(directory, game);
+    if sessions.len() <= keep
+    {
+        return;
+    }
+
+    for session in &sessions[..sessions.len() - keep]
+    {
+        let prefix = format!("{}_{}_", game, session);
+        if let Ok(entries) = fs::read_dir(directory)
+        {
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path())
+            {
+                if path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(&prefix))
+                {
+                    let _ = fs::remove_file(path);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::event_flags::journal::*;
+
+    fn entry(value: EventFlagValue) -> JournalEntry
+    {
+        JournalEntry
+        {
+            session: String::from("20240101-120000"),
+            process: String::from("eldenring.exe"),
+            game_version: String::from("1.2.3.0"),
+            monotonic_ms: 1500,
+            time: String::from("2024-01-01T12:00:01.500+01:00"),
+            flag: 171,
+            value,
+        }
+    }
+
+    #[test]
+    pub fn csv_round_trip()
+    {
+        let csv = format!("{}\n{}\n{}\n", CSV_HEADER, entry(EventFlagValue::State(true)).to_csv_line(), entry(EventFlagValue::Quantity(-3)).to_csv_line());
+        let entries = parse_csv(&csv).unwrap();
+        assert_eq!(entries, vec![entry(EventFlagValue::State(true)), entry(EventFlagValue::Quantity(-3))]);
+        assert!(parse_csv("a,b,c\n").is_err());
+    }
+
+    #[test]
+    pub fn jsonl_round_trip()
+    {
+        let jsonl = format!("{}\n\n{}\n", serde_json::to_string(&entry(EventFlagValue::State(false))).unwrap(), serde_json::to_string(&entry(EventFlagValue::Quantity(4))).unwrap());
+        let entries = parse_jsonl(&jsonl).unwrap();
+        assert_eq!(entries, vec![entry(EventFlagValue::State(false)), entry(EventFlagValue::Quantity(4))]);
+    }
+}
@@ -0,0 +1,301 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use crate::util::{game_key, DATA_DIRECTORY};
+
+//Flag names ship per game in soulmemory-rs/event_flags/<game>.tsv. Users can add or override names by placing
+//<game>.tsv or <game>.json in the event_flags directory inside the data directory.
+//TSV columns: flag, name, category, area. The flag column is either a single id or an inclusive range like 1000-1999.
+//JSON: [{"flag": 171, "name": "...", "category": "...", "area": "..."}, {"flag": 1000, "end": 1999, "name": "..."}]
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct NamedEventFlag
+{
+    pub flag: u32,
+    //Last flag of an inclusive range, None for a single flag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<u32>,
+    pub name: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub area: String,
+}
+
+impl NamedEventFlag
+{
+    pub fn contains(&self, flag: u32) -> bool
+    {
+        return match self.end
+        {
+            Some(end) => self.flag <= flag && flag <= end,
+            None => self.flag == flag,
+        };
+    }
+
+    fn width(&self) -> u32
+    {
+        return self.end.map(|end| end - self.flag).unwrap_or(0);
+    }
+}
+
+#[derive(Default)]
+pub struct EventFlagNames
+{
+    entries: Vec<NamedEventFlag>,
+    exact: HashMap<u32, usize>,
+}
+
+impl EventFlagNames
+{
+    pub fn new() -> Self
+    {
+        EventFlagNames::default()
+    }
+
+    ///Load the names that ship with soulmemory-rs for the given process, merged with the user's own files
+    pub fn load(process_name: &str) -> Self
+    {
+        let game = game_key(process_name);
+        let mut names = EventFlagNames::new();
+
+        if let Some(tsv) = Self::embedded_names(&game)
+        {
+            match EventFlagNames::from_tsv(tsv)
+            {
+                Ok(embedded) => names.merge(embedded),
+                Err(e) => warn!("failed to parse embedded event flag names for {}: {}", game, e),
+            }
+        }
+
+        let directory = Path::new(DATA_DIRECTORY).join("event_flags");
+        for (extension, parse) in [("tsv", EventFlagNames::from_tsv as fn(&str) -> Result<EventFlagNames, String>), ("json", EventFlagNames::from_json)]
+        {
+            let path = directory.join(format!("{}.{}", game, extension));
+            if !path.exists()
+            {
+                continue;
+            }
+
+            match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|s| parse(&s))
+            {
+                Ok(user) =>
+                {
+                    info!("loaded {} event flag names from {}", user.len(), path.display());
+                    names.merge(user);
+                }
+                Err(e) => warn!("failed to load event flag names from {}: {}", path.display(), e),
+            }
+        }
+
+        info!("{} event flag names available", names.len());
+        return names;
+    }
+
+    pub fn from_tsv(tsv: &str) -> Result<Self, String>
+    {
+        let mut names = EventFlagNames::new();
+        for (index, line) in tsv.lines().enumerate()
+        {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#')
+            {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split('\t').map(|c| c.trim()).collect();
+            if columns[0] == "flag"
+            {
+                continue; //header
+            }
+
+            let (flag, end) = Self::parse_flag_column(columns[0]).map_err(|e| format!("line {}: {}", index + 1, e))?;
+            let column = |i: usize| columns.get(i).map(|c| c.to_string()).unwrap_or_default();
+
+            names.add(NamedEventFlag { flag, end, name: column(1), category: column(2), area: column(3) });
+        }
+        return Ok(names);
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String>
+    {
+        let entries = serde_json::from_str::<Vec<NamedEventFlag>>(json).map_err(|e| e.to_string())?;
+        let mut names = EventFlagNames::new();
+        for entry in entries
+        {
+            if entry.end.is_some_and(|end| end < entry.flag)
+            {
+                return Err(format!("range {}-{} ends before it starts", entry.flag, entry.end.unwrap()));
+            }
+            names.add(entry);
+        }
+        return Ok(names);
+    }
+
+    ///Add a name. A single flag that is already named gets overwritten.
+    pub fn add(&mut self, entry: NamedEventFlag)
+    {
+        if entry.end.is_none()
+        {
+            if let Some(index) = self.exact.get(&entry.flag)
+            {
+                self.entries[*index] = entry;
+                return;
+            }
+            self.exact.insert(entry.flag, self.entries.len());
+        }
+        self.entries.push(entry);
+    }
+
+    ///Merge another database into this one, names from the other database take precedence
+    pub fn merge(&mut self, other: EventFlagNames)
+    {
+        for entry in other.entries
+        {
+            self.add(entry);
+        }
+    }
+
+    ///Find the name of a flag. An exact entry wins over ranges, otherwise the narrowest range containing the flag is used.
+    pub fn lookup(&self, flag: u32) -> Option<&NamedEventFlag>
+    {
+        if let Some(index) = self.exact.get(&flag)
+        {
+            return Some(&self.entries[*index]);
+        }
+
+        return self.entries.iter()
+            .filter(|e| e.end.is_some() && e.contains(flag))
+            .min_by_key(|e| e.width());
+    }
+
+    pub fn name(&self, flag: u32) -> Option<&str>
+    {
+        return self.lookup(flag).map(|e| e.name.as_str());
+    }
+
+    ///Case insensitive search through names, categories and areas
+    pub fn search(&self, query: &str) -> Vec<&NamedEventFlag>
+    {
+        let query = query.to_lowercase();
+        return self.entries.iter()
+            .filter(|e| e.name.to_lowercase().contains(&query) || e.category.to_lowercase().contains(&query) || e.area.to_lowercase().contains(&query))
+            .collect();
+    }
+
+    pub fn len(&self) -> usize
+    {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        return self.entries.is_empty();
+    }
+
+    fn embedded_names(game: &str) -> Option<&'static str>
+    {
+        return match game
+        {
+            "darksouls" | "darksoulsremastered" => Some(include_str!("../../event_flags/darksouls.tsv")),
+            "darksoulsiii"                      => Some(include_str!("../../event_flags/darksoulsiii.tsv")),
+            "sekiro"                            => Some(include_str!("../../event_flags/sekiro.tsv")),
+            "eldenring"                         => Some(include_str!("../../event_flags/eldenring.tsv")),
+            _                                   => None,
+        };
+    }
+
+    fn parse_flag_column(column: &str) -> Result<(u32, Option<u32>), String>
+    {
+        let parse = |s: &str| s.trim().parse::<u32>().map_err(|e| format!("invalid flag '{}': {}", s, e));
+        return match column.split_once('-')
+        {
+            Some((start, end)) =>
+            {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if end < start
+                {
+                    return Err(format!("range {}-{} ends before it starts", start, end));
+                }
+                Ok((start, Some(end)))
+            }
+            None => Ok((parse(column)?, None)),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::event_flags::names::*;
+    use crate::util::game_key;
+
+    #[test]
+    pub fn tsv_exact_and_ranges()
+    {
+        let tsv = "#comment\nflag\tname\tcategory\tarea\n171\tGodrick's Great Rune\tgreat rune\tStormveil Castle\n1000-1999\tWide\n1500-1599\tNarrow\n";
+        let names = EventFlagNames::from_tsv(tsv).unwrap();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names.name(171), Some("Godrick's Great Rune"));
+        assert_eq!(names.lookup(171).unwrap().area, "Stormveil Castle");
+        assert_eq!(names.name(1000), Some("Wide"));
+        assert_eq!(names.name(1550), Some("Narrow"));
+        assert_eq!(names.name(2000), None);
+    }
+
+    #[test]
+    pub fn tsv_reports_invalid_lines()
+    {
+        let error = EventFlagNames::from_tsv("flag\tname\n12x\tbroken\n").err().unwrap();
+        assert!(error.starts_with("line 2"));
+        assert!(EventFlagNames::from_tsv("20-10\tbackwards\n").is_err());
+    }
+
+    #[test]
+    pub fn json_overrides_tsv()
+    {
+        let mut names = EventFlagNames::from_tsv("171\tGodrick\n").unwrap();
+        names.merge(EventFlagNames::from_json(r#"[{"flag": 171, "name": "Godrick's Great Rune"}, {"flag": 100, "end": 200, "name": "range"}]"#).unwrap());
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.name(171), Some("Godrick's Great Rune"));
+        assert_eq!(names.name(150), Some("range"));
+    }
+
+    #[test]
+    pub fn search_is_case_insensitive()
+    {
+        let names = EventFlagNames::from_tsv("171\tGodrick's Great Rune\tgreat rune\n10000800\tGodrick the Grafted\tboss\tStormveil Castle\n").unwrap();
+        assert_eq!(names.search("godrick").len(), 2);
+        assert_eq!(names.search("STORMVEIL").len(), 1);
+        assert_eq!(names.search("BOSS")[0].flag, 10000800);
+    }
+
+    #[test]
+    pub fn embedded_databases_parse()
+    {
+        for game in ["darksouls", "darksoulsiii", "sekiro", "eldenring"]
+        {
+            let names = EventFlagNames::from_tsv(EventFlagNames::embedded_names(game).unwrap()).unwrap();
+            assert!(!names.is_empty());
+        }
+        assert_eq!(game_key("EldenRing.exe"), "eldenring");
+    }
+}
@@ -0,0 +1,147 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use chrono::{DateTime, Local};
+use crate::util::server::EventFlagRange;
+
+//Snapshots read flags through get_event_flag_state instead of the hooks,
+//so they also see flags that are set through code paths the hooks don't cover.
+
+//Flags are grouped in blocks of 1000 ids in every game
+pub const EVENT_FLAG_BLOCK_SIZE: u32 = 1000;
+
+//Snapshots are taken on the render thread with one native call per flag, ten blocks is about as much as fits in a frame
+pub const MAX_SNAPSHOT_FLAGS: u64 = 10 * EVENT_FLAG_BLOCK_SIZE as u64;
+
+impl EventFlagRange
+{
+    ///The whole flag block the given flag lives in
+    pub fn block(flag: u32) -> Self
+    {
+        let start = flag - flag % EVENT_FLAG_BLOCK_SIZE;
+        return EventFlagRange { start, end: start + (EVENT_FLAG_BLOCK_SIZE - 1) };
+    }
+
+    pub fn len(&self) -> u64
+    {
+        return self.end as u64 - self.start as u64 + 1;
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EventFlagChange
+{
+    pub flag: u32,
+    pub before: bool,
+    pub after: bool,
+}
+
+pub struct EventFlagSnapshot
+{
+    pub time: DateTime<Local>,
+    pub ranges: Vec<EventFlagRange>,
+    states: BTreeMap<u32, bool>,
+}
+
+impl EventFlagSnapshot
+{
+    ///Read every flag in the ranges, get_state is usually BufferedEventFlags::get_event_flag_state
+    pub fn take(ranges: &[EventFlagRange], get_state: impl Fn(u32) -> bool) -> Result<Self, String>
+    {
+        if let Some(range) = ranges.iter().find(|r| r.end < r.start)
+        {
+            return Err(format!("range {}-{} ends before it starts", range.start, range.end));
+        }
+
+        let count: u64 = ranges.iter().map(|r| r.len()).sum();
+        if count > MAX_SNAPSHOT_FLAGS
+        {
+            return Err(format!("snapshot of {} flags exceeds the limit of {}", count, MAX_SNAPSHOT_FLAGS));
+        }
+
+        let mut states = BTreeMap::new();
+        for range in ranges
+        {
+            for flag in range.start..=range.end
+            {
+                states.insert(flag, get_state(flag));
+            }
+        }
+
+        return Ok(EventFlagSnapshot { time: Local::now(), ranges: ranges.to_vec(), states });
+    }
+
+    pub fn len(&self) -> usize
+    {
+        return self.states.len();
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        return self.states.is_empty();
+    }
+
+    pub fn get(&self, flag: u32) -> Option<bool>
+    {
+        return self.states.get(&flag).copied();
+    }
+
+    ///Every flag present in both snapshots whose state differs, ordered by flag
+    pub fn diff(&self, later: &EventFlagSnapshot) -> Vec<EventFlagChange>
+    {
+        return self.states.iter()
+            .filter_map(|(flag, before)| later.get(*flag).filter(|after| after != before).map(|after| EventFlagChange { flag: *flag, before: *before, after }))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::event_flags::snapshot::*;
+
+    #[test]
+    pub fn block_range()
+    {
+        assert_eq!(EventFlagRange::block(11002123), EventFlagRange { start: 11002000, end: 11002999 });
+        assert_eq!(EventFlagRange::block(0).len(), 1000);
+    }
+
+    #[test]
+    pub fn diff_lists_changed_flags()
+    {
+        let ranges = [EventFlagRange { start: 100, end: 109 }];
+        let before = EventFlagSnapshot::take(&ranges, |f| f == 101).unwrap();
+        let after = EventFlagSnapshot::take(&ranges, |f| f == 105).unwrap();
+
+        assert_eq!(before.len(), 10);
+        assert_eq!(before.diff(&after), vec!
+        [
+            EventFlagChange { flag: 101, before: true, after: false },
+            EventFlagChange { flag: 105, before: false, after: true },
+        ]);
+    }
+
+    #[test]
+    pub fn take_rejects_invalid_ranges()
+    {
+        assert!(EventFlagSnapshot::take(&[EventFlagRange { start: 10, end: 5 }], |_| false).is_err());
+        assert!(EventFlagSnapshot::take(&[EventFlagRange { start: 0, end: u32::MAX }], |_| false).is_err());
+        assert!(EventFlagSnapshot::take(&[EventFlagRange { start: 0, end: 9999 }], |_| false).is_ok());
+        assert!(EventFlagSnapshot::take(&[EventFlagRange { start: 0, end: 10000 }], |_| false).is_err());
+    }
+}
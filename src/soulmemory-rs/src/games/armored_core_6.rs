@@ -29,8 +29,14 @@ use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
 use crate::games::dx_version::DxVersion;
 use crate::games::game::Game;
 use crate::games::GameExt;
+use crate::games::traits::in_game_time::InGameTime;
+use crate::games::traits::loading_state::{LoadingState, ScreenState};
+use crate::games::hook_guard::{call_hooked_function, is_calling_hooked_function};
+use crate::games::resolver::*;
+use soulmemory_common::report::ResolutionReport;
 
-type FnGetEventFlag = fn(event_flag_man: u64, event_flag: u32) -> u8;
+type FnGetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32) -> u8;
+type FnSetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32, state: u8);
 
 pub struct ArmoredCore6
 {
@@ -39,24 +45,61 @@ pub struct ArmoredCore6
     event_flags: Arc<Mutex<Vec<EventFlag>>>,
     virtual_memory_flag: Pointer,
     fn_get_event_flag: FnGetEventFlag,
+    fn_set_event_flag: FnSetEventFlag,
     set_event_flag_address: usize,
     set_event_flag_hook: Option<HookPoint>,
+    fd4_time: Pointer,
+    menu_man: Pointer,
+    resolution: ResolutionReport,
 }
 
 impl ArmoredCore6
 {
     pub fn new() -> Self
     {
+        unsafe extern "win64" fn empty(_: u64, _: u32) -> u8 { 0 }
+        unsafe extern "win64" fn empty_set(_: u64, _: u32, _: u8) {}
+
         ArmoredCore6
         {
             process: Process::new("armoredcore6.exe"),
 
             event_flags: Arc::new(Mutex::new(Vec::new())),
             virtual_memory_flag: Pointer::default(),
-            fn_get_event_flag: |_,_|{0},
+            fn_get_event_flag: empty,
+            fn_set_event_flag: empty_set,
             set_event_flag_address: 0,
             set_event_flag_hook: None,
+            fd4_time: Pointer::default(),
+            menu_man: Pointer::default(),
+            resolution: ResolutionReport::default(),
+        }
+    }
+}
+
+//soulmods patches the IGT so it matches the real time spent in game
+impl InGameTime for ArmoredCore6
+{
+    fn get_in_game_time_milliseconds(&self) -> u32
+    {
+        if !self.process.is_attached()
+        {
+            return 0;
         }
+        return self.fd4_time.read_u32_rel(Some(0x114));
+    }
+}
+
+//Only the loading screen is known, the main menu is reported as in game
+impl LoadingState for ArmoredCore6
+{
+    fn get_screen_state(&self) -> ScreenState
+    {
+        if !self.process.is_attached()
+        {
+            return ScreenState::MainMenu;
+        }
+        return if self.menu_man.read_u32_rel(Some(0x8e4)) != 0 { ScreenState::Loading } else { ScreenState::InGame };
     }
 }
 
@@ -68,9 +111,21 @@ impl BufferedEventFlags for ArmoredCore6
     }
 
     fn get_event_flag_state(&self, event_flag: u32) -> bool {
-        let result = (self.fn_get_event_flag)(self.virtual_memory_flag.read_u64_rel(None), event_flag);
+        let result = unsafe { (self.fn_get_event_flag)(self.virtual_memory_flag.read_u64_rel(None), event_flag) };
         return result == 1;
     }
+
+    fn set_event_flag_state(&self, event_flag: u32, state: bool) -> Result<(), String>
+    {
+        if !self.process.is_attached()
+        {
+            return Err(String::from("not attached to the game"));
+        }
+
+        call_hooked_function(|| unsafe { (self.fn_set_event_flag)(self.virtual_memory_flag.read_u64_rel(None), event_flag, state as u8) });
+        self.event_flags.lock().unwrap().push(EventFlag::from_state(chrono::offset::Local::now(), event_flag, state));
+        Ok(())
+    }
 }
 
 
@@ -86,16 +141,30 @@ impl Game for ArmoredCore6
             {
                 self.process.refresh()?;
 
-                self.virtual_memory_flag = self.process.scan_rel("CSEventFlagMan", "48 8b 35 ? ? ? ? 83 f8 ff 0f 44 c1", 3, 7, vec![0])?;
-
-                self.set_event_flag_address = self.process.scan_abs("set_event_flag", "48 89 5c 24 18 56 41 56 41 57 48 83 ec 20 44 8b 49 1c 44 8b f2", 0, Vec::new())?.get_base_address();
-                let get_event_flag_address = self.process.scan_abs("get_event_flag", "44 8b 41 1c 44 8b da 33 d2 41 8b c3 41 f7 f0 4c 8b d1 45 33 c9 44 0f af c0", 0, Vec::new())?.get_base_address();
-                self.fn_get_event_flag = mem::transmute(get_event_flag_address);
-
-                #[cfg(target_arch = "x86_64")]
+                //A pattern that isn't found only disables its own capability, the rest of the game keeps working
+                let mut resolution = ResolutionReport::default();
+                self.virtual_memory_flag = resolve_scan(&mut resolution, EVENT_FLAGS, "CSEventFlagMan", self.process.scan_rel("CSEventFlagMan", "48 8b 35 ? ? ? ? 83 f8 ff 0f 44 c1", 3, 7, vec![0]));
+                self.set_event_flag_address = resolve_scan(&mut resolution, EVENT_FLAGS, "set_event_flag", self.process.scan_abs("set_event_flag", "48 89 5c 24 18 56 41 56 41 57 48 83 ec 20 44 8b 49 1c 44 8b f2", 0, Vec::new())).get_base_address();
+                let get_event_flag_address = resolve_scan(&mut resolution, EVENT_FLAGS, "get_event_flag", self.process.scan_abs("get_event_flag", "44 8b 41 1c 44 8b da 33 d2 41 8b c3 41 f7 f0 4c 8b d1 45 33 c9 44 0f af c0", 0, Vec::new())).get_base_address();
+                self.fd4_time = resolve_scan(&mut resolution, IN_GAME_TIME, "FD4Time", self.process.scan_rel("FD4Time", "48 8b 0d ? ? ? ? 0f 28 c8 f3 0f 59 0d", 3, 7, vec![0, 0]));
+                self.menu_man = resolve_scan(&mut resolution, LOADING_STATE, "CSMenuMan", self.process.scan_rel("CSMenuMan", "48 8b 35 ? ? ? ? 33 db 89 5c 24 20", 3, 7, vec![0, 0]));
+                //Quitout stays off until there is a source for the quit request field in CSMenuMan, it shows up as unavailable in the diagnostics
+                resolution.fail(QUITOUT, "CSMenuMan", String::from("quit request field not known for this game yet"));
+                //Position stays off until there is a source for the path from WorldChrMan to the AC's physics module
+                resolution.fail(PLAYER_POSITION, "WorldChrMan", String::from("physics module path not known for this game yet"));
+                self.resolution = resolution;
+
+                //The functions are only called and hooked when all of the event flag patterns were found
+                if self.resolution.is_available(EVENT_FLAGS)
                 {
-                    let h = Hooker::new(self.set_event_flag_address, HookType::JmpBack(set_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
-                    self.set_event_flag_hook = Some(h.hook().unwrap());
+                    self.fn_get_event_flag = mem::transmute(get_event_flag_address);
+                    self.fn_set_event_flag = mem::transmute(self.set_event_flag_address);
+
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        let h = Hooker::new(self.set_event_flag_address, HookType::JmpBack(set_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
+                        self.set_event_flag_hook = Some(h.hook().unwrap());
+                    }
                 }
 
 
@@ -108,7 +177,8 @@ impl Game for ArmoredCore6
                 let mut buffer: [u8; 1] = [0x0];
                 self.process.read_memory_abs(self.set_event_flag_address, &mut buffer);
                 let byte = buffer[0];
-                if byte == 0x48
+                //Only when the hook was installed, the event flag patterns may not have been found
+                if byte == 0x48 && self.set_event_flag_hook.is_some()
                 {
                     info!("re-hook set event flag");
                     let hookpoint = self.set_event_flag_hook.take();
@@ -130,7 +200,10 @@ impl Game for ArmoredCore6
     fn get_dx_version(&self) -> DxVersion {
         DxVersion::Dx12
     }
-    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { Some(Box::new(self)) }
+    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { if self.resolution.is_available(EVENT_FLAGS) { Some(Box::new(self)) } else { None } }
+    fn in_game_time(&mut self) -> Option<Box<&mut dyn InGameTime>> { if self.resolution.is_available(IN_GAME_TIME) { Some(Box::new(self)) } else { None } }
+    fn loading_state(&mut self) -> Option<Box<&mut dyn LoadingState>> { if self.resolution.is_available(LOADING_STATE) { Some(Box::new(self)) } else { None } }
+    fn resolution_report(&self) -> Option<&ResolutionReport> { Some(&self.resolution) }
 
     fn as_any(&self) -> &dyn Any
     {
@@ -142,6 +215,11 @@ impl Game for ArmoredCore6
 #[cfg(target_arch = "x86_64")]
 unsafe extern "win64" fn set_event_flag_hook_fn(registers: *mut Registers, _:usize)
 {
+    if is_calling_hooked_function()
+    {
+        return;
+    }
+
     let instance = App::get_instance();
     let app = instance.lock().unwrap();
 
@@ -26,11 +26,17 @@ use std::sync::{Arc, Mutex};
 use ilhook::x64::{CallbackOption, Hooker, HookFlags, HookPoint, HookType, Registers};
 use log::info;
 use mem_rs::pointer::Pointer;
-use mem_rs::prelude::Process;
+use mem_rs::prelude::{Process, ReadWrite};
 use crate::App;
 use crate::games::dx_version::DxVersion;
 use crate::games::{Game, GameExt};
+use crate::games::hook_guard::is_calling_hooked_function;
+use crate::games::resolver::*;
 use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
+use crate::games::traits::loading_state::{LoadingState, ScreenState};
+use crate::games::traits::player_position::PlayerPosition;
+use crate::util::vector3f::Vector3f;
+use soulmemory_common::report::ResolutionReport;
 
 #[cfg(target_arch = "x86")]//This version exists only to make things compile easily for x86
 type FnGetEventFlag = unsafe extern "thiscall" fn(event_flag_man: u64, event_flag: u32) -> u8;
@@ -38,14 +44,24 @@ type FnGetEventFlag = unsafe extern "thiscall" fn(event_flag_man: u64, event_fla
 #[cfg(target_arch = "x86_64")]
 type FnGetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32) -> u8;
 
+#[cfg(target_arch = "x86")]//This version exists only to make things compile easily for x86
+type FnSetEventFlag = unsafe extern "thiscall" fn(event_flag_man: u64, event_flag: u32, state: u8);
+
+#[cfg(target_arch = "x86_64")]
+type FnSetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32, state: u8);
+
 pub struct DarkSouls2ScholarOfTheFirstSin
 {
     process: Process,
 
     event_flag_man: Pointer,
+    load_state: Pointer,
+    position: Pointer,
     event_flags: Arc<Mutex<Vec<EventFlag>>>,
     set_event_flag_hook: Option<HookPoint>,
     fn_get_event_flag: FnGetEventFlag,
+    fn_set_event_flag: FnSetEventFlag,
+    resolution: ResolutionReport,
 }
 
 impl DarkSouls2ScholarOfTheFirstSin
@@ -58,14 +74,64 @@ impl DarkSouls2ScholarOfTheFirstSin
         #[cfg(target_arch = "x86_64")]
         unsafe extern "win64" fn empty(_: u64, _: u32) -> u8 { 0 }
 
+        #[cfg(target_arch = "x86")]
+        unsafe extern "thiscall" fn empty_set(_: u64, _: u32, _: u8) {}
+
+        #[cfg(target_arch = "x86_64")]
+        unsafe extern "win64" fn empty_set(_: u64, _: u32, _: u8) {}
+
         DarkSouls2ScholarOfTheFirstSin
         {
             process: Process::new("darksoulsii.exe"),
 
             event_flag_man: Default::default(),
+            load_state: Default::default(),
+            position: Default::default(),
             event_flags: Arc::new(Mutex::new(vec![])),
             set_event_flag_hook: None,
             fn_get_event_flag: empty,
+            fn_set_event_flag: empty_set,
+            resolution: ResolutionReport::default(),
+        }
+    }
+}
+
+//Only loading is known, the main menu is reported as in game
+impl LoadingState for DarkSouls2ScholarOfTheFirstSin
+{
+    fn get_screen_state(&self) -> ScreenState
+    {
+        if !self.process.is_attached()
+        {
+            return ScreenState::MainMenu;
+        }
+        return if self.load_state.read_u32_rel(Some(0x11c)) == 1 { ScreenState::Loading } else { ScreenState::InGame };
+    }
+}
+
+//The map id is not known, presets are stored without a map
+impl PlayerPosition for DarkSouls2ScholarOfTheFirstSin
+{
+    fn get_position(&self) -> Vector3f
+    {
+        if !self.process.is_attached()
+        {
+            return Vector3f::default();
+        }
+
+        let x = self.position.read_f32_rel(Some(0xf0));
+        let y = self.position.read_f32_rel(Some(0xf4));
+        let z = self.position.read_f32_rel(Some(0xf8));
+        return Vector3f::new(x, y, z);
+    }
+
+    fn set_position(&self, position: &Vector3f)
+    {
+        if self.process.is_attached()
+        {
+            self.position.write_f32_rel(Some(0xf0), position.x);
+            self.position.write_f32_rel(Some(0xf4), position.y);
+            self.position.write_f32_rel(Some(0xf8), position.z);
         }
     }
 }
@@ -79,16 +145,27 @@ impl Game for DarkSouls2ScholarOfTheFirstSin
             unsafe
             {
                 self.process.refresh()?;
-                self.event_flag_man = self.process.scan_rel("GameDataMan" , "48 8b 35 ? ? ? ? 48 8b e9 48 85 f6", 3, 7, vec![0, 0x70, 0x20])?;
-                let get_event_flag_address = self.process.scan_abs("get_event_flag" , "44 8b d2 b8 ? ? ? ? f7 e2 44 8b ca", 0,  Vec::new())?.get_base_address();
-                let set_event_flag_address = self.process.scan_abs("set_event_flag" , "48 89 74 24 10 57 48 83 ec 20 8b fa 45 0f b6 d8", 0,  Vec::new())?.get_base_address();
 
-                self.fn_get_event_flag = mem::transmute(get_event_flag_address);
-
-                #[cfg(target_arch = "x86_64")]
+                //A pattern that isn't found only disables its own capability, the rest of the game keeps working
+                let mut resolution = ResolutionReport::default();
+                self.event_flag_man = resolve_scan(&mut resolution, EVENT_FLAGS, "GameDataMan", self.process.scan_rel("GameDataMan" , "48 8b 35 ? ? ? ? 48 8b e9 48 85 f6", 3, 7, vec![0, 0x70, 0x20]));
+                let get_event_flag_address = resolve_scan(&mut resolution, EVENT_FLAGS, "get_event_flag", self.process.scan_abs("get_event_flag" , "44 8b d2 b8 ? ? ? ? f7 e2 44 8b ca", 0,  Vec::new())).get_base_address();
+                let set_event_flag_address = resolve_scan(&mut resolution, EVENT_FLAGS, "set_event_flag", self.process.scan_abs("set_event_flag" , "48 89 74 24 10 57 48 83 ec 20 8b fa 45 0f b6 d8", 0,  Vec::new())).get_base_address();
+                self.load_state = resolve_scan(&mut resolution, LOADING_STATE, "LoadState", self.process.scan_rel("LoadState", "48 89 05 ? ? ? ? b0 01 48 83 c4 28", 3, 7, vec![0]));
+                self.position = resolve_scan(&mut resolution, PLAYER_POSITION, "GameManagerImp", self.process.scan_rel("GameManagerImp", "48 8b 35 ? ? ? ? 48 8b e9 48 85 f6", 3, 7, vec![0, 0xd0, 0xf8]));
+                self.resolution = resolution;
+
+                //The functions are only called and hooked when all of the event flag patterns were found
+                if self.resolution.is_available(EVENT_FLAGS)
                 {
-                    let h = Hooker::new(set_event_flag_address, HookType::JmpBack(crate::games::dark_souls_2_scholar_of_the_first_sin::read_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
-                    self.set_event_flag_hook = Some(h.hook().unwrap());
+                    self.fn_get_event_flag = mem::transmute(get_event_flag_address);
+                    self.fn_set_event_flag = mem::transmute(set_event_flag_address);
+
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        let h = Hooker::new(set_event_flag_address, HookType::JmpBack(crate::games::dark_souls_2_scholar_of_the_first_sin::read_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
+                        self.set_event_flag_hook = Some(h.hook().unwrap());
+                    }
                 }
 
                 info!("event_flag_man base address: 0x{:x}", self.event_flag_man.get_base_address());
@@ -104,8 +181,12 @@ impl Game for DarkSouls2ScholarOfTheFirstSin
     }
 
     fn get_dx_version(&self) -> DxVersion { DxVersion::Dx11 }
+    //Dark Souls 2 has no in game time, runs are timed in real time with loads removed
 
-    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { Some(Box::new(self)) }
+    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { if self.resolution.is_available(EVENT_FLAGS) { Some(Box::new(self)) } else { None } }
+    fn loading_state(&mut self) -> Option<Box<&mut dyn LoadingState>> { if self.resolution.is_available(LOADING_STATE) { Some(Box::new(self)) } else { None } }
+    fn player_position(&mut self) -> Option<Box<&mut dyn PlayerPosition>> { if self.resolution.is_available(PLAYER_POSITION) { Some(Box::new(self)) } else { None } }
+    fn resolution_report(&self) -> Option<&ResolutionReport> { Some(&self.resolution) }
 
     fn as_any(&self) -> &dyn Any { self }
 
@@ -115,6 +196,11 @@ impl Game for DarkSouls2ScholarOfTheFirstSin
 #[cfg(target_arch = "x86_64")]
 unsafe extern "win64" fn read_event_flag_hook_fn(registers: *mut Registers, _:usize)
 {
+    if is_calling_hooked_function()
+    {
+        return;
+    }
+
     let instance = App::get_instance();
     let app = instance.lock().unwrap();
 
@@ -17,6 +17,7 @@
 use std::sync::{Arc, Mutex};
 use mem_rs::prelude::ReadWrite;
 use crate::games::DarkSouls2Vanilla;
+use crate::games::hook_guard::call_hooked_function;
 use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
 
 impl BufferedEventFlags for DarkSouls2Vanilla
@@ -32,4 +33,17 @@ impl BufferedEventFlags for DarkSouls2Vanilla
         let result = unsafe { (self.fn_get_event_flag)(event_flag_man_address, event_flag) };
         return result == 1;
     }
+
+    fn set_event_flag_state(&self, event_flag: u32, state: bool) -> Result<(), String>
+    {
+        if !self.process.is_attached()
+        {
+            return Err(String::from("not attached to the game"));
+        }
+
+        let event_flag_man_address = self.event_flag_man.read_u32_rel(None);
+        call_hooked_function(|| unsafe { (self.fn_set_event_flag)(event_flag_man_address, event_flag, state as u8) });
+        self.event_flags.lock().unwrap().push(EventFlag::from_state(chrono::offset::Local::now(), event_flag, state));
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -23,12 +23,16 @@ use std::sync::{Arc, Mutex};
 use ilhook::x86::{CallbackOption, Hooker, HookFlags, HookPoint, HookType, Registers};
 use log::info;
 use mem_rs::pointer::Pointer;
-use mem_rs::prelude::Process;
+use mem_rs::prelude::{Process, ReadWrite};
 use crate::App;
 use crate::games::dx_version::DxVersion;
 use crate::games::{Game, GameExt};
+use crate::games::hook_guard::is_calling_hooked_function;
+use crate::games::resolver::*;
 use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
+use crate::games::traits::loading_state::{LoadingState, ScreenState};
 use crate::util::{get_stack_u32, get_stack_u8};
+use soulmemory_common::report::ResolutionReport;
 
 #[cfg(target_arch = "x86")]
 type FnGetEventFlag = unsafe extern "thiscall" fn(event_flag_man: u32, event_flag: u32) -> u8;
@@ -37,14 +41,24 @@ type FnGetEventFlag = unsafe extern "thiscall" fn(event_flag_man: u32, event_fla
 #[cfg(target_arch = "x86_64")]
 type FnGetEventFlag = unsafe extern "win64" fn(event_flag_man: u32, event_flag: u32) -> u8;
 
+#[cfg(target_arch = "x86")]
+type FnSetEventFlag = unsafe extern "thiscall" fn(event_flag_man: u32, event_flag: u32, state: u8);
+
+//This version exists only to make things compile easily for x64
+#[cfg(target_arch = "x86_64")]
+type FnSetEventFlag = unsafe extern "win64" fn(event_flag_man: u32, event_flag: u32, state: u8);
+
 pub struct DarkSouls2Vanilla
 {
     process: Process,
 
     event_flag_man: Pointer,
+    load_state: Pointer,
     event_flags: Arc<Mutex<Vec<EventFlag>>>,
     set_event_flag_hook: Option<HookPoint>,
     fn_get_event_flag: FnGetEventFlag,
+    fn_set_event_flag: FnSetEventFlag,
+    resolution: ResolutionReport,
 }
 
 impl DarkSouls2Vanilla
@@ -57,18 +71,40 @@ impl DarkSouls2Vanilla
         #[cfg(target_arch = "x86_64")]
         unsafe extern "win64" fn empty(_: u32, _: u32) -> u8 { 0 }
 
+        #[cfg(target_arch = "x86")]
+        unsafe extern "thiscall" fn empty_set(_: u32, _: u32, _: u8) {}
+
+        #[cfg(target_arch = "x86_64")]
+        unsafe extern "win64" fn empty_set(_: u32, _: u32, _: u8) {}
+
         DarkSouls2Vanilla
         {
             process: Process::new("darksoulsii.exe"),
 
             event_flag_man: Default::default(),
+            load_state: Default::default(),
             event_flags: Arc::new(Mutex::new(vec![])),
             set_event_flag_hook: None,
             fn_get_event_flag: empty,
+            fn_set_event_flag: empty_set,
+            resolution: ResolutionReport::default(),
         }
     }
 }
 
+//Only loading is known, the main menu is reported as in game
+impl LoadingState for DarkSouls2Vanilla
+{
+    fn get_screen_state(&self) -> ScreenState
+    {
+        if !self.process.is_attached()
+        {
+            return ScreenState::MainMenu;
+        }
+        return if self.load_state.read_u32_rel(Some(0x1d4)) == 1 { ScreenState::Loading } else { ScreenState::InGame };
+    }
+}
+
 impl Game for DarkSouls2Vanilla
 {
     fn refresh(&mut self) -> Result<(), String>
@@ -78,14 +114,24 @@ impl Game for DarkSouls2Vanilla
             unsafe
                 {
                     self.process.refresh()?;
-                    self.event_flag_man = self.process.scan_abs("GameManagerImp", "56 ff d2 c7 05 ? ? ? ? 00 00 00 00 5e", 5, vec![0, 0, 0x44, 0x10])?;
-                    let get_event_flag_address = self.process.scan_abs("get_event_flag", "55 8b ec 53 56 57 8b 7d 08 b8 ? ? ? ? f7", 0, Vec::new())?.get_base_address();
-                    let set_event_flag_address = self.process.scan_abs("set_event_flag", "55 8b ec 83 ec 08 53 56 8b 75 08 b8 ? ? ? ? f7", 0, Vec::new())?.get_base_address();
 
-                    self.fn_get_event_flag = mem::transmute(get_event_flag_address);
+                    //A pattern that isn't found only disables its own capability, the rest of the game keeps working
+                    let mut resolution = ResolutionReport::default();
+                    self.event_flag_man = resolve_scan(&mut resolution, EVENT_FLAGS, "GameManagerImp", self.process.scan_abs("GameManagerImp", "56 ff d2 c7 05 ? ? ? ? 00 00 00 00 5e", 5, vec![0, 0, 0x44, 0x10]));
+                    let get_event_flag_address = resolve_scan(&mut resolution, EVENT_FLAGS, "get_event_flag", self.process.scan_abs("get_event_flag", "55 8b ec 53 56 57 8b 7d 08 b8 ? ? ? ? f7", 0, Vec::new())).get_base_address();
+                    let set_event_flag_address = resolve_scan(&mut resolution, EVENT_FLAGS, "set_event_flag", self.process.scan_abs("set_event_flag", "55 8b ec 83 ec 08 53 56 8b 75 08 b8 ? ? ? ? f7", 0, Vec::new())).get_base_address();
+                    self.load_state = resolve_scan(&mut resolution, LOADING_STATE, "LoadState", self.process.scan_abs("LoadState", "89 35 ? ? ? ? e8 ? ? ? ? 66 0f ef c0", 2, vec![0, 0]));
+                    self.resolution = resolution;
+
+                    //The functions are only called and hooked when all of the event flag patterns were found
+                    if self.resolution.is_available(EVENT_FLAGS)
+                    {
+                        self.fn_get_event_flag = mem::transmute(get_event_flag_address);
+                        self.fn_set_event_flag = mem::transmute(set_event_flag_address);
 
-                    let h = Hooker::new(set_event_flag_address, HookType::JmpBack(set_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
-                    self.set_event_flag_hook = Some(h.hook().unwrap());
+                        let h = Hooker::new(set_event_flag_address, HookType::JmpBack(set_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
+                        self.set_event_flag_hook = Some(h.hook().unwrap());
+                    }
 
                     info!("event_flag_man base address: 0x{:x}", self.event_flag_man.get_base_address());
                     info!("get event flag address     : 0x{:x}", get_event_flag_address);
@@ -98,8 +144,11 @@ impl Game for DarkSouls2Vanilla
     }
 
     fn get_dx_version(&self) -> DxVersion { DxVersion::Dx9 }
+    //Dark Souls 2 has no in game time, runs are timed in real time with loads removed
 
-    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { Some(Box::new(self)) }
+    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { if self.resolution.is_available(EVENT_FLAGS) { Some(Box::new(self)) } else { None } }
+    fn loading_state(&mut self) -> Option<Box<&mut dyn LoadingState>> { if self.resolution.is_available(LOADING_STATE) { Some(Box::new(self)) } else { None } }
+    fn resolution_report(&self) -> Option<&ResolutionReport> { Some(&self.resolution) }
 
     fn as_any(&self) -> &dyn Any { self }
 
@@ -108,6 +157,11 @@ impl Game for DarkSouls2Vanilla
 
 unsafe extern "cdecl" fn set_event_flag_hook_fn(registers: *mut Registers, _:usize)
 {
+    if is_calling_hooked_function()
+    {
+        return;
+    }
+
     let instance = App::get_instance();
     let app = instance.lock().unwrap();
 
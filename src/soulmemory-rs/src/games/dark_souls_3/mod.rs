@@ -0,0 +1,286 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+#![allow(dead_code)]
+#![allow(unused_imports)]
+
+
+use std::any::Any;
+use std::mem;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use ilhook::x64::{CallbackOption, Hooker, HookFlags, HookPoint, HookType, Registers};
+use log::info;
+use mem_rs::prelude::*;
+use soulmemory_common::report::ResolutionReport;
+use crate::App;
+use crate::games::dx_version::DxVersion;
+use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
+use crate::games::game::Game;
+use crate::games::{read_chr_dbg_flags, ChrDbgFlag, GameExt, GetSetChrDbgFlags};
+use crate::games::resolver::*;
+use crate::games::hook_guard::{call_hooked_function, is_calling_hooked_function};
+use crate::games::traits::in_game_time::InGameTime;
+use crate::games::traits::loading_state::{LoadingState, ScreenState};
+use crate::games::traits::player_position::PlayerPosition;
+use crate::util::vector3f::Vector3f;
+use crate::util::game_version;
+use crate::memory::live::LiveMemory;
+use soulmemory_common::version::{Version, VersionSupport};
+use soulmemory_common::games::dark_souls_3::{DarkSouls3Layout, PATTERN_TABLE, SUPPORTED_VERSIONS};
+
+type FnGetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32) -> u8;
+type FnSetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32, state: u8, unknown: u8);
+
+pub struct DarkSouls3
+{
+    process: Process,
+    memory: LiveMemory,
+    layout: DarkSouls3Layout,
+
+    event_flags: Arc<Mutex<Vec<EventFlag>>>,
+    fn_get_event_flag: FnGetEventFlag,
+    fn_set_event_flag: FnSetEventFlag,
+    set_event_flag_hook: Option<HookPoint>,
+    resolution: ResolutionReport,
+}
+
+impl DarkSouls3
+{
+    pub fn new() -> Self
+    {
+        unsafe extern "win64" fn empty(_: u64, _: u32) -> u8 { 0 }
+        unsafe extern "win64" fn empty_set(_: u64, _: u32, _: u8, _: u8) {}
+
+        DarkSouls3
+        {
+            process: Process::new("darksoulsiii.exe"),
+            memory: LiveMemory::new(),
+            layout: DarkSouls3Layout::default(),
+
+            event_flags: Arc::new(Mutex::new(Vec::new())),
+            fn_get_event_flag: empty,
+            fn_set_event_flag: empty_set,
+            set_event_flag_hook: None,
+            resolution: ResolutionReport::default(),
+        }
+    }
+}
+
+impl InGameTime for DarkSouls3
+{
+    fn get_in_game_time_milliseconds(&self) -> u32
+    {
+        if !self.process.is_attached()
+        {
+            return 0;
+        }
+        return self.layout.in_game_time(&self.memory);
+    }
+}
+
+impl LoadingState for DarkSouls3
+{
+    fn get_screen_state(&self) -> ScreenState
+    {
+        if !self.process.is_attached()
+        {
+            return ScreenState::MainMenu;
+        }
+        return self.layout.screen_state(&self.memory);
+    }
+}
+
+impl PlayerPosition for DarkSouls3
+{
+    fn get_position(&self) -> Vector3f
+    {
+        if !self.process.is_attached()
+        {
+            return Vector3f::default();
+        }
+        return self.layout.position(&self.memory);
+    }
+
+    fn set_position(&self, position: &Vector3f)
+    {
+        if self.process.is_attached()
+        {
+            self.layout.set_position(&self.memory, position);
+        }
+    }
+
+    fn get_rotation(&self) -> Option<f32>
+    {
+        if !self.process.is_attached()
+        {
+            return None;
+        }
+        return Some(self.layout.rotation(&self.memory));
+    }
+
+    fn set_rotation(&self, rotation: f32)
+    {
+        if self.process.is_attached()
+        {
+            self.layout.set_rotation(&self.memory, rotation);
+        }
+    }
+}
+
+//Same layout as Sekiro's ChrDbg globals, with FP and arrows instead of the resource items
+const CHR_DBG_FLAGS: [(u32, &str); 14] =
+[
+    (0 , "Player No Dead"),
+    (1 , "Player Exterminate"),
+    (2 , "Player Exterminate Stamina"),
+    (3 , "Player No Goods Consume"),
+    (4 , "Player No FP Consume"),
+    (5 , "Player No Arrow Consume"),
+    (9 , "Player Hide"),
+    (10, "Player Silenced"),
+    (11, "All No Dead"),
+    (12, "All No Damage"),
+    (13, "All No Hit"),
+    (14, "All No Attack"),
+    (15, "All No Move"),
+    (16, "All No Update Ai"),
+];
+
+impl GetSetChrDbgFlags for DarkSouls3
+{
+    fn get_flags(&self) -> Vec<ChrDbgFlag>
+    {
+        return read_chr_dbg_flags(&self.memory, &self.layout.chr_dbg_flags, &CHR_DBG_FLAGS);
+    }
+
+    fn set_flag(&self, flag: u32, value: bool)
+    {
+        if self.process.is_attached()
+        {
+            self.layout.chr_dbg_flags.write_u8(&self.memory, flag as usize, value as u8);
+        }
+    }
+}
+
+impl BufferedEventFlags for DarkSouls3
+{
+    fn access_flag_storage(&self) -> &Arc<Mutex<Vec<EventFlag>>>
+    {
+        return &self.event_flags;
+    }
+
+    fn get_event_flag_state(&self, event_flag: u32) -> bool {
+        let result = unsafe { (self.fn_get_event_flag)(self.layout.event_flag_man(&self.memory), event_flag) };
+        return result == 1;
+    }
+
+    fn set_event_flag_state(&self, event_flag: u32, state: bool) -> Result<(), String>
+    {
+        if !self.process.is_attached()
+        {
+            return Err(String::from("not attached to the game"));
+        }
+
+        call_hooked_function(|| unsafe { (self.fn_set_event_flag)(self.layout.event_flag_man(&self.memory), event_flag, state as u8, 0) });
+        self.event_flags.lock().unwrap().push(EventFlag::from_state(chrono::offset::Local::now(), event_flag, state));
+        Ok(())
+    }
+}
+
+impl Game for DarkSouls3
+{
+    fn refresh(&mut self) -> Result<(), String> {
+        if !self.process.is_attached()
+        {
+            unsafe
+            {
+                self.process.refresh()?;
+
+
+                let version = game_version();
+                let table = load_pattern_table("darksoulsiii", PATTERN_TABLE)?;
+                let mut resolver = Resolver::scan(&table, version.as_ref(), SUPPORTED_VERSIONS, &self.memory)?;
+
+                self.layout = DarkSouls3Layout::resolve(&mut resolver);
+                let set_event_flag_address = resolver.address(EVENT_FLAGS, "set_event_flag");
+                let get_event_flag_address = resolver.address(EVENT_FLAGS, "get_event_flag");
+                self.resolution = resolver.finish();
+
+                //The functions are only called and hooked when both were found
+                if !self.resolution.is_available(EVENT_FLAGS)
+                {
+                    return Ok(());
+                }
+                self.fn_get_event_flag = mem::transmute(get_event_flag_address);
+                self.fn_set_event_flag = mem::transmute(set_event_flag_address);
+
+                #[cfg(target_arch = "x86_64")]
+                {
+                    let h = Hooker::new(set_event_flag_address, HookType::JmpBack(set_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
+                    self.set_event_flag_hook = Some(h.hook().unwrap());
+                }
+
+                info!("event_flag_man base address: 0x{:x}", self.layout.event_flag_man.base);
+                info!("set event flag address     : 0x{:x}", set_event_flag_address);
+                info!("get event flag address     : 0x{:x}", get_event_flag_address);
+            }
+        }
+        else
+        {
+            self.process.refresh()?;
+        }
+        Ok(())
+    }
+
+    fn get_dx_version(&self) -> DxVersion {
+        DxVersion::Dx11
+    }
+    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { if self.resolution.is_available(EVENT_FLAGS) { Some(Box::new(self)) } else { None } }
+    fn in_game_time(&mut self) -> Option<Box<&mut dyn InGameTime>> { if self.resolution.is_available(IN_GAME_TIME) { Some(Box::new(self)) } else { None } }
+    fn loading_state(&mut self) -> Option<Box<&mut dyn LoadingState>> { if self.resolution.is_available(LOADING_STATE) { Some(Box::new(self)) } else { None } }
+    fn player_position(&mut self) -> Option<Box<&mut dyn PlayerPosition>> { if self.resolution.is_available(PLAYER_POSITION) { Some(Box::new(self)) } else { None } }
+    fn chr_dbg_flags(&mut self) -> Option<Box<&mut dyn GetSetChrDbgFlags>> { if self.resolution.is_available(CHR_DBG) { Some(Box::new(self)) } else { None } }
+    fn resolution_report(&self) -> Option<&ResolutionReport> { Some(&self.resolution) }
+    fn supported_versions(&self) -> &'static [VersionSupport] { SUPPORTED_VERSIONS }
+
+    fn as_any(&self) -> &dyn Any
+    {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe extern "win64" fn set_event_flag_hook_fn(registers: *mut Registers, _:usize)
+{
+    if is_calling_hooked_function()
+    {
+        return;
+    }
+
+    let instance = App::get_instance();
+    let app = instance.lock().unwrap();
+
+    if let Some(game) = GameExt::get_game_ref::<DarkSouls3>(app.game.deref())
+    {
+        let event_flag_id = (*registers).rdx as u32;
+        let value = (*registers).r8 as u8;
+
+        let mut guard = game.event_flags.lock().unwrap();
+        guard.push(EventFlag::from_state(chrono::offset::Local::now(), event_flag_id, value != 0));
+    }
+}
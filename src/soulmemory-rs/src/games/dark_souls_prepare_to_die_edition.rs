@@ -26,14 +26,19 @@ use crate::games::dx_version::DxVersion;
 use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
 use crate::games::game::{Game};
 use crate::games::game_ext::GameExt;
+use crate::games::traits::in_game_time::InGameTime;
+use crate::games::resolver::*;
 use crate::util::{get_stack_u32, get_stack_u8};
+use soulmemory_common::report::ResolutionReport;
 
 pub struct DarkSoulsPrepareToDieEdition
 {
     process: Process,
     event_flag_man: Pointer,
+    game_data_man: Pointer,
     event_flags: Arc<Mutex<Vec<EventFlag>>>,
     set_event_flag_hook: Option<HookPoint>,
+    resolution: ResolutionReport,
 }
 
 impl DarkSoulsPrepareToDieEdition
@@ -44,8 +49,10 @@ impl DarkSoulsPrepareToDieEdition
         {
             process: Process::new("darksouls.exe"),
             event_flag_man: Pointer::default(),
+            game_data_man: Pointer::default(),
             event_flags: Arc::new(Mutex::new(Vec::new())),
             set_event_flag_hook: None,
+            resolution: ResolutionReport::default(),
         }
     }
 }
@@ -63,8 +70,35 @@ impl BufferedEventFlags for DarkSoulsPrepareToDieEdition
         let value = self.event_flag_man.read_u32_rel(Some(offset)) as usize;
         return (value & mask) != 0;
     }
+
+    //The game's set function uses the same custom calling convention, write the bit directly instead
+    fn set_event_flag_state(&self, event_flag: u32, state: bool) -> Result<(), String>
+    {
+        if !self.process.is_attached()
+        {
+            return Err(String::from("not attached to the game"));
+        }
+
+        let (offset, mask) = get_event_flag_offset(event_flag);
+        let value = self.event_flag_man.read_u32_rel(Some(offset)) as usize;
+        let value = if state { value | mask } else { value & !mask };
+        self.event_flag_man.write_u32_rel(Some(offset), value as u32);
+        self.event_flags.lock().unwrap().push(EventFlag::from_state(chrono::offset::Local::now(), event_flag, state));
+        Ok(())
+    }
 }
 
+impl InGameTime for DarkSoulsPrepareToDieEdition
+{
+    fn get_in_game_time_milliseconds(&self) -> u32
+    {
+        if !self.process.is_attached()
+        {
+            return 0;
+        }
+        return self.game_data_man.read_u32_rel(Some(0x68));
+    }
+}
 
 impl Game for DarkSoulsPrepareToDieEdition
 {
@@ -75,11 +109,20 @@ impl Game for DarkSoulsPrepareToDieEdition
             unsafe
             {
                 self.process.refresh()?;
-                self.event_flag_man = self.process.scan_abs("event flags", "56 8B F1 8B 46 1C 50 A1 ? ? ? ? 32 C9", 8, vec![0, 0, 0])?;
-                let set_event_flag_address = self.process.scan_abs("set_event_flag", "80 b8 14 01 00 00 00 56 8b 74 24 08 74 ? 57 51 50", 0, Vec::new())?.get_base_address();
 
-                let h = Hooker::new(set_event_flag_address, HookType::JmpBack(capture_the_flag), CallbackOption::None, 0, HookFlags::empty());
-                self.set_event_flag_hook = Some(h.hook().unwrap());
+                //A pattern that isn't found only disables its own capability, the rest of the game keeps working
+                let mut resolution = ResolutionReport::default();
+                self.event_flag_man = resolve_scan(&mut resolution, EVENT_FLAGS, "event flags", self.process.scan_abs("event flags", "56 8B F1 8B 46 1C 50 A1 ? ? ? ? 32 C9", 8, vec![0, 0, 0]));
+                let set_event_flag_address = resolve_scan(&mut resolution, EVENT_FLAGS, "set_event_flag", self.process.scan_abs("set_event_flag", "80 b8 14 01 00 00 00 56 8b 74 24 08 74 ? 57 51 50", 0, Vec::new())).get_base_address();
+                self.game_data_man = resolve_scan(&mut resolution, IN_GAME_TIME, "GameDataMan", self.process.scan_abs("GameDataMan", "8b 0d ? ? ? ? 8b 41 30 8b 4d 64", 2, vec![0, 0]));
+                self.resolution = resolution;
+
+                //Only hooked when all of the event flag patterns were found
+                if self.resolution.is_available(EVENT_FLAGS)
+                {
+                    let h = Hooker::new(set_event_flag_address, HookType::JmpBack(capture_the_flag), CallbackOption::None, 0, HookFlags::empty());
+                    self.set_event_flag_hook = Some(h.hook().unwrap());
+                }
 
                 info!("event_flag_man base address: 0x{:x}", self.event_flag_man.get_base_address());
                 info!("set event flag address     : 0x{:x}", set_event_flag_address);
@@ -94,7 +137,10 @@ impl Game for DarkSoulsPrepareToDieEdition
     fn get_dx_version(&self) -> DxVersion {
         DxVersion::Dx9
     }
-    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { Some(Box::new(self)) }
+    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { if self.resolution.is_available(EVENT_FLAGS) { Some(Box::new(self)) } else { None } }
+    fn in_game_time(&mut self) -> Option<Box<&mut dyn InGameTime>> { if self.resolution.is_available(IN_GAME_TIME) { Some(Box::new(self)) } else { None } }
+    fn resolution_report(&self) -> Option<&ResolutionReport> { Some(&self.resolution) }
+    //No loading state, SoulMemory knows no loading screen flag for Dark Souls 1
 
     fn as_any(&self) -> &dyn Any
     {
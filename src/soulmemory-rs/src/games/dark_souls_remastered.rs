@@ -20,7 +20,7 @@
 
 use std::{mem};
 use std::any::Any;
-use std::ops::Deref;
+use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, Mutex};
 use ilhook::x64::HookPoint;
 use log::info;
@@ -29,63 +29,173 @@ use windows::Win32::UI::Input::XboxController::XINPUT_STATE;
 use crate::App;
 use crate::games::dx_version::DxVersion;
 use crate::games::game::Game;
-use crate::games::GameExt;
+use crate::games::{read_chr_dbg_flags, ChrDbgFlag, GameExt, GetSetChrDbgFlags};
+use crate::games::hook_guard::{call_hooked_function, is_calling_hooked_function};
 use crate::games::ilhook::*;
-use crate::tas::tas::{get_xinput_get_state_fn_address, tas_ai_toggle, XInputGetState};
+use crate::games::resolver::*;
+use crate::memory::live::LiveMemory;
+use crate::memory::PointerChain;
+use crate::tas::inputs::TasInputs;
+use crate::tas::tas::{get_xinput_get_state_fn_address, tas_ai_toggle, tas_inputs, XInputGetState};
 use crate::tas::toggle_mode::ToggleMode;
 use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
+use crate::games::traits::in_game_time::InGameTime;
+use crate::games::traits::player_position::PlayerPosition;
+use crate::util::vector3f::Vector3f;
+use soulmemory_common::report::ResolutionReport;
+use soulmemory_common::version::{Version, VersionSupport};
 
 
-type FnGetEventFlag = fn(event_flag_man: u64, event_flag: u32) -> u8;
+type FnGetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32) -> u8;
+type FnSetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32, state: u8);
 pub struct DarkSoulsRemastered
 {
     process: Process,
 
     ai_timer: Pointer,
     game_data_man: Pointer,
+    chr_dbg_flags: PointerChain,
+    chr_pos_data: Pointer,
 
     event_flag_man: Pointer,
     fn_get_event_flag: FnGetEventFlag,
+    fn_set_event_flag: FnSetEventFlag,
     event_flags: Arc<Mutex<Vec<EventFlag>>>,
 
     set_event_flag_hook: Option<HookPoint>,
     xinput_get_state_hook: Option<HookPoint>,
+    resolution: ResolutionReport,
 
     pub ai_timer_toggle_threshold: f32,
     pub ai_timer_toggle_mode: ToggleMode,
+    //Buttons pressed by trigger rules
+    pub tas_inputs: TasInputs,
 }
 
 impl DarkSoulsRemastered
 {
     pub fn new() -> Self
     {
+        unsafe extern "win64" fn empty(_: u64, _: u32) -> u8 { 0 }
+        unsafe extern "win64" fn empty_set(_: u64, _: u32, _: u8) {}
+
         DarkSoulsRemastered
         {
             process: Process::new("DarkSoulsRemastered.exe"),
 
             ai_timer: Pointer::default(),
             game_data_man: Pointer::default(),
+            chr_dbg_flags: PointerChain::default(),
+            chr_pos_data: Pointer::default(),
 
             event_flag_man: Pointer::default(),
-            fn_get_event_flag: |_,_|{return 0},
+            fn_get_event_flag: empty,
+            fn_set_event_flag: empty_set,
             event_flags: Arc::new(Mutex::new(Vec::new())),
 
             set_event_flag_hook: None,
             xinput_get_state_hook: None,
+            resolution: ResolutionReport::default(),
 
             ai_timer_toggle_threshold: 4.8f32,
             ai_timer_toggle_mode: ToggleMode::None,
+            tas_inputs: TasInputs::new(),
         }
     }
 
-    pub fn get_in_game_time_milliseconds(&self) -> u32
+    pub fn get_ai_timer_value(&self) -> f32
+    {
+        self.ai_timer.read_f32_rel(Some(0x24))
+    }
+}
+
+impl InGameTime for DarkSoulsRemastered
+{
+    fn get_in_game_time_milliseconds(&self) -> u32
     {
+        if !self.process.is_attached()
+        {
+            return 0;
+        }
         return self.game_data_man.read_u32_rel(Some(0xa4));
     }
+}
 
-    pub fn get_ai_timer_value(&self) -> f32
+//Lordran is a single coordinate space, positions don't need a map
+impl PlayerPosition for DarkSoulsRemastered
+{
+    fn get_position(&self) -> Vector3f
     {
-        self.ai_timer.read_f32_rel(Some(0x24))
+        if !self.process.is_attached()
+        {
+            return Vector3f::default();
+        }
+
+        let x = self.chr_pos_data.read_f32_rel(Some(0x10));
+        let y = self.chr_pos_data.read_f32_rel(Some(0x14));
+        let z = self.chr_pos_data.read_f32_rel(Some(0x18));
+        return Vector3f::new(x, y, z);
+    }
+
+    fn set_position(&self, position: &Vector3f)
+    {
+        if self.process.is_attached()
+        {
+            self.chr_pos_data.write_f32_rel(Some(0x10), position.x);
+            self.chr_pos_data.write_f32_rel(Some(0x14), position.y);
+            self.chr_pos_data.write_f32_rel(Some(0x18), position.z);
+        }
+    }
+
+    fn get_rotation(&self) -> Option<f32>
+    {
+        if !self.process.is_attached()
+        {
+            return None;
+        }
+        return Some(self.chr_pos_data.read_f32_rel(Some(0x4)));
+    }
+
+    fn set_rotation(&self, rotation: f32)
+    {
+        if self.process.is_attached()
+        {
+            self.chr_pos_data.write_f32_rel(Some(0x4), rotation);
+        }
+    }
+}
+
+//Dark Souls 1 keeps its ChrDbg globals packed, stamina, magic and arrows are not player specific
+const CHR_DBG_FLAGS: [(u32, &str); 13] =
+[
+    (0 , "Player No Dead"),
+    (1 , "Player Exterminate"),
+    (2 , "All No Stamina Consume"),
+    (3 , "All No Magic Qty Consume"),
+    (4 , "All No Arrow Consume"),
+    (5 , "Player Hide"),
+    (6 , "Player Silenced"),
+    (7 , "All No Dead"),
+    (8 , "All No Damage"),
+    (9 , "All No Hit"),
+    (10, "All No Attack"),
+    (11, "All No Move"),
+    (12, "All No Update Ai"),
+];
+
+impl GetSetChrDbgFlags for DarkSoulsRemastered
+{
+    fn get_flags(&self) -> Vec<ChrDbgFlag>
+    {
+        return read_chr_dbg_flags(&LiveMemory::new(), &self.chr_dbg_flags, &CHR_DBG_FLAGS);
+    }
+
+    fn set_flag(&self, flag: u32, value: bool)
+    {
+        if self.process.is_attached()
+        {
+            self.chr_dbg_flags.write_u8(&LiveMemory::new(), flag as usize, value as u8);
+        }
     }
 }
 
@@ -98,11 +208,31 @@ impl BufferedEventFlags for DarkSoulsRemastered
     fn get_event_flag_state(&self, event_flag: u32) -> bool
     {
         let event_flag_man_address = self.event_flag_man.read_u32_rel(None) as u64; //Bit memes because DSR is 64bit, compiled with 32bit wide pointers
-        let result = (self.fn_get_event_flag)(event_flag_man_address, event_flag);
+        let result = unsafe { (self.fn_get_event_flag)(event_flag_man_address, event_flag) };
         return result == 1;
     }
+
+    fn set_event_flag_state(&self, event_flag: u32, state: bool) -> Result<(), String>
+    {
+        if !self.process.is_attached()
+        {
+            return Err(String::from("not attached to the game"));
+        }
+
+        let event_flag_man_address = self.event_flag_man.read_u32_rel(None) as u64;
+        call_hooked_function(|| unsafe { (self.fn_set_event_flag)(event_flag_man_address, event_flag, state as u8) });
+        self.event_flags.lock().unwrap().push(EventFlag::from_state(chrono::offset::Local::now(), event_flag, state));
+        Ok(())
+    }
 }
 
+//The versions SoulMemory's DsrVersion knows about
+const SUPPORTED_VERSIONS: &[VersionSupport] =
+&[
+    VersionSupport { min_version: Version::new(1, 0, 0, 0), max_version: Version::new(1, 0, 0, 0), capabilities: &[EVENT_FLAGS, IN_GAME_TIME, PLAYER_POSITION] },
+    VersionSupport { min_version: Version::new(1, 3, 0, 0), max_version: Version::new(1, 3, 1, 0), capabilities: &[EVENT_FLAGS, IN_GAME_TIME, PLAYER_POSITION] },
+];
+
 impl Game for DarkSoulsRemastered
 {
     fn refresh(&mut self) -> Result<(), String>
@@ -112,21 +242,41 @@ impl Game for DarkSoulsRemastered
             unsafe
             {
                 self.process.refresh()?;
-                self.game_data_man  = self.process.scan_rel("GameDataMan", "48 8b 05 ? ? ? ? 48 8b 50 10 48 89 54 24 60", 3, 7, vec![0])?;
-                self.ai_timer       = self.process.scan_rel("ai timer", "48 8b 0d ? ? ? ? 48 85 c9 74 0e 48 83 c1 28", 3, 7, vec![0])?;
-                self.event_flag_man = self.process.scan_rel("event flags", "48 8B 0D ? ? ? ? 99 33 C2 45 33 C0 2B C2 8D 50 F6", 3, 7, vec![0])?;
 
-                let set_event_flag_address = self.process.scan_abs("set_event_flag", "48 89 5c 24 08 57 48 83 ec 20 80 b9 24 02 00 00 00 41 0f b6 f8", 0, Vec::new())?.get_base_address();
-                let get_event_flag_address = self.process.scan_abs("get_event_flag", "40 53 48 83 ec 20 80 b9 24 02 00 00 00 8b da 74 4d", 0, Vec::new())?.get_base_address();
-                self.fn_get_event_flag = mem::transmute(get_event_flag_address);
+                //A pattern that isn't found only disables its own capability, the rest of the game keeps working
+                let mut resolution = ResolutionReport::default();
+                self.event_flag_man = resolve_scan(&mut resolution, EVENT_FLAGS, "event flags", self.process.scan_rel("event flags", "48 8B 0D ? ? ? ? 99 33 C2 45 33 C0 2B C2 8D 50 F6", 3, 7, vec![0]));
+                let set_event_flag_address = resolve_scan(&mut resolution, EVENT_FLAGS, "set_event_flag", self.process.scan_abs("set_event_flag", "48 89 5c 24 08 57 48 83 ec 20 80 b9 24 02 00 00 00 41 0f b6 f8", 0, Vec::new())).get_base_address();
+                let get_event_flag_address = resolve_scan(&mut resolution, EVENT_FLAGS, "get_event_flag", self.process.scan_abs("get_event_flag", "40 53 48 83 ec 20 80 b9 24 02 00 00 00 8b da 74 4d", 0, Vec::new())).get_base_address();
+                self.ai_timer       = resolve_scan(&mut resolution, AI_TOGGLE, "ai timer", self.process.scan_rel("ai timer", "48 8b 0d ? ? ? ? 48 85 c9 74 0e 48 83 c1 28", 3, 7, vec![0]));
+                self.game_data_man  = resolve_scan(&mut resolution, IN_GAME_TIME, "GameDataMan", self.process.scan_rel("GameDataMan", "48 8b 05 ? ? ? ? 48 8b 50 10 48 89 54 24 60", 3, 7, vec![0]));
+                //ChrDbg stays off until its pattern (80 3d ? ? ? ? 00 48 8b 8f ? ? ? ? 0f b6 db) and flag layout are verified against the game
+                resolution.fail(CHR_DBG, "ChrDbgFlags", String::from("not verified for this game yet"));
+                self.chr_pos_data   = resolve_scan(&mut resolution, PLAYER_POSITION, "WorldChrMan", self.process.scan_rel("WorldChrMan", "48 8b 05 ? ? ? ? 48 8b 48 68 48 85 c9 0f 84 ? ? ? ? 48 39 5e 10 0f 84 ? ? ? ? 48", 3, 7, vec![0, 0x68, 0x68, 0x28]));
+                //Quitout stays off until there is a source for the quit request field in MenuMan, it shows up as unavailable in the diagnostics
+                resolution.fail(QUITOUT, "MenuMan", String::from("quit request field not known for this game yet"));
+                self.resolution = resolution;
+
+                //The functions are only called and hooked when all of the event flag patterns were found
+                if self.resolution.is_available(EVENT_FLAGS)
+                {
+                    self.fn_get_event_flag = mem::transmute(get_event_flag_address);
+                    self.fn_set_event_flag = mem::transmute(set_event_flag_address);
+
+                    #[cfg(target_arch = "x86_64")]
+                    {
+                        let h = Hooker::new(set_event_flag_address, HookType::JmpBack(set_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
+                        self.set_event_flag_hook = Some(h.hook().unwrap());
+                    }
+                }
 
                 #[cfg(target_arch = "x86_64")]
                 {
-                    let h = Hooker::new(set_event_flag_address, HookType::JmpBack(set_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
-                    self.set_event_flag_hook = Some(h.hook().unwrap());
-
-                    let h = Hooker::new(get_xinput_get_state_fn_address() as usize, HookType::Retn(xinput_get_state_hook_fn), CallbackOption::None, 0, HookFlags::empty());
-                    self.xinput_get_state_hook = Some(h.hook().unwrap());
+                    if self.resolution.is_available(AI_TOGGLE)
+                    {
+                        let h = Hooker::new(get_xinput_get_state_fn_address() as usize, HookType::Retn(xinput_get_state_hook_fn), CallbackOption::None, 0, HookFlags::empty());
+                        self.xinput_get_state_hook = Some(h.hook().unwrap());
+                    }
                 }
 
                 info!("game_data_man base address : 0x{:x}", self.game_data_man.get_base_address());
@@ -147,7 +297,13 @@ impl Game for DarkSoulsRemastered
         DxVersion::Dx11
     }
 
-    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { Some(Box::new(self)) }
+    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { if self.resolution.is_available(EVENT_FLAGS) { Some(Box::new(self)) } else { None } }
+    fn in_game_time(&mut self) -> Option<Box<&mut dyn InGameTime>> { if self.resolution.is_available(IN_GAME_TIME) { Some(Box::new(self)) } else { None } }
+    fn player_position(&mut self) -> Option<Box<&mut dyn PlayerPosition>> { if self.resolution.is_available(PLAYER_POSITION) { Some(Box::new(self)) } else { None } }
+    fn chr_dbg_flags(&mut self) -> Option<Box<&mut dyn GetSetChrDbgFlags>> { if self.resolution.is_available(CHR_DBG) { Some(Box::new(self)) } else { None } }
+    fn resolution_report(&self) -> Option<&ResolutionReport> { Some(&self.resolution) }
+    fn supported_versions(&self) -> &'static [VersionSupport] { SUPPORTED_VERSIONS }
+    //No loading state, SoulMemory knows no loading screen flag for Dark Souls 1
 
     fn as_any(&self) -> &dyn Any
     {
@@ -159,6 +315,11 @@ impl Game for DarkSoulsRemastered
 #[cfg(target_arch = "x86_64")]
 unsafe extern "win64" fn set_event_flag_hook_fn(registers: *mut Registers, _:usize)
 {
+    if is_calling_hooked_function()
+    {
+        return;
+    }
+
     let instance = App::get_instance();
     let app = instance.lock().unwrap();
 
@@ -178,15 +339,16 @@ unsafe extern "win64" fn xinput_get_state_hook_fn(registers: *mut Registers, ori
     let original_func: XInputGetState = mem::transmute(ori_func_ptr);
 
     let instance = App::get_instance();
-    let app = instance.lock().unwrap();
+    let mut app = instance.lock().unwrap();
 
-    if let Some(dsr) = GameExt::get_game_ref::<DarkSoulsRemastered>(app.game.deref())
+    if let Some(dsr) = GameExt::get_game_mut::<DarkSoulsRemastered>(app.game.deref_mut())
     {
         let dw_user_index = (*registers).rcx as u32;
         let p_state = (*registers).rdx as *mut XINPUT_STATE;
 
         let res = original_func(dw_user_index, p_state);
         tas_ai_toggle(dsr.ai_timer_toggle_mode, dsr.get_ai_timer_value(), dsr.ai_timer_toggle_threshold, p_state);
+        tas_inputs(&mut dsr.tas_inputs, p_state);
         return res as usize;
     }
     panic!("Failed to resolve DSR");
@@ -15,18 +15,32 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::any::Any;
+use soulmemory_common::report::ResolutionReport;
+use soulmemory_common::version::VersionSupport;
 use crate::games::dx_version::DxVersion;
+use crate::games::GetSetChrDbgFlags;
 use crate::games::traits::buffered_emevd_logger::BufferedEmevdLogger;
 use crate::games::traits::buffered_event_flags::BufferedEventFlags;
 use crate::games::traits::player_position::PlayerPosition;
+use crate::games::traits::in_game_time::InGameTime;
+use crate::games::traits::loading_state::LoadingState;
+use crate::games::traits::quitout::Quitout;
 
 pub trait Game
 {
     fn refresh(&mut self) -> Result<(), String>;
     fn get_dx_version(&self) -> DxVersion;
     fn player_position(&mut self) -> Option<Box<&mut dyn PlayerPosition>>{ None }
+    fn in_game_time(&mut self) -> Option<Box<&mut dyn InGameTime>>{ None }
+    fn loading_state(&mut self) -> Option<Box<&mut dyn LoadingState>>{ None }
+    fn quitout(&mut self) -> Option<Box<&mut dyn Quitout>>{ None }
+    fn chr_dbg_flags(&mut self) -> Option<Box<&mut dyn GetSetChrDbgFlags>>{ None }
     fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>>{ None }
     fn buffered_emevd_logger(&mut self) -> Option<Box<&mut dyn BufferedEmevdLogger>>{ None }
+    ///What was found when attaching, for games that record their scans
+    fn resolution_report(&self) -> Option<&ResolutionReport>{ None }
+    ///Versions the game's patterns and offsets support, empty when the game doesn't declare them
+    fn supported_versions(&self) -> &'static [VersionSupport]{ &[] }
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
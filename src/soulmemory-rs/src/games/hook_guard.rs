@@ -0,0 +1,37 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::cell::Cell;
+
+thread_local!
+{
+    static CALLING_HOOKED_FUNCTION: Cell<bool> = const { Cell::new(false) };
+}
+
+///Call a game function that is hooked by soulmemory-rs itself. The caller already holds the App lock,
+///so the hook would deadlock when it tries to lock the App again. Hooks check is_calling_hooked_function and return early.
+pub fn call_hooked_function<T>(f: impl FnOnce() -> T) -> T
+{
+    CALLING_HOOKED_FUNCTION.with(|c| c.set(true));
+    let result = f();
+    CALLING_HOOKED_FUNCTION.with(|c| c.set(false));
+    return result;
+}
+
+pub fn is_calling_hooked_function() -> bool
+{
+    return CALLING_HOOKED_FUNCTION.with(|c| c.get());
+}
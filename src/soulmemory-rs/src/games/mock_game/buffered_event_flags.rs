@@ -29,4 +29,10 @@ impl BufferedEventFlags for MockGame
     {
         return true;
     }
+
+    fn set_event_flag_state(&self, event_flag: u32, state: bool) -> Result<(), String>
+    {
+        self.raise_event_flag(EventFlag::from_state(chrono::offset::Local::now(), event_flag, state));
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -16,6 +16,7 @@
 
 use std::any::Any;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use rand::random;
 use crate::darkscript3::sekiro_emedf::Emedf;
 use crate::games;
@@ -24,6 +25,7 @@ use crate::games::dx_version::DxVersion;
 use crate::games::traits::buffered_emevd_logger::{BufferedEmevdCall, BufferedEmevdLogger};
 use crate::games::traits::buffered_event_flags::BufferedEventFlags;
 use crate::games::traits::buffered_event_flags::EventFlag;
+use crate::games::traits::in_game_time::InGameTime;
 
 pub mod buffered_event_flags;
 mod buffered_emevd_logger;
@@ -33,6 +35,7 @@ pub struct MockGame
     event_flags: Arc<Mutex<Vec<EventFlag>>>,
     emevd_buffer: Arc<Mutex<Vec<BufferedEmevdCall>>>,
     emedf: Emedf,
+    started: Instant,
 }
 
 impl MockGame
@@ -50,6 +53,7 @@ impl MockGame
             event_flags: Arc::new(Mutex::new(vec)),
             emevd_buffer: Arc::new(Mutex::new(Vec::new())),
             emedf: games::sekiro::load_emevd(), //use sekiro emevd for now
+            started: Instant::now(),
         }
     }
 
@@ -68,6 +72,15 @@ impl MockGame
     }
 }
 
+//Runs from the moment the mock game is created
+impl InGameTime for MockGame
+{
+    fn get_in_game_time_milliseconds(&self) -> u32
+    {
+        return self.started.elapsed().as_millis() as u32;
+    }
+}
+
 impl Game for MockGame
 {
     fn refresh(&mut self) -> Result<(), String>
@@ -81,6 +94,7 @@ impl Game for MockGame
 
     fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { Some(Box::new(self)) }
     fn buffered_emevd_logger(&mut self) -> Option<Box<&mut dyn BufferedEmevdLogger>> { Some(Box::new(self)) }
+    fn in_game_time(&mut self) -> Option<Box<&mut dyn InGameTime>> { Some(Box::new(self)) }
 
     fn as_any(&self) -> &dyn Any
     {
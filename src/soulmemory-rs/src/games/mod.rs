@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use crate::memory::{Memory, PointerChain};
+
 pub mod traits;
 mod dark_souls_prepare_to_die_edition;
 mod dark_souls_remastered;
@@ -27,6 +29,8 @@ mod mock_game;
 pub mod dx_version;
 mod game;
 mod game_ext;
+pub(crate) mod hook_guard;
+pub(crate) mod resolver;
 
 
 #[cfg(target_arch = "x86")]
@@ -64,3 +68,13 @@ pub trait GetSetChrDbgFlags
     fn get_flags(&self) -> Vec<ChrDbgFlag>;
     fn set_flag(&self, flag: u32, value: bool);
 }
+
+///Read a block of byte sized ChrDbg flags, every entry in layout is an (offset, name) pair
+pub(crate) fn read_chr_dbg_flags(memory: &dyn Memory, chr_dbg_flags: &PointerChain, layout: &[(u32, &str)]) -> Vec<ChrDbgFlag>
+{
+    let size = layout.iter().map(|(offset, _)| *offset as usize + 1).max().unwrap_or(0);
+    let mut buffer = vec![0u8; size];
+    chr_dbg_flags.read_bytes(memory, 0, &mut buffer);
+
+    return layout.iter().map(|(offset, name)| (*offset, String::from(*name), buffer[*offset as usize] == 1)).collect();
+}
@@ -0,0 +1,33 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use log::info;
+use soulmemory_common::tables::PatternTable;
+use soulmemory_common::version::Version;
+use crate::util::dll_path;
+
+pub(crate) use soulmemory_common::games::resolver::*;
+
+///Patterns, pointers and offsets for a game, built into the dll and patched by <dll name>.<game>.json next to the dll
+pub(crate) fn load_pattern_table(game: &str, builtin: &str) -> Result<PatternTable<Version>, String>
+{
+    let override_path = dll_path().map(|path| PatternTable::<Version>::override_path(&path, game)).unwrap_or_default();
+    if override_path.exists()
+    {
+        info!("pattern overrides from {}", override_path.display());
+    }
+    return PatternTable::load(builtin, &override_path);
+}
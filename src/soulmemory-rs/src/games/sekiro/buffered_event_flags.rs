@@ -15,8 +15,8 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::{Arc, Mutex};
-use mem_rs::prelude::ReadWrite;
 use crate::games::Sekiro;
+use crate::games::hook_guard::call_hooked_function;
 use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
 
 impl BufferedEventFlags for Sekiro
@@ -27,7 +27,19 @@ impl BufferedEventFlags for Sekiro
     }
 
     fn get_event_flag_state(&self, event_flag: u32) -> bool {
-        let result = (self.fn_get_event_flag)(self.event_flag_man.read_u64_rel(None), event_flag);
+        let result = unsafe { (self.fn_get_event_flag)(self.layout.event_flag_man(&self.memory), event_flag) };
         return result == 1;
     }
+
+    fn set_event_flag_state(&self, event_flag: u32, state: bool) -> Result<(), String>
+    {
+        if !self.process.is_attached()
+        {
+            return Err(String::from("not attached to the game"));
+        }
+
+        call_hooked_function(|| unsafe { (self.fn_set_event_flag)(self.layout.event_flag_man(&self.memory), event_flag, state as u8, 0) });
+        self.event_flags.lock().unwrap().push(EventFlag::from_state(chrono::offset::Local::now(), event_flag, state));
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -14,7 +14,6 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use mem_rs::prelude::{BaseReadWrite, ReadWrite};
 use crate::games::{ChrDbgFlag, GetSetChrDbgFlags, Sekiro};
 
 #[derive(Eq, PartialEq, Hash, Copy, Clone)]
@@ -42,7 +41,7 @@ impl GetSetChrDbgFlags for Sekiro
     fn get_flags(&self) -> Vec<ChrDbgFlag>
     {
         let mut buffer = [0u8; 17];
-        self.chr_dbg_flags.read_memory_rel(None, &mut buffer);
+        self.layout.chr_dbg_flags.read_bytes(&self.memory, 0, &mut buffer);
 
         let mut result = Vec::new();
         result.push((SekiroChrDbgFlag::PlayerNoDead                as u32, String::from("Player No Dead")                 , buffer[SekiroChrDbgFlag::PlayerNoDead                   as usize] == 1));
@@ -67,7 +66,7 @@ impl GetSetChrDbgFlags for Sekiro
         if self.process.is_attached()
         {
             let value = match value{ true => 1, false => 0};
-            self.chr_dbg_flags.write_u8_rel(Some(flag as usize), value);
+            self.layout.chr_dbg_flags.write_u8(&self.memory, flag as usize, value);
         }
     }
 }
\ No newline at end of file
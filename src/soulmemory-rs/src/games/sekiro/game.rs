@@ -22,11 +22,20 @@ use ilhook::x64::{CallbackOption, Hooker, HookFlags, HookType, Registers};
 use log::info;
 use crate::App;
 use crate::darkscript3::sekiro_emedf::Emedf;
-use crate::games::{Game, GameExt, Sekiro};
+use crate::games::{Game, GameExt, GetSetChrDbgFlags, Sekiro};
 use crate::games::dx_version::DxVersion;
+use crate::games::hook_guard::is_calling_hooked_function;
+use crate::games::resolver::*;
+use soulmemory_common::games::sekiro::{SekiroLayout, PATTERN_TABLE, SUPPORTED_VERSIONS};
 use crate::games::traits::buffered_emevd_logger::{BufferedEmevdCall, BufferedEmevdLogger};
 use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
 use crate::games::traits::player_position::PlayerPosition;
+use crate::games::traits::in_game_time::InGameTime;
+use crate::games::traits::loading_state::LoadingState;
+use crate::games::traits::quitout::Quitout;
+use crate::util::game_version;
+use soulmemory_common::version::{Version, VersionSupport};
+use soulmemory_common::report::ResolutionReport;
 
 #[cfg(target_arch = "x86_64")]
 use crate::games::sekiro::emevd::emevd_event_hook_fn;
@@ -40,31 +49,40 @@ impl Game for Sekiro
             {
                 self.process.refresh()?;
 
-                self.event_flag_man = self.process.scan_rel("SprjEventFlagMan", "48 8b 0d ? ? ? ? 48 89 5c 24 50 48 89 6c 24 58 48 89 74 24 60", 3, 7, vec![0])?;
-                self.position = self.process.scan_rel("WorldChrManImp", "48 8B 35 ? ? ? ? 44 0F 28 18", 3, 7, vec![0, 0x48, 0x28])?;
-                self.chr_dbg_flags = self.process.scan_rel("chr dbg", "80 3d ? ? ? ? 00 0f ? ? ? ? ? 48 8b 9b d0 11 00 00", 2, 7, Vec::new())?;
-                self.menu_man = self.process.scan_rel("MenuMan", "48 8b 05 ? ? ? ? 0f b6 d1 48 8b 88 08 33 00 00", 3, 7, vec![0])?;
+                let version = game_version();
+                let table = load_pattern_table("sekiro", PATTERN_TABLE)?;
+                let mut resolver = Resolver::scan(&table, version.as_ref(), SUPPORTED_VERSIONS, &self.memory)?;
 
-                let set_event_flag_address = self.process.scan_abs("set_event_flag", "40 55 41 54 41 55 41 56 48 83 ec 58 80 b9 28 02 00 00 00 45 0f b6 e1 45 0f b6 e8 44 8b f2 48 8b e9", 0, Vec::new())?.get_base_address();
-                let get_event_flag_address = self.process.scan_abs("get_event_flag", "40 53 48 83 ec 20 80 b9 28 02 00 00 00 8b da", 0, Vec::new())?.get_base_address();
-                let emevd_events_address = self.process.scan_abs("emevd_events", "40 53 56 57 48 81 ec 40 01 00 00 48 c7 44 24 20 fe ff ff ff 0f 29 b4 24 30 01 00 00", 0, Vec::new())?.get_base_address();
-                self.fn_get_event_flag = mem::transmute(get_event_flag_address);
+                self.layout = SekiroLayout::resolve(&mut resolver);
+                let set_event_flag_address = resolver.address(EVENT_FLAGS, "set_event_flag");
+                let get_event_flag_address = resolver.address(EVENT_FLAGS, "get_event_flag");
+                let emevd_events_address = resolver.address(EMEVD_LOGGER, "emevd_events");
+                self.resolution = resolver.finish();
 
-                info!("event_flag_man base address: 0x{:x}", self.event_flag_man.get_base_address());
-                info!("WorldChrManImp base address: 0x{:x}", self.position.get_base_address());
-                info!("chr dbg flags  base address: 0x{:x}", self.chr_dbg_flags.get_base_address());
-                info!("MenuMan        base address: 0x{:x}", self.menu_man.get_base_address());
+                info!("event_flag_man base address: 0x{:x}", self.layout.event_flag_man.base);
+                info!("WorldChrManImp base address: 0x{:x}", self.layout.position.base);
+                info!("chr dbg flags  base address: 0x{:x}", self.layout.chr_dbg_flags.base);
+                info!("MenuMan        base address: 0x{:x}", self.layout.menu_man.base);
                 info!("set event flag address     : 0x{:x}", set_event_flag_address);
                 info!("get event flag address     : 0x{:x}", get_event_flag_address);
                 info!("emevd events               : 0x{:x}", emevd_events_address);
 
                 #[cfg(target_arch = "x86_64")]
                 {
-                    let h = Hooker::new(set_event_flag_address, HookType::JmpBack(set_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
-                    self.set_event_flag_hook = Some(h.hook().unwrap());
-
-                    let h = Hooker::new(emevd_events_address, HookType::JmpBack(emevd_event_hook_fn), CallbackOption::None, 0, HookFlags::empty());
-                    self.emevd_event_hook = Some(h.hook().unwrap());
+                    if self.resolution.is_available(EVENT_FLAGS)
+                    {
+                        self.fn_get_event_flag = mem::transmute(get_event_flag_address);
+                        self.fn_set_event_flag = mem::transmute(set_event_flag_address);
+
+                        let h = Hooker::new(set_event_flag_address, HookType::JmpBack(set_event_flag_hook_fn), CallbackOption::None, 0, HookFlags::empty());
+                        self.set_event_flag_hook = Some(h.hook().unwrap());
+                    }
+
+                    if self.resolution.is_available(EMEVD_LOGGER)
+                    {
+                        let h = Hooker::new(emevd_events_address, HookType::JmpBack(emevd_event_hook_fn), CallbackOption::None, 0, HookFlags::empty());
+                        self.emevd_event_hook = Some(h.hook().unwrap());
+                    }
                 }
             }
         }
@@ -78,9 +96,15 @@ impl Game for Sekiro
     fn get_dx_version(&self) -> DxVersion {
         DxVersion::Dx11
     }
-    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { Some(Box::new(self)) }
-    fn player_position(&mut self) -> Option<Box<&mut dyn PlayerPosition>>{ Some(Box::new(self)) }
-    fn buffered_emevd_logger(&mut self) -> Option<Box<&mut dyn BufferedEmevdLogger>>{ Some(Box::new(self)) }
+    fn event_flags(&mut self) -> Option<Box<&mut dyn BufferedEventFlags>> { if self.resolution.is_available(EVENT_FLAGS) { Some(Box::new(self)) } else { None } }
+    fn player_position(&mut self) -> Option<Box<&mut dyn PlayerPosition>>{ if self.resolution.is_available(PLAYER_POSITION) { Some(Box::new(self)) } else { None } }
+    fn in_game_time(&mut self) -> Option<Box<&mut dyn InGameTime>>{ if self.resolution.is_available(IN_GAME_TIME) { Some(Box::new(self)) } else { None } }
+    fn loading_state(&mut self) -> Option<Box<&mut dyn LoadingState>>{ if self.resolution.is_available(LOADING_STATE) { Some(Box::new(self)) } else { None } }
+    fn quitout(&mut self) -> Option<Box<&mut dyn Quitout>>{ if self.resolution.is_available(QUITOUT) { Some(Box::new(self)) } else { None } }
+    fn chr_dbg_flags(&mut self) -> Option<Box<&mut dyn GetSetChrDbgFlags>>{ if self.resolution.is_available(CHR_DBG) { Some(Box::new(self)) } else { None } }
+    fn buffered_emevd_logger(&mut self) -> Option<Box<&mut dyn BufferedEmevdLogger>>{ if self.resolution.is_available(EMEVD_LOGGER) { Some(Box::new(self)) } else { None } }
+    fn resolution_report(&self) -> Option<&ResolutionReport>{ Some(&self.resolution) }
+    fn supported_versions(&self) -> &'static [VersionSupport]{ SUPPORTED_VERSIONS }
     fn as_any(&self) -> &dyn Any
     {
         self
@@ -115,6 +139,11 @@ fn format_event(group: u32, type_: u32, emedf: &Emedf) -> String
 #[cfg(target_arch = "x86_64")]
 unsafe extern "win64" fn set_event_flag_hook_fn(registers: *mut Registers, _:usize)
 {
+    if is_calling_hooked_function()
+    {
+        return;
+    }
+
     let instance = App::get_instance();
     let app = instance.lock().unwrap();
 
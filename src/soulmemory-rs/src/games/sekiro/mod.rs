@@ -19,6 +19,9 @@
 
 mod game;
 mod player_position;
+mod in_game_time;
+mod loading_state;
+mod quitout;
 mod chr_dbg_flag;
 mod buffered_emevd_logger;
 mod buffered_event_flags;
@@ -30,6 +33,7 @@ use std::{mem, ptr};
 use std::sync::{Arc, Mutex};
 use log::info;
 use mem_rs::prelude::*;
+use soulmemory_common::report::ResolutionReport;
 use crate::App;
 use crate::darkscript3::sekiro_emedf::Emedf;
 use crate::games::{ChrDbgFlag, GameExt, GetSetChrDbgFlags};
@@ -40,53 +44,53 @@ use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag};
 use crate::games::traits::buffered_emevd_logger::*;
 use crate::util::vector3f::Vector3f;
 use crate::games::ilhook::*;
+use crate::memory::live::LiveMemory;
+use soulmemory_common::games::sekiro::SekiroLayout;
 
-type FnGetEventFlag = fn(event_flag_man: u64, event_flag: u32) -> u8;
+type FnGetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32) -> u8;
+type FnSetEventFlag = unsafe extern "win64" fn(event_flag_man: u64, event_flag: u32, state: u8, unknown: u8);
 
 
 pub struct Sekiro
 {
     process: Process,
+    memory: LiveMemory,
+    layout: SekiroLayout,
 
     event_flags: Arc<Mutex<Vec<EventFlag>>>,
-    event_flag_man: Pointer,
-    position: Pointer,
-    chr_dbg_flags: Pointer,
     fn_get_event_flag: FnGetEventFlag,
+    fn_set_event_flag: FnSetEventFlag,
     set_event_flag_hook: Option<HookPoint>,
     emevd_event_hook: Option<HookPoint>,
 
-    menu_man: Pointer,
     emedf: Emedf,
     emevd_buffer: Arc<Mutex<Vec<BufferedEmevdCall>>>,
+    resolution: ResolutionReport,
 }
 
 impl Sekiro
 {
     pub fn new() -> Self
     {
+        unsafe extern "win64" fn empty(_: u64, _: u32) -> u8 { 0 }
+        unsafe extern "win64" fn empty_set(_: u64, _: u32, _: u8, _: u8) {}
+
         Sekiro
         {
             process: Process::new("sekiro.exe"),
+            memory: LiveMemory::new(),
+            layout: SekiroLayout::default(),
             event_flags: Arc::new(Mutex::new(Vec::new())),
-            event_flag_man: Pointer::default(),
-            position: Pointer::default(),
-            chr_dbg_flags: Pointer::default(),
-            fn_get_event_flag: |_,_|{0},
+            fn_get_event_flag: empty,
+            fn_set_event_flag: empty_set,
             set_event_flag_hook: None,
             emevd_event_hook: None,
 
-            menu_man: Pointer::default(),
             emedf: load_emevd(),
             emevd_buffer: Arc::new(Mutex::new(Vec::new())),
+            resolution: ResolutionReport::default(),
         }
     }
-
-
-    pub fn request_quitout(&self)
-    {
-        self.menu_man.write_u32_rel(Some(0x23c), 1);
-    }
 }
 
 
@@ -14,7 +14,6 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use mem_rs::prelude::ReadWrite;
 use crate::games::Sekiro;
 use crate::games::traits::player_position::PlayerPosition;
 use crate::util::vector3f::Vector3f;
@@ -27,18 +26,11 @@ impl PlayerPosition for Sekiro
         {
             return Vector3f::default();
         }
-
-        let x = self.position.read_f32_rel(Some(0x80));
-        let y = self.position.read_f32_rel(Some(0x84));
-        let z = self.position.read_f32_rel(Some(0x88));
-
-        return Vector3f::new(x, y, z);
+        return self.layout.position(&self.memory);
     }
 
     fn set_position(&self, position: &Vector3f)
     {
-        self.position.write_f32_rel(Some(0x80), position.x);
-        self.position.write_f32_rel(Some(0x84), position.y);
-        self.position.write_f32_rel(Some(0x88), position.z);
+        self.layout.set_position(&self.memory, position);
     }
 }
\ No newline at end of file
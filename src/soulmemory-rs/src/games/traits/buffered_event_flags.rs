@@ -17,9 +17,12 @@
 use std::{fmt, mem};
 use std::fmt::Display;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum EventFlagValue
 {
     State(bool),
@@ -30,6 +33,8 @@ pub enum EventFlagValue
 pub struct EventFlag
 {
     pub time: DateTime<Local>,
+    //Monotonic clock reading taken together with time, not affected by system clock changes
+    pub instant: Instant,
     pub flag: u32,
     pub value: EventFlagValue,
 }
@@ -60,6 +65,7 @@ impl EventFlag
         EventFlag
         {
             time,
+            instant: Instant::now(),
             flag,
             value: EventFlagValue::State(state),
         }
@@ -70,6 +76,7 @@ impl EventFlag
         EventFlag
         {
             time,
+            instant: Instant::now(),
             flag,
             value: EventFlagValue::Quantity(quantity),
         }
@@ -81,6 +88,18 @@ pub trait BufferedEventFlags
     fn access_flag_storage(&self) -> &Arc<Mutex<Vec<EventFlag>>>;
     fn get_event_flag_state(&self, event_flag: u32) -> bool;
 
+    ///Read a flag that spans multiple bits. Returns None when the game does not support quantity flags.
+    fn get_event_flag_quantity(&self, _event_flag: u32, _bit_count: u8) -> Option<i32>
+    {
+        None
+    }
+
+    ///Write a flag through the game. Implementations also buffer the change, since the set_event_flag hooks skip calls made by soulmemory-rs.
+    fn set_event_flag_state(&self, _event_flag: u32, _state: bool) -> Result<(), String>
+    {
+        Err(String::from("setting event flags is not supported for this game"))
+    }
+
     fn get_buffered_flags(&mut self) -> Vec<EventFlag>
     {
         let mut event_flags = self.access_flag_storage().lock().unwrap();
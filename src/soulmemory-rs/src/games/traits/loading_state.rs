@@ -0,0 +1,129 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+pub use soulmemory_common::screen_state::ScreenState;
+
+pub trait LoadingState
+{
+    fn get_screen_state(&self) -> ScreenState;
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ScreenStateChange
+{
+    pub time: DateTime<Local>,
+    pub from: ScreenState,
+    pub to: ScreenState,
+    pub quitouts: u32,
+}
+
+///Turns the polled screen state into transitions and counts quitouts
+pub struct ScreenStateTracker
+{
+    current: Option<ScreenState>,
+    //A character was in game since the last time the main menu was reached
+    in_session: bool,
+    quitouts: u32,
+}
+
+impl ScreenStateTracker
+{
+    pub fn new() -> Self
+    {
+        ScreenStateTracker { current: None, in_session: false, quitouts: 0 }
+    }
+
+    pub fn current(&self) -> Option<ScreenState>
+    {
+        return self.current;
+    }
+
+    pub fn quitouts(&self) -> u32
+    {
+        return self.quitouts;
+    }
+
+    pub fn reset_quitouts(&mut self)
+    {
+        self.quitouts = 0;
+    }
+
+    ///Called every refresh, returns a change when the state differs from the last one
+    pub fn update(&mut self, state: ScreenState, time: DateTime<Local>) -> Option<ScreenStateChange>
+    {
+        let from = match self.current
+        {
+            Some(current) if current == state => return None,
+            //The first state is not a transition
+            None =>
+            {
+                self.current = Some(state);
+                self.in_session = state == ScreenState::InGame;
+                return None;
+            }
+            Some(current) => current,
+        };
+        self.current = Some(state);
+
+        match state
+        {
+            ScreenState::InGame => self.in_session = true,
+            //Games without quitout detection go straight back to the main menu
+            ScreenState::Quitout | ScreenState::MainMenu if self.in_session =>
+            {
+                self.in_session = false;
+                self.quitouts += 1;
+            }
+            _ => {}
+        }
+
+        return Some(ScreenStateChange { time, from, to: state, quitouts: self.quitouts });
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::games::traits::loading_state::*;
+
+    #[test]
+    pub fn transitions_and_quitouts()
+    {
+        let mut tracker = ScreenStateTracker::new();
+        assert_eq!(tracker.update(ScreenState::MainMenu, Local::now()), None);
+        assert_eq!(tracker.update(ScreenState::MainMenu, Local::now()), None);
+
+        //Loading a character does not count as a quitout
+        for state in [ScreenState::Loading, ScreenState::InGame, ScreenState::Loading, ScreenState::InGame]
+        {
+            assert_eq!(tracker.update(state, Local::now()).unwrap().to, state);
+        }
+        assert_eq!(tracker.quitouts(), 0);
+
+        //Quitout followed by the main menu is a single quitout
+        tracker.update(ScreenState::Quitout, Local::now());
+        tracker.update(ScreenState::Loading, Local::now());
+        let change = tracker.update(ScreenState::MainMenu, Local::now()).unwrap();
+        assert_eq!((change.from, change.quitouts), (ScreenState::Loading, 1));
+
+        //Without quitout detection the main menu counts
+        tracker.update(ScreenState::InGame, Local::now());
+        tracker.update(ScreenState::MainMenu, Local::now());
+        assert_eq!(tracker.quitouts(), 2);
+    }
+}
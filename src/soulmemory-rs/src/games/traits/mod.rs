@@ -17,3 +17,6 @@
 pub mod player_position;
 pub mod buffered_event_flags;
 pub mod buffered_emevd_logger;
+pub mod in_game_time;
+pub mod loading_state;
+pub mod quitout;
@@ -14,10 +14,19 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+
 use crate::util::vector3f::Vector3f;
+pub use soulmemory_common::map_id::MapId;
 
 pub trait PlayerPosition
 {
     fn get_position(&self) -> Vector3f;
     fn set_position(&self, position: &Vector3f);
+
+    ///Facing angle in radians, None when the game's rotation is not known
+    fn get_rotation(&self) -> Option<f32>{ None }
+    fn set_rotation(&self, _rotation: f32) {}
+
+    ///Map the position is relative to, None for games that use one coordinate space for the whole world
+    fn get_map_id(&self) -> Option<MapId>{ None }
 }
@@ -23,6 +23,12 @@ mod widgets;
 mod tas;
 mod render_hooks;
 mod darkscript3;
+mod event_flags;
+mod triggers;
+mod trackers;
+mod splits;
+mod positions;
+mod memory;
 
 use std::time::Duration;
 use std::ffi::c_void;
@@ -36,7 +42,7 @@ use crate::render_hooks::RenderHooks;
 
 pub use app::App;
 
-static mut HMODULE: HINSTANCE = HINSTANCE(std::ptr::null_mut());
+pub(crate) static mut HMODULE: HINSTANCE = HINSTANCE(std::ptr::null_mut());
 
 #[unsafe(no_mangle)]
 #[allow(non_snake_case)]
@@ -81,9 +87,10 @@ fn dispatched_dll_main()
     RenderHooks::init();
 
     info!("starting main loop");
+    let mut last_error = None;
     loop
     {
-        main_loop();
+        main_loop(&mut last_error);
         thread::sleep(Duration::from_millis(16));
     }
 }
@@ -94,14 +101,22 @@ fn dispatched_dll_detach()
 }
 
 
-fn main_loop()
+//Refresh errors repeat every frame until something changes, only log them when they do
+fn main_loop(last_error: &mut Option<String>)
 {
     let instance = App::get_instance();
     let mut app = instance.lock().unwrap();
 
     match app.refresh()
     {
-        Ok(()) => {}
-        Err(e) => error!("{}", e),
+        Ok(()) => *last_error = None,
+        Err(e) =>
+        {
+            if last_error.as_ref() != Some(&e)
+            {
+                error!("{}", e);
+                *last_error = Some(e);
+            }
+        }
     }
 }
@@ -0,0 +1,79 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::env;
+use std::ffi::c_void;
+use std::mem;
+use windows::core::PCWSTR;
+use windows::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::ProcessStatus::{GetModuleInformation, MODULEINFO};
+use windows::Win32::System::Threading::GetCurrentProcess;
+use crate::memory::{Memory, ModuleInfo};
+
+///Memory of the process the dll is injected in. Reads and writes go through Read/WriteProcessMemory so that
+///unmapped addresses fail instead of crashing the game.
+pub struct LiveMemory {}
+
+impl LiveMemory
+{
+    pub fn new() -> Self
+    {
+        LiveMemory {}
+    }
+}
+
+impl Memory for LiveMemory
+{
+    fn read(&self, address: usize, buffer: &mut [u8]) -> bool
+    {
+        let mut read = 0usize;
+        unsafe
+        {
+            let result = ReadProcessMemory(GetCurrentProcess(), address as *const c_void, buffer.as_mut_ptr() as *mut c_void, buffer.len(), Some(&mut read));
+            return result.is_ok() && read == buffer.len();
+        }
+    }
+
+    fn write(&self, address: usize, buffer: &[u8]) -> bool
+    {
+        let mut written = 0usize;
+        unsafe
+        {
+            let result = WriteProcessMemory(GetCurrentProcess(), address as *const c_void, buffer.as_ptr() as *const c_void, buffer.len(), Some(&mut written));
+            return result.is_ok() && written == buffer.len();
+        }
+    }
+
+    fn main_module(&self) -> Option<ModuleInfo>
+    {
+        unsafe
+        {
+            let module = GetModuleHandleW(PCWSTR::null()).ok()?;
+            let mut info = MODULEINFO::default();
+            GetModuleInformation(GetCurrentProcess(), module, &mut info, mem::size_of::<MODULEINFO>() as u32).ok()?;
+
+            let name = env::current_exe().ok()?.file_name()?.to_string_lossy().to_string();
+            return Some(ModuleInfo { name, base: info.lpBaseOfDll as usize, size: info.SizeOfImage as usize });
+        }
+    }
+
+    fn is_64_bit(&self) -> bool
+    {
+        return cfg!(target_pointer_width = "64");
+    }
+}
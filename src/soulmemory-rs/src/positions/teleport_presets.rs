@@ -0,0 +1,178 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::games::traits::player_position::MapId;
+use crate::util::config::{export, import, SavedPosition};
+use crate::util::{game_key, DATA_DIRECTORY};
+
+//Teleport presets are stored in <data directory>/<game>.teleports.json, grouped per map:
+//[{"map": "m60_42_36_00", "presets": [{"name": "Gatefront", "position": {"x": 1.0, "y": 2.0, "z": 3.0}, "rotation": 1.57}]}]
+//Games that use one coordinate space for the whole world keep their presets in a group without a map.
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct PresetGroup
+{
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map: Option<MapId>,
+    pub presets: Vec<SavedPosition>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct TeleportPresets
+{
+    groups: Vec<PresetGroup>,
+}
+
+impl TeleportPresets
+{
+    pub fn new() -> Self
+    {
+        TeleportPresets { groups: Vec::new() }
+    }
+
+    pub fn path(process_name: &str) -> PathBuf
+    {
+        return Path::new(DATA_DIRECTORY).join(format!("{}.teleports.json", game_key(process_name)));
+    }
+
+    ///Load the presets from a json file, a missing file means no presets
+    pub fn load(path: &Path) -> Result<Self, String>
+    {
+        if !path.exists()
+        {
+            return Ok(TeleportPresets::new());
+        }
+
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut presets = TeleportPresets::new();
+        for group in import::<Vec<PresetGroup>>(&json)?
+        {
+            presets.add_all(group.map, group.presets);
+        }
+        return Ok(presets);
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String>
+    {
+        if let Some(directory) = path.parent()
+        {
+            fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+        return fs::write(path, export(&self.groups)).map_err(|e| e.to_string());
+    }
+
+    ///Groups ordered by map, the group without a map first
+    pub fn groups(&self) -> &Vec<PresetGroup>
+    {
+        return &self.groups;
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        return self.groups.is_empty();
+    }
+
+    pub fn presets(&self, map: Option<MapId>) -> Option<&Vec<SavedPosition>>
+    {
+        return self.groups.iter().find(|g| g.map == map).map(|g| &g.presets);
+    }
+
+    pub fn add(&mut self, map: Option<MapId>, preset: SavedPosition)
+    {
+        let index = match self.groups.binary_search_by(|g| g.map.cmp(&map))
+        {
+            Ok(index) => index,
+            Err(index) =>
+            {
+                self.groups.insert(index, PresetGroup { map, presets: Vec::new() });
+                index
+            }
+        };
+        self.groups[index].presets.push(preset);
+    }
+
+    ///Add presets to a map, presets that are already there are skipped. Returns how many were added.
+    pub fn add_all(&mut self, map: Option<MapId>, presets: Vec<SavedPosition>) -> usize
+    {
+        let mut added = 0;
+        for preset in presets
+        {
+            if !self.presets(map).is_some_and(|p| p.contains(&preset))
+            {
+                self.add(map, preset);
+                added += 1;
+            }
+        }
+        return added;
+    }
+
+    ///Remove a preset, its group goes away with the last preset
+    pub fn remove(&mut self, map: Option<MapId>, index: usize)
+    {
+        if let Some(group_index) = self.groups.iter().position(|g| g.map == map)
+        {
+            let group = &mut self.groups[group_index];
+            if index < group.presets.len()
+            {
+                group.presets.remove(index);
+            }
+            if group.presets.is_empty()
+            {
+                self.groups.remove(group_index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::positions::teleport_presets::*;
+    use crate::util::vector3f::Vector3f;
+
+    fn preset(name: &str) -> SavedPosition
+    {
+        SavedPosition { name: String::from(name), position: Vector3f::new(1.0, 2.0, 3.0), rotation: None }
+    }
+
+    #[test]
+    pub fn groups_per_map()
+    {
+        let limgrave = Some(MapId::new(60, 42, 36, 0));
+        let stormveil = Some(MapId::new(10, 0, 0, 0));
+
+        let mut presets = TeleportPresets::new();
+        presets.add(limgrave, preset("gatefront"));
+        presets.add(None, preset("anywhere"));
+        presets.add(stormveil, preset("godrick"));
+        assert_eq!(presets.add_all(limgrave, vec![preset("gatefront"), preset("church")]), 1);
+
+        let maps: Vec<Option<MapId>> = presets.groups().iter().map(|g| g.map).collect();
+        assert_eq!(maps, vec![None, stormveil, limgrave]);
+        assert_eq!(presets.presets(limgrave).unwrap().len(), 2);
+
+        presets.remove(stormveil, 0);
+        assert_eq!(presets.presets(stormveil), None);
+
+        let json = export(presets.groups());
+        assert!(json.contains("\"map\": \"m60_42_36_00\""));
+        assert_eq!(import::<Vec<PresetGroup>>(&json).unwrap(), *presets.groups());
+    }
+}
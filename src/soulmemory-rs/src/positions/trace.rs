@@ -0,0 +1,290 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use chrono::Local;
+use crate::games::traits::player_position::MapId;
+use crate::util::vector3f::Vector3f;
+use crate::util::{game_key, DATA_DIRECTORY};
+
+//Tracks are csv files in <data directory>/traces, one sampled position per line:
+//ms,x,y,z,map
+//1016,-171.25,2.51,55.02,m60_42_36_00
+//The map column is empty for games that use one coordinate space for the whole world.
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TracePoint
+{
+    //Milliseconds since the recording started
+    pub ms: u64,
+    pub position: Vector3f,
+    pub map: Option<MapId>,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Track
+{
+    points: Vec<TracePoint>,
+}
+
+///How a live position compares to the nearest point of a reference track
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Deviation
+{
+    pub distance: f32,
+    //Time into the reference run at the nearest point
+    pub reference_ms: u64,
+    //Positive when behind the reference, negative when ahead
+    pub time_delta_ms: i64,
+}
+
+impl Track
+{
+    pub fn new() -> Self
+    {
+        Track { points: Vec::new() }
+    }
+
+    pub fn directory() -> PathBuf
+    {
+        return Path::new(DATA_DIRECTORY).join("traces");
+    }
+
+    ///Path for a new recording, named after the game and the current time
+    pub fn new_path(process_name: &str) -> PathBuf
+    {
+        return Self::directory().join(format!("{}_{}.csv", game_key(process_name), Local::now().format("%Y%m%d-%H%M%S")));
+    }
+
+    pub fn points(&self) -> &Vec<TracePoint>
+    {
+        return &self.points;
+    }
+
+    pub fn push(&mut self, point: TracePoint)
+    {
+        self.points.push(point);
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String>
+    {
+        let csv = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        return Self::parse_csv(&csv);
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String>
+    {
+        if let Some(directory) = path.parent()
+        {
+            fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+        return fs::write(path, self.to_csv()).map_err(|e| e.to_string());
+    }
+
+    pub fn to_csv(&self) -> String
+    {
+        let mut csv = String::from("ms,x,y,z,map\n");
+        for point in &self.points
+        {
+            let map = point.map.map(|m| m.to_string()).unwrap_or_default();
+            csv.push_str(&format!("{},{},{},{},{}\n", point.ms, point.position.x, point.position.y, point.position.z, map));
+        }
+        return csv;
+    }
+
+    pub fn parse_csv(csv: &str) -> Result<Self, String>
+    {
+        let mut track = Track::new();
+        for (index, line) in csv.lines().enumerate()
+        {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("ms")
+            {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split(',').map(|c| c.trim()).collect();
+            if columns.len() < 4
+            {
+                return Err(format!("line {}: expected ms,x,y,z,map", index + 1));
+            }
+
+            let number = |i: usize| columns[i].parse::<f32>().map_err(|e| format!("line {}: invalid number '{}': {}", index + 1, columns[i], e));
+            track.points.push(TracePoint
+            {
+                ms: columns[0].parse::<u64>().map_err(|e| format!("line {}: invalid ms '{}': {}", index + 1, columns[0], e))?,
+                position: Vector3f::new(number(1)?, number(2)?, number(3)?),
+                map: match columns.get(4).filter(|m| !m.is_empty())
+                {
+                    Some(map) => Some(map.parse::<MapId>().map_err(|e| format!("line {}: {}", index + 1, e))?),
+                    None => None,
+                },
+            });
+        }
+        return Ok(track);
+    }
+
+    ///Compare a position, reached ms into the run, to the nearest point of this track on the same map
+    pub fn compare(&self, position: &Vector3f, map: Option<MapId>, ms: u64) -> Option<Deviation>
+    {
+        let nearest = self.points.iter()
+            .filter(|p| p.map == map)
+            .map(|p| (p, p.position.distance(position)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        return Some(Deviation
+        {
+            distance: nearest.1,
+            reference_ms: nearest.0.ms,
+            time_delta_ms: ms as i64 - nearest.0.ms as i64,
+        });
+    }
+}
+
+///Samples the player position every refresh into a track, and compares it to a reference track while recording
+pub struct TraceRecorder
+{
+    recording: bool,
+    started: Instant,
+    track: Track,
+    reference: Option<(String, Track)>,
+    deviation: Option<Deviation>,
+}
+
+impl TraceRecorder
+{
+    pub fn new() -> Self
+    {
+        TraceRecorder
+        {
+            recording: false,
+            started: Instant::now(),
+            track: Track::new(),
+            reference: None,
+            deviation: None,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool
+    {
+        return self.recording;
+    }
+
+    ///Start a new recording, the previous track is discarded
+    pub fn start(&mut self, now: Instant)
+    {
+        self.recording = true;
+        self.started = now;
+        self.track = Track::new();
+        self.deviation = None;
+    }
+
+    pub fn stop(&mut self)
+    {
+        self.recording = false;
+    }
+
+    ///The track that is being or was last recorded
+    pub fn track(&self) -> &Track
+    {
+        return &self.track;
+    }
+
+    pub fn reference_name(&self) -> Option<&str>
+    {
+        return self.reference.as_ref().map(|(name, _)| name.as_str());
+    }
+
+    pub fn set_reference(&mut self, name: String, track: Track)
+    {
+        self.reference = Some((name, track));
+        self.deviation = None;
+    }
+
+    pub fn clear_reference(&mut self)
+    {
+        self.reference = None;
+        self.deviation = None;
+    }
+
+    ///Deviation from the reference at the last sample
+    pub fn deviation(&self) -> Option<Deviation>
+    {
+        return self.deviation;
+    }
+
+    ///Called every refresh with the current position
+    pub fn sample(&mut self, position: Vector3f, map: Option<MapId>, now: Instant)
+    {
+        if !self.recording
+        {
+            return;
+        }
+
+        let ms = now.saturating_duration_since(self.started).as_millis() as u64;
+        self.track.push(TracePoint { ms, position, map });
+        self.deviation = self.reference.as_ref().and_then(|(_, reference)| reference.compare(&position, map, ms));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+    use crate::positions::trace::*;
+
+    #[test]
+    pub fn csv_round_trip()
+    {
+        let csv = "ms,x,y,z,map\n0,1,2,3,m60_42_36_00\n16,1.5,2,3,\n";
+        let track = Track::parse_csv(csv).unwrap();
+        assert_eq!(track.points()[0].map, Some(MapId::new(60, 42, 36, 0)));
+        assert_eq!(track.points()[1], TracePoint { ms: 16, position: Vector3f::new(1.5, 2.0, 3.0), map: None });
+        assert_eq!(track.to_csv(), csv);
+        assert!(Track::parse_csv("0,1,2\n").is_err());
+    }
+
+    #[test]
+    pub fn compares_to_the_nearest_reference_point()
+    {
+        let mut reference = Track::new();
+        for i in 0..10
+        {
+            reference.push(TracePoint { ms: i * 1000, position: Vector3f::new(i as f32, 0.0, 0.0), map: None });
+        }
+
+        let start = Instant::now();
+        let mut recorder = TraceRecorder::new();
+        recorder.set_reference(String::from("pb"), reference);
+        recorder.sample(Vector3f::default(), None, start);
+        assert_eq!(recorder.deviation(), None);
+
+        recorder.start(start);
+        recorder.sample(Vector3f::new(4.1, 1.0, 0.0), None, start + Duration::from_millis(3000));
+        let deviation = recorder.deviation().unwrap();
+        assert_eq!(deviation.reference_ms, 4000);
+        assert_eq!(deviation.time_delta_ms, -1000);
+        assert!((deviation.distance - 1.005).abs() < 0.01);
+
+        //No reference points on another map
+        recorder.sample(Vector3f::new(4.0, 0.0, 0.0), Some(MapId(1)), start + Duration::from_millis(3016));
+        assert_eq!(recorder.deviation(), None);
+        assert_eq!(recorder.track().points().len(), 2);
+    }
+}
@@ -0,0 +1,386 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Write;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use crate::splits::splitter::SplitEvent;
+use crate::util::format_milliseconds;
+
+//Drives LiveSplit (the LiveSplit Server component) or LiveSplit One (a server bridge) with
+//the newline terminated text commands of the LiveSplit Server protocol.
+//When the game has an in game time, LiveSplit's game time is paused and set to the game's IGT instead of running on its own.
+//Games without IGT but with a loading state get load removed game time, paused while the game is loading.
+
+pub const DEFAULT_LIVESPLIT_ADDRESS: &str = "127.0.0.1:16834";
+
+//Don't flood the connection with a new game time every frame, splits always send the exact time first
+const GAME_TIME_INTERVAL_MS: u32 = 100;
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+///Turns splitter events and in game time into LiveSplit Server commands
+pub struct LiveSplitCommands
+{
+    running: bool,
+    //Game time is paused in LiveSplit and set from the game
+    game_time_driven: bool,
+    game_time_ms: Option<u32>,
+    sent_game_time_ms: Option<u32>,
+    loading: Option<bool>,
+    //Game time is paused for a load in games without IGT
+    load_paused: bool,
+}
+
+impl LiveSplitCommands
+{
+    pub fn new() -> Self
+    {
+        LiveSplitCommands
+        {
+            running: false,
+            game_time_driven: false,
+            game_time_ms: None,
+            sent_game_time_ms: None,
+            loading: None,
+            load_paused: false,
+        }
+    }
+
+    ///Latest in game time, None for games without IGT. IGT reads 0 on the main menu in most games, that is ignored.
+    pub fn game_time(&mut self, game_time_ms: Option<u32>) -> Vec<String>
+    {
+        if let Some(ms) = game_time_ms.filter(|ms| *ms > 0)
+        {
+            self.game_time_ms = Some(ms);
+        }
+
+        let mut commands = Vec::new();
+        let due = match (self.game_time_ms, self.sent_game_time_ms)
+        {
+            (Some(ms), Some(sent)) => ms.abs_diff(sent) >= GAME_TIME_INTERVAL_MS,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if self.running && due
+        {
+            self.push_game_time(&mut commands);
+        }
+        return commands;
+    }
+
+    ///Latest loading state, None for games without one. Only used when there is no IGT.
+    pub fn loading(&mut self, loading: Option<bool>) -> Vec<String>
+    {
+        self.loading = loading;
+
+        let mut commands = Vec::new();
+        if self.running && self.game_time_ms.is_none()
+        {
+            self.push_load_pause(&mut commands);
+        }
+        return commands;
+    }
+
+    pub fn split_event(&mut self, event: &SplitEvent) -> Vec<String>
+    {
+        let mut commands = Vec::new();
+        match event
+        {
+            SplitEvent::Started =>
+            {
+                self.running = true;
+                self.game_time_driven = false;
+                self.sent_game_time_ms = None;
+                commands.push(String::from("starttimer"));
+                self.push_game_time(&mut commands);
+                if self.game_time_ms.is_none() && self.loading.is_some()
+                {
+                    self.load_paused = false;
+                    commands.push(String::from("initgametime"));
+                    self.push_load_pause(&mut commands);
+                }
+            }
+            SplitEvent::Split { .. } =>
+            {
+                self.push_game_time(&mut commands);
+                commands.push(String::from("split"));
+            }
+            SplitEvent::Skipped { .. } => commands.push(String::from("skipsplit")),
+            SplitEvent::Undone { .. } => commands.push(String::from("unsplit")),
+            //The last split already ends the run in LiveSplit
+            SplitEvent::Finished { .. } => self.running = false,
+            SplitEvent::Reset =>
+            {
+                self.running = false;
+                commands.push(String::from("reset"));
+            }
+        }
+        return commands;
+    }
+
+    fn push_load_pause(&mut self, commands: &mut Vec<String>)
+    {
+        if let Some(loading) = self.loading.filter(|l| *l != self.load_paused)
+        {
+            self.load_paused = loading;
+            commands.push(String::from(if loading { "pausegametime" } else { "unpausegametime" }));
+        }
+    }
+
+    fn push_game_time(&mut self, commands: &mut Vec<String>)
+    {
+        if let Some(ms) = self.game_time_ms
+        {
+            if !self.game_time_driven
+            {
+                self.game_time_driven = true;
+                commands.push(String::from("pausegametime"));
+            }
+
+            if self.sent_game_time_ms != Some(ms)
+            {
+                self.sent_game_time_ms = Some(ms);
+                commands.push(format!("setgametime {}", format_milliseconds(ms as u64)));
+            }
+        }
+    }
+}
+
+///Writes commands to a LiveSplit Server from a background thread, reconnecting when the connection drops
+pub struct LiveSplitConnection
+{
+    address: String,
+    sender: Sender<String>,
+    connected: Arc<AtomicBool>,
+}
+
+impl LiveSplitConnection
+{
+    pub fn new(address: &str) -> Self
+    {
+        let (sender, receiver) = mpsc::channel::<String>();
+        let connected = Arc::new(AtomicBool::new(false));
+        let thread_connected = Arc::clone(&connected);
+        let thread_address = String::from(address);
+
+        //The thread exits once the connection is dropped and the channel closes
+        thread::spawn(move ||
+        {
+            let mut stream: Option<TcpStream> = None;
+            let mut last_attempt: Option<Instant> = None;
+
+            loop
+            {
+                if stream.is_none() && last_attempt.is_none_or(|i| i.elapsed() >= RECONNECT_INTERVAL)
+                {
+                    last_attempt = Some(Instant::now());
+                    stream = Self::connect(&thread_address);
+                    thread_connected.store(stream.is_some(), Ordering::Relaxed);
+                }
+
+                match receiver.recv_timeout(Duration::from_millis(100))
+                {
+                    Ok(command) =>
+                    {
+                        //Commands are dropped while disconnected, a split sent late would be wrong anyway
+                        if let Some(s) = &mut stream
+                        {
+                            if let Err(e) = s.write_all(format!("{}\r\n", command).as_bytes())
+                            {
+                                warn!("lost connection to livesplit server at {}: {}", thread_address, e);
+                                stream = None;
+                                thread_connected.store(false, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+
+        LiveSplitConnection { address: String::from(address), sender, connected }
+    }
+
+    fn connect(address: &str) -> Option<TcpStream>
+    {
+        let addr: SocketAddr = match address.to_socket_addrs().ok().and_then(|mut a| a.next())
+        {
+            Some(addr) => addr,
+            None =>
+            {
+                warn!("invalid livesplit server address: {}", address);
+                return None;
+            }
+        };
+
+        return match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+        {
+            Ok(stream) =>
+            {
+                let _ = stream.set_nodelay(true);
+                info!("connected to livesplit server at {}", address);
+                Some(stream)
+            }
+            Err(_) => None,
+        };
+    }
+
+    pub fn address(&self) -> &str
+    {
+        return &self.address;
+    }
+
+    pub fn is_connected(&self) -> bool
+    {
+        return self.connected.load(Ordering::Relaxed);
+    }
+
+    pub fn send(&self, command: String)
+    {
+        let _ = self.sender.send(command);
+    }
+}
+
+///LiveSplit output as configured in the overlay, disabled by default
+pub struct LiveSplitOutput
+{
+    commands: LiveSplitCommands,
+    connection: Option<LiveSplitConnection>,
+}
+
+impl LiveSplitOutput
+{
+    pub fn new() -> Self
+    {
+        LiveSplitOutput { commands: LiveSplitCommands::new(), connection: None }
+    }
+
+    ///Connect to the address, or disconnect when address is None
+    pub fn configure(&mut self, address: Option<&str>)
+    {
+        if self.connection.as_ref().map(|c| c.address()) == address
+        {
+            return;
+        }
+
+        self.connection = address.map(LiveSplitConnection::new);
+    }
+
+    pub fn is_enabled(&self) -> bool
+    {
+        return self.connection.is_some();
+    }
+
+    pub fn is_connected(&self) -> bool
+    {
+        return self.connection.as_ref().is_some_and(|c| c.is_connected());
+    }
+
+    ///Called every refresh with the splitter's events, the game's IGT and whether the game is loading
+    pub fn update(&mut self, events: &[SplitEvent], game_time_ms: Option<u32>, loading: Option<bool>)
+    {
+        let mut commands = self.commands.game_time(game_time_ms);
+        commands.append(&mut self.commands.loading(loading));
+        for event in events
+        {
+            commands.append(&mut self.commands.split_event(event));
+        }
+
+        if let Some(connection) = &self.connection
+        {
+            for command in commands
+            {
+                connection.send(command);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use crate::splits::livesplit::*;
+
+    #[test]
+    pub fn commands_follow_the_run()
+    {
+        let mut commands = LiveSplitCommands::new();
+        assert!(commands.game_time(Some(1000)).is_empty());
+        assert_eq!(commands.split_event(&SplitEvent::Started), vec!["starttimer", "pausegametime", "setgametime 0:00:01.000"]);
+
+        //Small changes wait for the interval, IGT 0 on the main menu keeps the last time
+        assert!(commands.game_time(Some(1050)).is_empty());
+        assert!(commands.game_time(Some(0)).is_empty());
+        assert_eq!(commands.game_time(Some(61_100)), vec!["setgametime 0:01:01.100"]);
+
+        commands.game_time(Some(61_150));
+        assert_eq!(commands.split_event(&SplitEvent::Split { index: 0, name: String::from("boss"), elapsed_ms: 60_000 }), vec!["setgametime 0:01:01.150", "split"]);
+        assert_eq!(commands.split_event(&SplitEvent::Undone { index: 0 }), vec!["unsplit"]);
+        assert_eq!(commands.split_event(&SplitEvent::Reset), vec!["reset"]);
+        assert!(commands.game_time(Some(90_000)).is_empty());
+    }
+
+    #[test]
+    pub fn without_igt_livesplit_keeps_its_own_time()
+    {
+        let mut commands = LiveSplitCommands::new();
+        commands.game_time(None);
+        assert_eq!(commands.split_event(&SplitEvent::Started), vec!["starttimer"]);
+        assert_eq!(commands.split_event(&SplitEvent::Split { index: 0, name: String::from("boss"), elapsed_ms: 10 }), vec!["split"]);
+        assert_eq!(commands.split_event(&SplitEvent::Skipped { index: 1, name: String::from("skip") }), vec!["skipsplit"]);
+        assert!(commands.split_event(&SplitEvent::Finished { elapsed_ms: 10 }).is_empty());
+    }
+
+    #[test]
+    pub fn loads_are_removed_without_igt()
+    {
+        let mut commands = LiveSplitCommands::new();
+        commands.loading(Some(true));
+        assert_eq!(commands.split_event(&SplitEvent::Started), vec!["starttimer", "initgametime", "pausegametime"]);
+        assert!(commands.loading(Some(true)).is_empty());
+        assert_eq!(commands.loading(Some(false)), vec!["unpausegametime"]);
+
+        //IGT takes over from the loading state
+        commands.game_time(Some(5000));
+        assert!(commands.loading(Some(true)).is_empty());
+    }
+
+    #[test]
+    pub fn connection_writes_lines()
+    {
+        //Stand-in for the LiveSplit Server component
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let connection = LiveSplitConnection::new(&listener.local_addr().unwrap().to_string());
+        let (stream, _) = listener.accept().unwrap();
+
+        connection.send(String::from("starttimer"));
+        connection.send(String::from("split"));
+
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!(lines.next().unwrap().unwrap(), "starttimer");
+        assert_eq!(lines.next().unwrap().unwrap(), "split");
+    }
+}
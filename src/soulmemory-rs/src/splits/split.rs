@@ -0,0 +1,63 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use crate::util::vector3f::Vector3f;
+
+//Splits are loaded from <data directory>/<game>.splits.json, an ordered list like:
+//[
+//    { "name": "Margit", "condition": { "type": "flag_set", "flag": 10000850 } },
+//    { "name": "Stormveil", "condition": { "type": "position_in_box", "min": { "x": 0, "y": 0, "z": 0 }, "max": { "x": 10, "y": 10, "z": 10 } } },
+//    { "name": "Leave", "condition": { "type": "loading", "transition": "start" } }
+//]
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Split
+{
+    pub name: String,
+    pub condition: SplitCondition,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SplitCondition
+{
+    FlagSet { flag: u32 },
+    QuantityAtLeast { flag: u32, quantity: i32 },
+    //Inclusive axis aligned box around the player position
+    PositionInBox { min: Vector3f, max: Vector3f },
+    Loading { transition: LoadingTransition },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadingTransition
+{
+    //A loading screen appears
+    Start,
+    //A loading screen disappears
+    End,
+}
+
+impl SplitCondition
+{
+    pub fn position_in_box(position: &Vector3f, min: &Vector3f, max: &Vector3f) -> bool
+    {
+        return min.x <= position.x && position.x <= max.x
+            && min.y <= position.y && position.y <= max.y
+            && min.z <= position.z && position.z <= max.z;
+    }
+}
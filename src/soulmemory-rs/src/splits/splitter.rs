@@ -0,0 +1,327 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use crate::games::traits::buffered_event_flags::{EventFlag, EventFlagValue};
+use crate::splits::split::{LoadingTransition, Split, SplitCondition};
+use crate::util::{game_key, DATA_DIRECTORY};
+use crate::util::vector3f::Vector3f;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SplitTime
+{
+    pub name: String,
+    pub time: DateTime<Local>,
+    //Milliseconds since the run started
+    pub elapsed_ms: u64,
+    pub skipped: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SplitEvent
+{
+    Started,
+    Split { index: usize, name: String, elapsed_ms: u64 },
+    Skipped { index: usize, name: String },
+    Undone { index: usize },
+    Finished { elapsed_ms: u64 },
+    Reset,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SplitterState
+{
+    pub splits: Vec<String>,
+    pub running: bool,
+    //Index of the split that is waiting for its condition, equal to the number of splits once the run is finished
+    pub current: usize,
+    pub times: Vec<SplitTime>,
+}
+
+///What the game looked like during one refresh
+pub struct SplitInput<'a>
+{
+    pub event_flags: &'a [EventFlag],
+    pub position: Option<Vector3f>,
+    //None for games without a loading state, loading conditions never trigger there
+    pub loading: Option<bool>,
+}
+
+pub struct Splitter
+{
+    splits: Vec<Split>,
+    running: bool,
+    started: Instant,
+    times: Vec<SplitTime>,
+    last_loading: Option<bool>,
+    //Events since the last call to take_events, the app forwards them to the server
+    events: Vec<SplitEvent>,
+}
+
+impl Splitter
+{
+    pub fn new(splits: Vec<Split>) -> Self
+    {
+        Splitter
+        {
+            splits,
+            running: false,
+            started: Instant::now(),
+            times: Vec::new(),
+            last_loading: None,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn path(process_name: &str) -> PathBuf
+    {
+        return Path::new(DATA_DIRECTORY).join(format!("{}.splits.json", game_key(process_name)));
+    }
+
+    ///Load the split list from a json file, a missing file means no splits
+    pub fn load(path: &Path) -> Result<Self, String>
+    {
+        if !path.exists()
+        {
+            return Ok(Splitter::new(Vec::new()));
+        }
+
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let splits = serde_json::from_str::<Vec<Split>>(&json).map_err(|e| e.to_string())?;
+        return Ok(Splitter::new(splits));
+    }
+
+    pub fn splits(&self) -> &Vec<Split>
+    {
+        return &self.splits;
+    }
+
+    pub fn is_running(&self) -> bool
+    {
+        return self.running;
+    }
+
+    ///Index of the split waiting for its condition, None when not running or finished
+    pub fn current_index(&self) -> Option<usize>
+    {
+        return if self.running && self.times.len() < self.splits.len() { Some(self.times.len()) } else { None };
+    }
+
+    pub fn elapsed_ms(&self) -> u64
+    {
+        if self.running
+        {
+            return self.started.elapsed().as_millis() as u64;
+        }
+        //Finished runs keep showing the final time
+        return self.times.last().map(|t| t.elapsed_ms).unwrap_or(0);
+    }
+
+    pub fn state(&self) -> SplitterState
+    {
+        SplitterState
+        {
+            splits: self.splits.iter().map(|s| s.name.clone()).collect(),
+            running: self.running,
+            current: self.times.len(),
+            times: self.times.clone(),
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<SplitEvent>
+    {
+        return std::mem::take(&mut self.events);
+    }
+
+    ///Start a new run, resets a run that is in progress
+    pub fn start(&mut self)
+    {
+        if self.running || !self.times.is_empty()
+        {
+            self.reset();
+        }
+        self.running = true;
+        self.started = Instant::now();
+        self.events.push(SplitEvent::Started);
+    }
+
+    ///Complete the current split by hand
+    pub fn split(&mut self)
+    {
+        self.complete_current(Instant::now(), Local::now(), false);
+    }
+
+    pub fn skip(&mut self)
+    {
+        self.complete_current(Instant::now(), Local::now(), true);
+    }
+
+    pub fn undo(&mut self)
+    {
+        if self.times.pop().is_some()
+        {
+            //Undoing the last split of a finished run continues it
+            self.running = true;
+            self.events.push(SplitEvent::Undone { index: self.times.len() });
+        }
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.running = false;
+        self.times.clear();
+        self.events.push(SplitEvent::Reset);
+    }
+
+    ///Check the current split's condition against one refresh worth of game state
+    pub fn update(&mut self, input: &SplitInput)
+    {
+        let loading_transition = match (self.last_loading, input.loading)
+        {
+            (Some(false), Some(true)) => Some(LoadingTransition::Start),
+            (Some(true), Some(false)) => Some(LoadingTransition::End),
+            _ => None,
+        };
+        self.last_loading = input.loading;
+
+        //A single refresh can contain flags for several consecutive splits
+        for event_flag in input.event_flags
+        {
+            let index = match self.current_index()
+            {
+                Some(index) => index,
+                None => return,
+            };
+
+            let done = match (&self.splits[index].condition, event_flag.value)
+            {
+                (SplitCondition::FlagSet { flag }, EventFlagValue::State(true)) => *flag == event_flag.flag,
+                (SplitCondition::QuantityAtLeast { flag, quantity }, EventFlagValue::Quantity(value)) => *flag == event_flag.flag && value >= *quantity,
+                _ => false,
+            };
+
+            if done
+            {
+                self.complete_current(event_flag.instant, event_flag.time, false);
+            }
+        }
+
+        if let Some(index) = self.current_index()
+        {
+            let done = match &self.splits[index].condition
+            {
+                SplitCondition::PositionInBox { min, max } => input.position.is_some_and(|p| SplitCondition::position_in_box(&p, min, max)),
+                SplitCondition::Loading { transition } => loading_transition == Some(*transition),
+                _ => false,
+            };
+
+            if done
+            {
+                self.split();
+            }
+        }
+    }
+
+    fn complete_current(&mut self, instant: Instant, time: DateTime<Local>, skipped: bool)
+    {
+        let index = match self.current_index()
+        {
+            Some(index) => index,
+            None => return,
+        };
+
+        let elapsed_ms = instant.saturating_duration_since(self.started).as_millis() as u64;
+        let name = self.splits[index].name.clone();
+        self.times.push(SplitTime { name: name.clone(), time, elapsed_ms, skipped });
+
+        self.events.push(if skipped { SplitEvent::Skipped { index, name } } else { SplitEvent::Split { index, name, elapsed_ms } });
+
+        if self.times.len() == self.splits.len()
+        {
+            self.running = false;
+            self.events.push(SplitEvent::Finished { elapsed_ms });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::splits::splitter::*;
+
+    fn splitter() -> Splitter
+    {
+        let json = r#"[
+            {"name": "flag", "condition": {"type": "flag_set", "flag": 10}},
+            {"name": "runes", "condition": {"type": "quantity_at_least", "flag": 20, "quantity": 3}},
+            {"name": "box", "condition": {"type": "position_in_box", "min": {"x": 0, "y": 0, "z": 0}, "max": {"x": 1, "y": 1, "z": 1}}},
+            {"name": "load", "condition": {"type": "loading", "transition": "end"}}
+        ]"#;
+        return Splitter::new(serde_json::from_str(json).unwrap());
+    }
+
+    fn input(event_flags: &[EventFlag], position: Option<Vector3f>, loading: Option<bool>) -> SplitInput<'_>
+    {
+        SplitInput { event_flags, position, loading }
+    }
+
+    #[test]
+    pub fn runs_through_every_condition()
+    {
+        let mut splitter = splitter();
+        let flags = [EventFlag::from_state(Local::now(), 10, true), EventFlag::from_quantity(Local::now(), 20, 2), EventFlag::from_quantity(Local::now(), 20, 3)];
+
+        //Not running yet
+        splitter.update(&input(&flags, None, None));
+        assert_eq!(splitter.state().current, 0);
+
+        splitter.start();
+        splitter.update(&input(&flags, Some(Vector3f::new(5.0, 0.0, 0.0)), Some(true)));
+        assert_eq!(splitter.current_index(), Some(2));
+
+        splitter.update(&input(&[], Some(Vector3f::new(0.5, 0.5, 0.5)), Some(true)));
+        assert_eq!(splitter.current_index(), Some(3));
+
+        splitter.update(&input(&[], None, Some(false)));
+        assert_eq!(splitter.current_index(), None);
+        assert!(!splitter.is_running());
+
+        let events = splitter.take_events();
+        assert_eq!(events.first(), Some(&SplitEvent::Started));
+        assert!(matches!(events.last(), Some(SplitEvent::Finished { .. })));
+    }
+
+    #[test]
+    pub fn undo_skip_and_reset()
+    {
+        let mut splitter = splitter();
+        splitter.start();
+        splitter.skip();
+        splitter.split();
+        assert!(splitter.state().times[0].skipped);
+
+        splitter.undo();
+        assert_eq!(splitter.current_index(), Some(1));
+
+        splitter.reset();
+        assert_eq!(splitter.state(), SplitterState { splits: vec![String::from("flag"), String::from("runes"), String::from("box"), String::from("load")], running: false, current: 0, times: Vec::new() });
+    }
+}
@@ -0,0 +1,78 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//Gamepad buttons queued by trigger rules, ORed into the XInputGetState result.
+//Durations count XInputGetState polls, the games poll once per frame.
+
+struct Press
+{
+    buttons: u16,
+    remaining: u32,
+}
+
+pub struct TasInputs
+{
+    presses: Vec<Press>,
+}
+
+impl TasInputs
+{
+    pub fn new() -> Self
+    {
+        TasInputs { presses: Vec::new() }
+    }
+
+    ///Hold buttons, an XINPUT_GAMEPAD button mask, for the given number of polls
+    pub fn press(&mut self, buttons: u16, frames: u32)
+    {
+        if frames > 0
+        {
+            self.presses.push(Press { buttons, remaining: frames });
+        }
+    }
+
+    ///The buttons to hold for this poll, every press counts down by one
+    pub fn next(&mut self) -> u16
+    {
+        let mut buttons = 0;
+        for press in &mut self.presses
+        {
+            buttons |= press.buttons;
+            press.remaining -= 1;
+        }
+        self.presses.retain(|p| p.remaining > 0);
+        return buttons;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::tas::inputs::*;
+
+    #[test]
+    pub fn presses_overlap_and_expire()
+    {
+        let mut inputs = TasInputs::new();
+        inputs.press(0x1000, 2);
+        inputs.press(0x0008, 1);
+        inputs.press(0x0004, 0);
+
+        assert_eq!(inputs.next(), 0x1008);
+        assert_eq!(inputs.next(), 0x1000);
+        assert_eq!(inputs.next(), 0);
+    }
+}
@@ -15,5 +15,6 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 pub mod toggle_mode;
+pub mod inputs;
 pub(crate) mod tas;
 
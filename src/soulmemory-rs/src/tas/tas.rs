@@ -20,6 +20,7 @@ use std::ffi::c_void;
 use ::log::info;
 use windows::Win32::Foundation::HINSTANCE;
 use windows::Win32::UI::Input::XboxController::XINPUT_STATE;
+use crate::tas::inputs::TasInputs;
 use crate::tas::toggle_mode::ToggleMode;
 
 
@@ -51,7 +52,11 @@ pub fn tas_ai_toggle(toggle_mode: ToggleMode, timer_value: f32, timer_threshold:
     }
 }
 
-
+pub fn tas_inputs(inputs: &mut TasInputs, xinput_state: *mut XINPUT_STATE)
+{
+    let buttons = inputs.next();
+    unsafe{ (*xinput_state).Gamepad.wButtons.0 |= buttons; }
+}
 
 pub fn get_xinput_get_state_fn_address() -> u64
 {
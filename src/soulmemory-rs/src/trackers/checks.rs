@@ -0,0 +1,275 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use crate::games::traits::buffered_event_flags::{EventFlag, EventFlagValue};
+use crate::util::{game_key, DATA_DIRECTORY};
+
+//Checks are defined in <data directory>/<game>.checks.tsv, in the spirit of CheckPatterns.tsv:
+//flag<TAB>check name<TAB>group
+//11100100	Rusty Key Check	Stormveil Castle
+//A check is collected the first time its flag is set during a run.
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CheckDefinition
+{
+    pub flag: u32,
+    pub name: String,
+    pub group: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CollectedCheck
+{
+    pub flag: u32,
+    pub name: String,
+    pub group: String,
+    pub collected_at: DateTime<Local>,
+    //Milliseconds since the run started
+    pub run_ms: u64,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct GroupProgress
+{
+    pub group: String,
+    pub collected: usize,
+    pub total: usize,
+}
+
+pub struct CheckTracker
+{
+    definitions: Vec<CheckDefinition>,
+    by_flag: HashMap<u32, usize>,
+    timeline: Vec<CollectedCheck>,
+    run_started: Instant,
+}
+
+impl CheckTracker
+{
+    pub fn new(definitions: Vec<CheckDefinition>) -> Self
+    {
+        let by_flag = definitions.iter().enumerate().map(|(i, d)| (d.flag, i)).collect();
+        CheckTracker
+        {
+            definitions,
+            by_flag,
+            timeline: Vec::new(),
+            run_started: Instant::now(),
+        }
+    }
+
+    pub fn path(process_name: &str) -> PathBuf
+    {
+        return Path::new(DATA_DIRECTORY).join(format!("{}.checks.tsv", game_key(process_name)));
+    }
+
+    ///Load the check definitions for the game, no definitions when the file is missing or broken
+    pub fn load(process_name: &str) -> Self
+    {
+        let path = Self::path(process_name);
+        if !path.exists()
+        {
+            return CheckTracker::new(Vec::new());
+        }
+
+        match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|tsv| Self::parse_tsv(&tsv))
+        {
+            Ok(definitions) =>
+            {
+                info!("loaded {} checks from {}", definitions.len(), path.display());
+                CheckTracker::new(definitions)
+            }
+            Err(e) =>
+            {
+                warn!("failed to load checks from {}: {}", path.display(), e);
+                CheckTracker::new(Vec::new())
+            }
+        }
+    }
+
+    pub fn parse_tsv(tsv: &str) -> Result<Vec<CheckDefinition>, String>
+    {
+        let mut definitions = Vec::new();
+        for (index, line) in tsv.lines().enumerate()
+        {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#')
+            {
+                continue;
+            }
+
+            let columns: Vec<&str> = line.split('\t').map(|c| c.trim()).collect();
+            if columns[0] == "flag"
+            {
+                continue; //header
+            }
+
+            if columns.len() < 2
+            {
+                return Err(format!("line {}: expected at least a flag and a check name", index + 1));
+            }
+
+            let flag = columns[0].parse::<u32>().map_err(|e| format!("line {}: invalid flag '{}': {}", index + 1, columns[0], e))?;
+            definitions.push(CheckDefinition
+            {
+                flag,
+                name: columns[1].to_string(),
+                group: columns.get(2).filter(|g| !g.is_empty()).map(|g| g.to_string()).unwrap_or_else(|| String::from("other")),
+            });
+        }
+        return Ok(definitions);
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        return self.definitions.is_empty();
+    }
+
+    pub fn timeline(&self) -> &Vec<CollectedCheck>
+    {
+        return &self.timeline;
+    }
+
+    pub fn is_collected(&self, flag: u32) -> bool
+    {
+        return self.timeline.iter().any(|c| c.flag == flag);
+    }
+
+    pub fn on_event_flags(&mut self, event_flags: &[EventFlag])
+    {
+        for event_flag in event_flags
+        {
+            let set = match event_flag.value
+            {
+                EventFlagValue::State(state) => state,
+                EventFlagValue::Quantity(quantity) => quantity != 0,
+            };
+
+            if !set || self.is_collected(event_flag.flag)
+            {
+                continue;
+            }
+
+            if let Some(index) = self.by_flag.get(&event_flag.flag)
+            {
+                let definition = &self.definitions[*index];
+                self.timeline.push(CollectedCheck
+                {
+                    flag: definition.flag,
+                    name: definition.name.clone(),
+                    group: definition.group.clone(),
+                    collected_at: event_flag.time,
+                    run_ms: event_flag.instant.saturating_duration_since(self.run_started).as_millis() as u64,
+                });
+            }
+        }
+    }
+
+    ///Progress per group, in the order the groups first appear in the definitions
+    pub fn progress(&self) -> Vec<GroupProgress>
+    {
+        let mut progress: Vec<GroupProgress> = Vec::new();
+        for definition in &self.definitions
+        {
+            let collected = self.is_collected(definition.flag) as usize;
+            match progress.iter_mut().find(|p| p.group == definition.group)
+            {
+                Some(group) =>
+                {
+                    group.collected += collected;
+                    group.total += 1;
+                }
+                None => progress.push(GroupProgress { group: definition.group.clone(), collected, total: 1 }),
+            }
+        }
+        return progress;
+    }
+
+    ///Start a new run, forgets every collected check
+    pub fn reset(&mut self)
+    {
+        self.timeline.clear();
+        self.run_started = Instant::now();
+    }
+
+    pub fn timeline_csv(&self) -> String
+    {
+        let mut csv = String::from("run_ms,time,flag,check,group\n");
+        for check in &self.timeline
+        {
+            csv.push_str(&format!("{},{},{},{},{}\n", check.run_ms, check.collected_at.to_rfc3339(), check.flag, Self::csv_field(&check.name), Self::csv_field(&check.group)));
+        }
+        return csv;
+    }
+
+    pub fn timeline_json(&self) -> String
+    {
+        return serde_json::to_string_pretty(&self.timeline).unwrap();
+    }
+
+    fn csv_field(value: &str) -> String
+    {
+        if value.contains(',') || value.contains('"')
+        {
+            return format!("\"{}\"", value.replace('"', "\"\""));
+        }
+        return value.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::trackers::checks::*;
+
+    const TSV: &str = "flag\tcheck\tgroup\n100\tRusty Key Check\tStormveil Castle\n101\tBelfries Check\tLiurnia\n102\tGlintstone Key Check, Smarag\tLiurnia\n";
+
+    #[test]
+    pub fn parse_definitions()
+    {
+        let definitions = CheckTracker::parse_tsv(TSV).unwrap();
+        assert_eq!(definitions.len(), 3);
+        assert_eq!(definitions[1], CheckDefinition { flag: 101, name: String::from("Belfries Check"), group: String::from("Liurnia") });
+        assert_eq!(CheckTracker::parse_tsv("5\tno group\n").unwrap()[0].group, "other");
+        assert!(CheckTracker::parse_tsv("x\tbroken\n").is_err());
+    }
+
+    #[test]
+    pub fn collects_each_check_once_and_tracks_progress()
+    {
+        let mut tracker = CheckTracker::new(CheckTracker::parse_tsv(TSV).unwrap());
+        tracker.on_event_flags(&[EventFlag::from_state(Local::now(), 102, true), EventFlag::from_state(Local::now(), 999, true)]);
+        tracker.on_event_flags(&[EventFlag::from_state(Local::now(), 102, false), EventFlag::from_state(Local::now(), 102, true)]);
+
+        assert_eq!(tracker.timeline().len(), 1);
+        assert_eq!(tracker.progress(), vec!
+        [
+            GroupProgress { group: String::from("Stormveil Castle"), collected: 0, total: 1 },
+            GroupProgress { group: String::from("Liurnia"), collected: 1, total: 2 },
+        ]);
+        assert!(tracker.timeline_csv().lines().nth(1).unwrap().ends_with(",102,\"Glintstone Key Check, Smarag\",Liurnia"));
+
+        tracker.reset();
+        assert!(tracker.timeline().is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use crate::games::traits::buffered_event_flags::{EventFlag, EventFlagValue};
+
+//Only obtaining a rune is tracked. Restoring one at a divine tower has no known event flag: SoulMemory's GreatRune enum
+//and the GreatRuneTracker tool only know the obtained flags, and the flags right after them (181-187) are the "Gets Nth Great Rune" counters.
+//Restoration swaps the unpowered rune item (8148-8153) for the powered one (191-196), but SoulMemory has no sourced path to the inventory,
+//so the restored state stays out until a flag or inventory layout is verified.
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GreatRune
+{
+    Godrick,
+    Radahn,
+    Morgott,
+    Rykard,
+    Mohg,
+    Malenia,
+    Unborn,
+}
+
+impl GreatRune
+{
+    pub const ALL: [GreatRune; 7] = [GreatRune::Godrick, GreatRune::Radahn, GreatRune::Morgott, GreatRune::Rykard, GreatRune::Mohg, GreatRune::Malenia, GreatRune::Unborn];
+
+    pub fn name(&self) -> &'static str
+    {
+        return match self
+        {
+            GreatRune::Godrick => "Godrick's Great Rune",
+            GreatRune::Radahn  => "Radahn's Great Rune",
+            GreatRune::Morgott => "Morgott's Great Rune",
+            GreatRune::Rykard  => "Rykard's Great Rune",
+            GreatRune::Mohg    => "Mohg's Great Rune",
+            GreatRune::Malenia => "Malenia's Great Rune",
+            GreatRune::Unborn  => "Great Rune of the Unborn",
+        };
+    }
+
+    ///Set when the rune is picked up, same ids as SoulMemory's GreatRune enum
+    pub fn obtained_flag(&self) -> u32
+    {
+        return match self
+        {
+            GreatRune::Godrick => 171,
+            GreatRune::Radahn  => 172,
+            GreatRune::Morgott => 173,
+            GreatRune::Rykard  => 174,
+            GreatRune::Mohg    => 175,
+            GreatRune::Malenia => 176,
+            GreatRune::Unborn  => 197,
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct GreatRuneStatus
+{
+    pub rune: GreatRune,
+    pub name: String,
+    pub obtained: bool,
+    //None when the rune was already obtained before the tracker saw the flag being set
+    pub obtained_at: Option<DateTime<Local>>,
+}
+
+pub struct GreatRuneTracker
+{
+    runes: Vec<GreatRuneStatus>,
+}
+
+impl GreatRuneTracker
+{
+    pub fn new() -> Self
+    {
+        GreatRuneTracker
+        {
+            runes: GreatRune::ALL.iter().map(|rune| GreatRuneStatus
+            {
+                rune: *rune,
+                name: String::from(rune.name()),
+                obtained: false,
+                obtained_at: None,
+            }).collect(),
+        }
+    }
+
+    pub fn runes(&self) -> &Vec<GreatRuneStatus>
+    {
+        return &self.runes;
+    }
+
+    pub fn is_great_rune_flag(flag: u32) -> bool
+    {
+        return GreatRune::ALL.iter().any(|r| r.obtained_flag() == flag);
+    }
+
+    ///Record a flag written by the game, called from the set_event_flag hook so the time is exact
+    pub fn on_event_flag(&mut self, event_flag: &EventFlag)
+    {
+        let state = match event_flag.value
+        {
+            EventFlagValue::State(state) => state,
+            EventFlagValue::Quantity(_) => return,
+        };
+
+        for status in self.runes.iter_mut()
+        {
+            if status.rune.obtained_flag() == event_flag.flag
+            {
+                Self::update(&mut status.obtained, &mut status.obtained_at, state, Some(event_flag.time));
+            }
+        }
+    }
+
+    ///Bring the tracker in line with the flags in memory, for runes obtained before the hook was installed or after loading another character
+    pub fn sync(&mut self, get_state: impl Fn(u32) -> bool)
+    {
+        for status in self.runes.iter_mut()
+        {
+            Self::update(&mut status.obtained, &mut status.obtained_at, get_state(status.rune.obtained_flag()), None);
+        }
+    }
+
+    fn update(current: &mut bool, at: &mut Option<DateTime<Local>>, state: bool, time: Option<DateTime<Local>>)
+    {
+        if *current == state
+        {
+            return;
+        }
+
+        *current = state;
+        *at = if state { time } else { None };
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::trackers::great_runes::*;
+
+    #[test]
+    pub fn hook_records_time_sync_does_not()
+    {
+        let mut tracker = GreatRuneTracker::new();
+        let flag = EventFlag::from_state(Local::now(), 171, true);
+        tracker.on_event_flag(&flag);
+        assert_eq!(tracker.runes()[0].obtained_at, Some(flag.time));
+
+        //Syncing an already known state keeps the exact time
+        tracker.sync(|f| f == 171 || f == 172);
+        assert_eq!(tracker.runes()[0].obtained_at, Some(flag.time));
+        assert!(tracker.runes()[1].obtained);
+        assert_eq!(tracker.runes()[1].obtained_at, None);
+
+        //Loading a character without the rune clears it
+        tracker.sync(|_| false);
+        assert!(!tracker.runes()[0].obtained);
+        assert_eq!(tracker.runes()[0].obtained_at, None);
+    }
+
+    #[test]
+    pub fn great_rune_flags()
+    {
+        assert!(GreatRuneTracker::is_great_rune_flag(197));
+        assert!(GreatRuneTracker::is_great_rune_flag(176));
+        assert!(!GreatRuneTracker::is_great_rune_flag(196));
+        assert!(!GreatRuneTracker::is_great_rune_flag(181));
+    }
+}
@@ -0,0 +1,361 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::games::traits::buffered_event_flags::{EventFlag, EventFlagValue};
+use crate::games::traits::loading_state::ScreenState;
+use crate::triggers::rule::{Action, Condition, Rule};
+use crate::util::{game_key, DATA_DIRECTORY};
+
+//Conditions are evaluated against the flag values seen in the event flag stream.
+//Flags that have not been written yet are read from the game the first time a rule looks at them,
+//quantity flags can only be read when the condition gives their bit count, until then conditions on them are false.
+//Rules are edge triggered: a rule is only evaluated when one of its flags shows up in the stream, not on every refresh.
+//Seen values are forgotten on loads and on the main menu, where the game can load a different character or save.
+
+enum Node
+{
+    FlagSet(u32),
+    FlagCleared(u32),
+    QuantityAtLeast(u32, i32),
+    All(Vec<Node>),
+    Any(Vec<Node>),
+    Sequence { steps: Vec<Node>, progress: usize },
+}
+
+impl Node
+{
+    fn new(condition: &Condition) -> Self
+    {
+        return match condition
+        {
+            Condition::FlagSet { flag } => Node::FlagSet(*flag),
+            Condition::FlagCleared { flag } => Node::FlagCleared(*flag),
+            Condition::QuantityAtLeast { flag, quantity, .. } => Node::QuantityAtLeast(*flag, *quantity),
+            Condition::All { conditions } => Node::All(conditions.iter().map(Node::new).collect()),
+            Condition::Any { conditions } => Node::Any(conditions.iter().map(Node::new).collect()),
+            Condition::Sequence { steps } => Node::Sequence { steps: steps.iter().map(Node::new).collect(), progress: 0 },
+        };
+    }
+
+    fn evaluate(&mut self, values: &HashMap<u32, EventFlagValue>) -> bool
+    {
+        return match self
+        {
+            Node::FlagSet(flag) => matches!(values.get(flag), Some(EventFlagValue::State(true))) || matches!(values.get(flag), Some(EventFlagValue::Quantity(q)) if *q != 0),
+            Node::FlagCleared(flag) => matches!(values.get(flag), Some(EventFlagValue::State(false)) | Some(EventFlagValue::Quantity(0))),
+            Node::QuantityAtLeast(flag, quantity) => matches!(values.get(flag), Some(EventFlagValue::Quantity(q)) if q >= quantity),
+            //Evaluate every child so that nested sequences keep making progress
+            Node::All(nodes) => nodes.iter_mut().map(|n| n.evaluate(values)).fold(true, |a, b| a && b),
+            Node::Any(nodes) => nodes.iter_mut().map(|n| n.evaluate(values)).fold(false, |a, b| a || b),
+            Node::Sequence { steps, progress } =>
+            {
+                //Only the current step is looked at, so steps that are already true don't complete the sequence early
+                if *progress < steps.len() && steps[*progress].evaluate(values)
+                {
+                    *progress += 1;
+                }
+                *progress == steps.len()
+            }
+        };
+    }
+
+    fn reset(&mut self)
+    {
+        match self
+        {
+            Node::All(nodes) | Node::Any(nodes) => nodes.iter_mut().for_each(|n| n.reset()),
+            Node::Sequence { steps, progress } =>
+            {
+                *progress = 0;
+                steps.iter_mut().for_each(|n| n.reset());
+            }
+            _ => {}
+        }
+    }
+}
+
+//Every flag a condition looks at, with the bit count to read it with when it is a quantity
+fn reads(condition: &Condition, reads: &mut HashMap<u32, Option<u8>>)
+{
+    match condition
+    {
+        Condition::FlagSet { flag } | Condition::FlagCleared { flag } => { reads.entry(*flag).or_insert(None); }
+        Condition::QuantityAtLeast { flag, bits, .. } =>
+        {
+            let entry = reads.entry(*flag).or_insert(None);
+            if bits.is_some()
+            {
+                *entry = *bits;
+            }
+        }
+        Condition::All { conditions } | Condition::Any { conditions } | Condition::Sequence { steps: conditions } => conditions.iter().for_each(|c| self::reads(c, reads)),
+    }
+}
+
+struct RuleState
+{
+    rule: Rule,
+    node: Node,
+    flags: HashMap<u32, Option<u8>>,
+    //Condition was true at the last evaluation
+    active: bool,
+    fire_count: u32,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FiredTrigger
+{
+    pub rule: String,
+    pub actions: Vec<Action>,
+}
+
+pub struct TriggerEngine
+{
+    rules: Vec<RuleState>,
+    values: HashMap<u32, EventFlagValue>,
+}
+
+impl TriggerEngine
+{
+    pub fn new(rules: Vec<Rule>) -> Self
+    {
+        TriggerEngine
+        {
+            rules: rules.into_iter().map(|rule| RuleState
+            {
+                node: Node::new(&rule.condition),
+                flags:
+                {
+                    let mut flags = HashMap::new();
+                    reads(&rule.condition, &mut flags);
+                    flags
+                },
+                rule,
+                active: false,
+                fire_count: 0,
+            }).collect(),
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn path(process_name: &str) -> PathBuf
+    {
+        return Path::new(DATA_DIRECTORY).join(format!("{}.triggers.json", game_key(process_name)));
+    }
+
+    ///Load rules from a json file, a missing file means no rules
+    pub fn load(path: &Path) -> Result<Self, String>
+    {
+        if !path.exists()
+        {
+            return Ok(TriggerEngine::new(Vec::new()));
+        }
+
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let rules = serde_json::from_str::<Vec<Rule>>(&json).map_err(|e| e.to_string())?;
+        return Ok(TriggerEngine::new(rules));
+    }
+
+    pub fn len(&self) -> usize
+    {
+        return self.rules.len();
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        return self.rules.is_empty();
+    }
+
+    ///Feed flags from the event flag stream in order, returns the rules that fired.
+    ///read is called with a flag and its bit count (None for a single bit flag) to get the value of a flag that hasn't been seen yet.
+    pub fn process<F: FnMut(u32, Option<u8>) -> Option<EventFlagValue>>(&mut self, event_flags: &[EventFlag], mut read: F) -> Vec<FiredTrigger>
+    {
+        let mut fired = Vec::new();
+        if self.rules.is_empty()
+        {
+            return fired;
+        }
+
+        for event_flag in event_flags
+        {
+            self.values.insert(event_flag.flag, event_flag.value);
+
+            for state in self.rules.iter_mut().filter(|s| s.flags.contains_key(&event_flag.flag))
+            {
+                if state.fire_count > 0 && !state.rule.repeat
+                {
+                    continue;
+                }
+
+                let unseen: Vec<_> = state.flags.iter().filter(|(flag, _)| !self.values.contains_key(flag)).collect();
+                for (flag, bits) in unseen
+                {
+                    if let Some(value) = read(*flag, *bits)
+                    {
+                        self.values.insert(*flag, value);
+                    }
+                }
+
+                let result = state.node.evaluate(&self.values);
+                if result && !state.active
+                {
+                    state.fire_count += 1;
+                    fired.push(FiredTrigger { rule: state.rule.name.clone(), actions: state.rule.actions.clone() });
+
+                    //Start sequences over, so a repeating rule has to see the whole sequence again
+                    state.node.reset();
+                    state.active = state.node.evaluate(&self.values);
+                }
+                else
+                {
+                    state.active = result;
+                }
+            }
+        }
+        return fired;
+    }
+
+    ///Called when the screen state changes. Values from before a load can be stale and are read from the game again,
+    ///on the main menu every rule is re-armed as well since the next load can be a different character.
+    pub fn on_screen_state(&mut self, state: ScreenState)
+    {
+        match state
+        {
+            ScreenState::MainMenu => self.reset(),
+            ScreenState::Loading | ScreenState::Quitout => self.values.clear(),
+            ScreenState::InGame => {}
+        }
+    }
+
+    ///Forget every seen flag and re-arm all rules, flags are read from the game again
+    pub fn reset(&mut self)
+    {
+        self.values.clear();
+        for state in &mut self.rules
+        {
+            state.node.reset();
+            state.active = false;
+            state.fire_count = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use chrono::Local;
+    use crate::triggers::engine::*;
+
+    fn rule(condition: Condition, repeat: bool) -> Rule
+    {
+        Rule { name: String::from("rule"), condition, actions: vec![Action::Log { message: String::from("fired") }], repeat }
+    }
+
+    fn set(flag: u32, state: bool) -> EventFlag
+    {
+        EventFlag::from_state(Local::now(), flag, state)
+    }
+
+    fn unknown(_flag: u32, _bits: Option<u8>) -> Option<EventFlagValue>
+    {
+        None
+    }
+
+    #[test]
+    pub fn flag_set_fires_once()
+    {
+        let mut engine = TriggerEngine::new(vec![rule(Condition::FlagSet { flag: 10 }, false)]);
+        assert!(engine.process(&[set(11, true)], unknown).is_empty());
+        assert_eq!(engine.process(&[set(10, true)], unknown).len(), 1);
+        assert!(engine.process(&[set(10, false), set(10, true)], unknown).is_empty());
+    }
+
+    #[test]
+    pub fn repeat_fires_on_every_rising_edge()
+    {
+        let mut engine = TriggerEngine::new(vec![rule(Condition::FlagSet { flag: 10 }, true)]);
+        assert_eq!(engine.process(&[set(10, true), set(10, true)], unknown).len(), 1);
+        assert_eq!(engine.process(&[set(10, false), set(10, true)], unknown).len(), 1);
+    }
+
+    #[test]
+    pub fn sequence_requires_order()
+    {
+        let sequence = Condition::Sequence { steps: vec![Condition::FlagSet { flag: 1 }, Condition::FlagSet { flag: 2 }] };
+        let mut engine = TriggerEngine::new(vec![rule(sequence, false)]);
+        assert!(engine.process(&[set(2, true), set(1, true)], unknown).is_empty());
+        assert_eq!(engine.process(&[set(2, true)], unknown).len(), 1);
+    }
+
+    #[test]
+    pub fn all_any_and_quantity()
+    {
+        let json = r#"[{"name": "runes", "condition": {"type": "all", "conditions": [
+            {"type": "quantity_at_least", "flag": 5, "quantity": 2},
+            {"type": "any", "conditions": [{"type": "flag_cleared", "flag": 6}, {"type": "flag_set", "flag": 7}]}
+        ]}, "actions": [{"type": "alert", "message": "done"}]}]"#;
+        let mut engine = TriggerEngine::new(serde_json::from_str(json).unwrap());
+        assert!(engine.process(&[EventFlag::from_quantity(Local::now(), 5, 2)], unknown).is_empty());
+
+        let fired = engine.process(&[set(7, true)], unknown);
+        assert_eq!(fired[0].actions, vec![Action::Alert { message: String::from("done"), seconds: 5.0f32 }]);
+    }
+
+    #[test]
+    pub fn unseen_flags_are_read_from_the_game()
+    {
+        let json = r#"[{"name": "rune", "condition": {"type": "all", "conditions": [
+            {"type": "flag_set", "flag": 171}, {"type": "flag_cleared", "flag": 181}, {"type": "quantity_at_least", "flag": 5, "quantity": 2, "bits": 8}
+        ]}, "actions": []}]"#;
+        let mut engine = TriggerEngine::new(serde_json::from_str(json).unwrap());
+        let read = |flag: u32, bits: Option<u8>| match (flag, bits)
+        {
+            (181, None) => Some(EventFlagValue::State(false)),
+            (5, Some(8)) => Some(EventFlagValue::Quantity(3)),
+            _ => None,
+        };
+        assert_eq!(engine.process(&[set(171, true)], read).len(), 1);
+    }
+
+    #[test]
+    pub fn loads_forget_values_and_the_main_menu_rearms()
+    {
+        let condition = Condition::All { conditions: vec![Condition::FlagSet { flag: 1 }, Condition::FlagSet { flag: 2 }] };
+        let mut engine = TriggerEngine::new(vec![rule(condition, false)]);
+        let set_in_game = |_flag: u32, _bits: Option<u8>| Some(EventFlagValue::State(true));
+
+        //Flag 2 was seen cleared before the load, afterwards it is read from the game again
+        assert!(engine.process(&[set(2, false)], unknown).is_empty());
+        engine.on_screen_state(ScreenState::Loading);
+        assert_eq!(engine.process(&[set(1, true)], set_in_game).len(), 1);
+
+        //Fired once, until the main menu re-arms it
+        engine.on_screen_state(ScreenState::Loading);
+        assert!(engine.process(&[set(1, true)], set_in_game).is_empty());
+        engine.on_screen_state(ScreenState::MainMenu);
+        assert_eq!(engine.process(&[set(1, true)], set_in_game).len(), 1);
+    }
+
+    #[test]
+    pub fn tas_press_action()
+    {
+        let actions = serde_json::from_str::<Vec<Action>>(r#"[{"type": "tas_press", "buttons": 4096, "frames": 3}]"#).unwrap();
+        assert_eq!(actions, vec![Action::TasPress { buttons: 0x1000, frames: 3 }]);
+    }
+}
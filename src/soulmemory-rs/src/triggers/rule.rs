@@ -0,0 +1,81 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use crate::tas::toggle_mode::ToggleMode;
+
+//Rules are loaded from <data directory>/<game>.triggers.json, a list of rules like:
+//{
+//    "name": "Godrick's Great Rune",
+//    "condition": { "type": "all", "conditions": [{ "type": "flag_set", "flag": 171 }, { "type": "flag_cleared", "flag": 181 }] },
+//    "actions": [{ "type": "alert", "message": "Great Rune obtained" }, { "type": "notify", "message": "godrick" }]
+//}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Rule
+{
+    pub name: String,
+    pub condition: Condition,
+    pub actions: Vec<Action>,
+    //Fire again every time the condition becomes true, instead of only the first time
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Condition
+{
+    FlagSet { flag: u32 },
+    FlagCleared { flag: u32 },
+    //bits is the flag's bit count, needed to read the flag from the game before it has been written
+    QuantityAtLeast
+    {
+        flag: u32,
+        quantity: i32,
+        #[serde(default)]
+        bits: Option<u8>,
+    },
+    All { conditions: Vec<Condition> },
+    Any { conditions: Vec<Condition> },
+    //Every step has to become true, in order
+    Sequence { steps: Vec<Condition> },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action
+{
+    Log { message: String },
+    //Pushed to every connected server client
+    Notify { message: String },
+    //Shown on top of the overlay for a few seconds
+    Alert
+    {
+        message: String,
+        #[serde(default = "default_alert_seconds")]
+        seconds: f32,
+    },
+    SetEventFlag { flag: u32, state: bool },
+    AiToggleMode { mode: ToggleMode },
+    //Hold gamepad buttons, an XINPUT_GAMEPAD button mask like 0x1000 for A, for a number of frames
+    TasPress { buttons: u16, frames: u32 },
+}
+
+fn default_alert_seconds() -> f32
+{
+    return 5.0f32;
+}
@@ -0,0 +1,182 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use crate::splits::livesplit::DEFAULT_LIVESPLIT_ADDRESS;
+use crate::util::{game_key, DATA_DIRECTORY};
+use crate::util::vector3f::Vector3f;
+
+//Everything the user configures in the overlay, stored per game in <data directory>/<game>.json.
+//Every field is optional in the file so that older or hand written configs keep loading.
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default)]
+pub struct Config
+{
+    pub event_flag_blacklist: Vec<u32>,
+    pub watched_event_flags: Vec<WatchedEventFlag>,
+    //Positions saved before teleport presets had their own file, moved there on load
+    pub positions: Vec<SavedPosition>,
+    pub ai_timer_toggle_threshold: Option<f32>,
+    //Emevd main class index -> shown in the log
+    pub emevd_group_filters: BTreeMap<u64, bool>,
+    pub livesplit: LiveSplitSettings,
+    //Virtual key code, the \ key when not set
+    pub quitout_hotkey: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct WatchedEventFlag
+{
+    pub flag: u32,
+    //Set for quantity flags, the width of the flag in bits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_count: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SavedPosition
+{
+    pub name: String,
+    pub position: Vector3f,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(default)]
+pub struct LiveSplitSettings
+{
+    pub enabled: bool,
+    //host:port of the LiveSplit Server component
+    pub address: String,
+}
+
+impl Default for LiveSplitSettings
+{
+    fn default() -> Self
+    {
+        LiveSplitSettings { enabled: false, address: String::from(DEFAULT_LIVESPLIT_ADDRESS) }
+    }
+}
+
+impl Config
+{
+    pub fn path(process_name: &str) -> PathBuf
+    {
+        return Path::new(DATA_DIRECTORY).join(format!("{}.json", game_key(process_name)));
+    }
+
+    pub fn backup_path(path: &Path) -> PathBuf
+    {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        return PathBuf::from(backup);
+    }
+
+    ///Load the config, falls back to defaults when the file is missing or broken.
+    ///A broken file is moved to <file>.bak first so that saving the defaults doesn't overwrite it, errors when that fails.
+    pub fn load(path: &Path) -> Result<Config, String>
+    {
+        if !path.exists()
+        {
+            return Ok(Config::default());
+        }
+
+        match fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|json| import::<Config>(&json))
+        {
+            Ok(config) =>
+            {
+                info!("loaded config from {}", path.display());
+                Ok(config)
+            }
+            Err(e) =>
+            {
+                let backup = Config::backup_path(path);
+                fs::rename(path, &backup).map_err(|rename_error| format!("failed to load config from {}: {}, and failed to move it to {}: {}", path.display(), e, backup.display(), rename_error))?;
+                warn!("failed to load config from {}, moved it to {} and using defaults: {}", path.display(), backup.display(), e);
+                Ok(Config::default())
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String>
+    {
+        if let Some(directory) = path.parent()
+        {
+            fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+        return fs::write(path, export(self)).map_err(|e| e.to_string());
+    }
+}
+
+///Serialize a config or part of one, used for sharing blacklists and position sets
+pub fn export<T: Serialize>(value: &T) -> String
+{
+    return serde_json::to_string_pretty(value).unwrap();
+}
+
+pub fn import<T: DeserializeOwned>(json: &str) -> Result<T, String>
+{
+    return serde_json::from_str::<T>(json).map_err(|e| e.to_string());
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::util::config::*;
+
+    #[test]
+    pub fn missing_fields_use_defaults()
+    {
+        let config = import::<Config>(r#"{"event_flag_blacklist": [1, 2]}"#).unwrap();
+        assert_eq!(config.event_flag_blacklist, vec![1, 2]);
+        assert!(config.positions.is_empty());
+        assert_eq!(config.ai_timer_toggle_threshold, None);
+    }
+
+    #[test]
+    pub fn export_import_round_trip()
+    {
+        let mut config = Config::default();
+        config.watched_event_flags.push(WatchedEventFlag { flag: 100, bit_count: Some(8) });
+        config.positions.push(SavedPosition { name: String::from("bonfire"), position: Vector3f::new(1.0, 2.0, 3.0), rotation: None });
+        config.emevd_group_filters.insert(2000, false);
+
+        assert_eq!(import::<Config>(&export(&config)).unwrap(), config);
+        assert_eq!(import::<Vec<SavedPosition>>(&export(&config.positions)).unwrap(), config.positions);
+    }
+
+    #[test]
+    pub fn broken_config_is_moved_aside()
+    {
+        let directory = std::env::temp_dir().join(format!("soulmemory-config-test-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("eldenring.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(Config::backup_path(&path)).unwrap(), "{ not json");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
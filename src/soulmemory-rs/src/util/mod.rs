@@ -17,7 +17,51 @@
 pub(crate) mod log;
 pub(crate) mod console;
 pub(crate) mod server;
-pub mod vector3f;
+pub(crate) mod config;
+pub use soulmemory_common::vector3f;
+
+use std::env;
+use std::path::PathBuf;
+use soulmemory_common::version::Version;
+use windows::Win32::Foundation::HMODULE;
+use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
+
+///Directory for files that soulmemory-rs reads and writes, next to the log file
+pub const DATA_DIRECTORY: &str = "C:/temp/soulmemory";
+
+///Path of the injected dll
+pub fn dll_path() -> Option<PathBuf>
+{
+    unsafe
+    {
+        let mut buffer = [0u16; 1024];
+        let length = GetModuleFileNameW(HMODULE(crate::HMODULE.0), &mut buffer) as usize;
+        if length == 0 || length == buffer.len()
+        {
+            return None;
+        }
+        return Some(PathBuf::from(String::from_utf16_lossy(&buffer[..length])));
+    }
+}
+
+///File version of the game's exe, None when it can't be read
+pub fn game_version() -> Option<Version>
+{
+    return env::current_exe().ok().and_then(|path| Version::from_file(&path).ok());
+}
+
+///Key used for per game files, the lowercase process name without extension
+pub fn game_key(process_name: &str) -> String
+{
+    let lower = process_name.to_lowercase();
+    return lower.strip_suffix(".exe").unwrap_or(&lower).to_string();
+}
+
+///h:mm:ss.fff, as shown by timers
+pub fn format_milliseconds(ms: u64) -> String
+{
+    return format!("{}:{:02}:{:02}.{:03}", ms / 3_600_000, (ms / 60_000) % 60, (ms / 1000) % 60, ms % 1000);
+}
 
 pub unsafe fn get_stack_u32(esp: u32, offset: usize) -> u32
 {
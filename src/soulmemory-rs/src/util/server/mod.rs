@@ -0,0 +1,321 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod protocol;
+mod subscriptions;
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use log::info;
+use crate::games::traits::buffered_event_flags::EventFlag;
+use crate::event_flags::names::EventFlagNames;
+
+pub use protocol::*;
+pub use subscriptions::*;
+
+pub type ClientId = u64;
+
+//Lines waiting to be written to a client, a client that falls this far behind is dropped
+const CLIENT_QUEUE_SIZE: usize = 4096;
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+//Lines are handed to a writer thread per client, so a client that stops reading can never block the refresh loop
+struct Client
+{
+    stream: TcpStream,
+    sender: SyncSender<String>,
+}
+
+type Clients = Arc<Mutex<HashMap<ClientId, Client>>>;
+
+pub struct IncomingRequest
+{
+    pub client: ClientId,
+    pub id: u64,
+    pub request: Request,
+}
+
+pub struct Server
+{
+    requests: Arc<Mutex<VecDeque<IncomingRequest>>>,
+    clients: Clients,
+    subscriptions: Subscriptions,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>
+}
+
+impl Default for Server
+{
+    fn default() -> Server
+    {
+        Server
+        {
+            requests: Arc::new(Mutex::new(VecDeque::new())),
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            subscriptions: Subscriptions::new(),
+            shutdown: Arc::new(AtomicBool::new(false)),
+            handle: None,
+        }
+    }
+}
+
+impl Drop for Server
+{
+    fn drop(&mut self)
+    {
+        if !self.shutdown.load(Ordering::Relaxed)
+        {
+            self.shutdown();
+        }
+    }
+}
+
+impl Server
+{
+    pub fn get_request(&mut self) -> Option<IncomingRequest>
+    {
+        let mut guard = self.requests.lock().unwrap();
+        return guard.pop_front();
+    }
+
+    pub fn respond(&self, client: ClientId, id: u64, response: Response)
+    {
+        self.send_line(client, ResponseFrame::new(Some(id), response).to_line());
+    }
+
+    pub fn subscribe_event_flags(&mut self, client: ClientId, filter: EventFlagFilter) -> SubscriptionId
+    {
+        return self.subscriptions.subscribe_event_flags(client, filter);
+    }
+
+    pub fn unsubscribe(&mut self, client: ClientId, subscription: SubscriptionId) -> bool
+    {
+        return self.subscriptions.unsubscribe(client, subscription);
+    }
+
+    ///Push every flag to the subscriptions whose filter lets it through, named from the game's flag database when known
+    pub fn publish_event_flags(&mut self, event_flags: &[EventFlag], names: &EventFlagNames)
+    {
+        self.remove_disconnected_subscriptions();
+
+        for subscription in self.subscriptions.event_flags()
+        {
+            for event_flag in event_flags.iter().filter(|f| subscription.filter.matches(f.flag))
+            {
+                let response = Response::EventFlag
+                {
+                    subscription: subscription.id,
+                    time: event_flag.time.to_rfc3339(),
+                    flag: event_flag.flag,
+                    value: event_flag.value,
+                    name: names.name(event_flag.flag).map(String::from),
+                };
+                self.send_line(subscription.client, ResponseFrame::new(None, response).to_line());
+            }
+        }
+    }
+
+    fn remove_disconnected_subscriptions(&mut self)
+    {
+        let clients = self.clients.lock().unwrap();
+        let disconnected: Vec<ClientId> = self.subscriptions.event_flags().iter().map(|s| s.client).filter(|c| !clients.contains_key(c)).collect();
+        drop(clients);
+
+        for client in disconnected
+        {
+            self.subscriptions.remove_client(client);
+        }
+    }
+
+    ///Send a notification to every connected client
+    pub fn broadcast(&self, response: Response)
+    {
+        let line = ResponseFrame::new(None, response).to_line();
+        let clients: Vec<ClientId> = self.clients.lock().unwrap().keys().copied().collect();
+        for client in clients
+        {
+            self.send_line(client, line.clone());
+        }
+    }
+
+    fn send_line(&self, client: ClientId, line: String)
+    {
+        Self::queue_line(&self.clients, client, line);
+    }
+
+    //Never blocks, a client whose queue is full or whose writer stopped is dropped
+    fn queue_line(clients: &Clients, client: ClientId, line: String)
+    {
+        let mut clients = clients.lock().unwrap();
+        let result = match clients.get(&client)
+        {
+            Some(connection) => connection.sender.try_send(line),
+            None => return,
+        };
+
+        let reason = match result
+        {
+            Ok(()) => return,
+            Err(TrySendError::Full(_)) => "it is not reading",
+            Err(TrySendError::Disconnected(_)) => "writing failed",
+        };
+        info!("dropping client {}, {}", client, reason);
+        if let Some(connection) = clients.remove(&client)
+        {
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    pub fn shutdown(&mut self)
+    {
+        self.shutdown.store(true, Ordering::Relaxed);
+
+        //Unblock any connection threads waiting on a read
+        for connection in self.clients.lock().unwrap().values()
+        {
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+
+        if let Some(handle) = self.handle.take()
+        {
+            handle.join().unwrap();
+        }
+    }
+
+    pub fn new(addr: String) -> Self
+    {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let requests = Arc::new(Mutex::new(VecDeque::new()));
+        let clients = Arc::new(Mutex::new(HashMap::new()));
+
+        let thread_shutdown = Arc::clone(&shutdown);
+        let thread_requests = Arc::clone(&requests);
+        let thread_clients = Arc::clone(&clients);
+
+        let handle = thread::spawn(move ||
+        {
+            let listener = TcpListener::bind(addr).unwrap();
+            //Non-blocking accept so the shutdown flag gets checked regularly
+            listener.set_nonblocking(true).unwrap();
+            let next_client_id = AtomicU64::new(0);
+
+            while !thread_shutdown.load(Ordering::Relaxed)
+            {
+                match listener.accept()
+                {
+                    Ok((stream, peer)) =>
+                    {
+                        let client = next_client_id.fetch_add(1, Ordering::Relaxed);
+                        info!("client {} connected from {}", client, peer);
+                        Self::accept_client(client, stream, &thread_requests, &thread_clients);
+                    },
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(50)),
+                    Err(e) => info!("connection failed {}", e),
+                }
+            }
+        });
+
+        Server
+        {
+            requests,
+            clients,
+            subscriptions: Subscriptions::new(),
+            shutdown,
+            handle: Some(handle),
+        }
+    }
+
+    fn accept_client(client: ClientId, stream: TcpStream, requests: &Arc<Mutex<VecDeque<IncomingRequest>>>, clients: &Clients)
+    {
+        //Accepted sockets inherit the non-blocking mode of the listener
+        if let Err(e) = stream.set_nonblocking(false).and_then(|_| stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT)))
+        {
+            info!("failed to configure client {}. {}", client, e);
+            return;
+        }
+
+        let (mut writer, connection) = match (stream.try_clone(), stream.try_clone())
+        {
+            (Ok(writer), Ok(connection)) => (writer, connection),
+            (Err(e), _) | (_, Err(e)) =>
+            {
+                info!("failed to clone stream for client {}. {}", client, e);
+                return;
+            }
+        };
+
+        let (sender, receiver) = sync_channel::<String>(CLIENT_QUEUE_SIZE);
+        clients.lock().unwrap().insert(client, Client { stream: connection, sender });
+
+        thread::spawn(move ||
+        {
+            //Ends when the client is dropped, or when a write fails or times out
+            for line in receiver
+            {
+                if let Err(e) = writer.write_all(line.as_bytes())
+                {
+                    info!("sending to client {} failed. {}", client, e);
+                    let _ = writer.shutdown(Shutdown::Both);
+                    break;
+                }
+            }
+        });
+
+        let thread_requests = Arc::clone(requests);
+        let thread_clients = Arc::clone(clients);
+        thread::spawn(move ||
+        {
+            let reader = BufReader::new(stream);
+            for line in reader.lines()
+            {
+                let line = match line
+                {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+
+                if line.trim().is_empty()
+                {
+                    continue;
+                }
+
+                match parse_request(&line)
+                {
+                    Ok(frame) =>
+                    {
+                        let mut guard = thread_requests.lock().unwrap();
+                        guard.push_back(IncomingRequest{ client, id: frame.id, request: frame.request });
+                    }
+                    Err(error) =>
+                    {
+                        info!("Parsing incoming message failed. Raw message:\n{}", line);
+                        Self::queue_line(&thread_clients, client, error.to_line());
+                    }
+                }
+            }
+
+            info!("client {} disconnected", client);
+            thread_clients.lock().unwrap().remove(&client);
+        });
+    }
+}
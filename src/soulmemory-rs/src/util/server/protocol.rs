@@ -0,0 +1,266 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use soulmemory_common::report::ResolutionReport;
+use soulmemory_common::version::VersionCheck;
+use crate::games::traits::buffered_event_flags::EventFlagValue;
+use crate::event_flags::names::NamedEventFlag;
+use crate::trackers::great_runes::GreatRuneStatus;
+use crate::games::traits::loading_state::{ScreenState, ScreenStateChange};
+use crate::splits::splitter::{SplitEvent, SplitterState};
+use crate::util::server::subscriptions::{EventFlagFilter, SubscriptionId};
+
+//Frames are exchanged as newline delimited json, one frame per line.
+//Every request carries an id chosen by the client, the matching response echoes it back.
+//Example: {"id": 1, "command": "Ping"} -> {"id": 1, "type": "Pong"}
+//Notifications pushed by the server for a subscription carry no id.
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "command")]
+pub enum Request
+{
+    Ping,
+    SubscribeEventFlags
+    {
+        #[serde(default)]
+        filter: EventFlagFilter,
+    },
+    Unsubscribe { subscription: SubscriptionId },
+    GetEventFlags { flags: Vec<u32> },
+    GetEventFlagQuantities { flags: Vec<EventFlagQuantityQuery> },
+    SetEventFlag { flag: u32, state: bool },
+    //Case insensitive search through the names, categories and areas of the game's event flag database
+    SearchEventFlagNames { query: String },
+    //Reload the trigger rules file and re-arm every rule
+    ReloadTriggers,
+    GetGreatRunes,
+    GetInGameTime,
+    GetScreenState,
+    Quitout,
+    GetSplits,
+    //Reload the split list file, resets the current run
+    ReloadSplits,
+    StartRun,
+    Split,
+    SkipSplit,
+    UndoSplit,
+    ResetRun,
+    //Save the main module and the given ranges to a snapshot file, for testing game logic against a fixture
+    CaptureMemorySnapshot
+    {
+        #[serde(default)]
+        regions: Vec<MemoryRange>,
+    },
+    //The detected game version, which patterns were found on attach and which capabilities are unavailable
+    GetDiagnostics,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct EventFlagQuantityQuery
+{
+    pub flag: u32,
+    //Width of the flag in bits
+    pub bit_count: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct MemoryRange
+{
+    pub address: usize,
+    pub size: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct EventFlagReading
+{
+    pub flag: u32,
+    pub value: EventFlagValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct RequestFrame
+{
+    pub id: u64,
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum Response
+{
+    Pong,
+    Error { message: String },
+    //The loaded game does not support the requested capability
+    Unsupported { capability: String },
+    Subscribed { subscription: SubscriptionId },
+    Unsubscribed { subscription: SubscriptionId },
+    EventFlag
+    {
+        subscription: SubscriptionId,
+        time: String,
+        flag: u32,
+        value: EventFlagValue,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+    EventFlags { flags: Vec<EventFlagReading> },
+    EventFlagSet { flag: u32, state: bool },
+    EventFlagNames { names: Vec<NamedEventFlag> },
+    TriggersLoaded { rules: usize },
+    //Sent to every client by a trigger rule with a notify action
+    Trigger { rule: String, message: String },
+    GreatRunes { runes: Vec<GreatRuneStatus> },
+    InGameTime { milliseconds: u32 },
+    ScreenState { state: ScreenState, quitouts: u32 },
+    QuitoutRequested,
+    //Sent to every client whenever the game starts or stops loading, reaches the main menu or quits out
+    ScreenStateChanged { change: ScreenStateChange },
+    Splits { state: SplitterState },
+    //Sent to every client whenever the run changes
+    SplitEvent { event: SplitEvent },
+    MemorySnapshotSaved { path: String },
+    Diagnostics
+    {
+        version: VersionCheck,
+        //None for games that don't resolve from a pattern table
+        report: Option<ResolutionReport>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ResponseFrame
+{
+    //None when the request could not be parsed far enough to read its id
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub response: Response,
+}
+
+impl ResponseFrame
+{
+    pub fn new(id: Option<u64>, response: Response) -> Self
+    {
+        ResponseFrame { id, response }
+    }
+
+    pub fn error(id: Option<u64>, message: String) -> Self
+    {
+        ResponseFrame { id, response: Response::Error { message } }
+    }
+
+    pub fn to_line(&self) -> String
+    {
+        let mut json = serde_json::to_string(self).unwrap();
+        json.push('\n');
+        return json;
+    }
+}
+
+///Parse a single line into a request frame. When parsing fails, returns the error frame that should be sent back.
+pub fn parse_request(line: &str) -> Result<RequestFrame, ResponseFrame>
+{
+    let value = match serde_json::from_str::<serde_json::Value>(line)
+    {
+        Ok(value) => value,
+        Err(e) => return Err(ResponseFrame::error(None, format!("invalid json: {}", e))),
+    };
+
+    let id = value.get("id").and_then(|id| id.as_u64());
+    match serde_json::from_value::<RequestFrame>(value)
+    {
+        Ok(frame) => Ok(frame),
+        Err(e) => Err(ResponseFrame::error(id, format!("invalid request: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use soulmemory_common::version::Version;
+    use crate::util::server::protocol::*;
+
+    #[test]
+    pub fn parse_ping()
+    {
+        let frame = parse_request(r#"{"id": 7, "command": "Ping"}"#).unwrap();
+        assert_eq!(frame.id, 7);
+        assert_eq!(frame.request, Request::Ping);
+    }
+
+    #[test]
+    pub fn parse_invalid_json()
+    {
+        let error = parse_request("{\"id\": 7, \"command\"").unwrap_err();
+        assert_eq!(error.id, None);
+        assert!(matches!(error.response, Response::Error { .. }));
+    }
+
+    #[test]
+    pub fn parse_unknown_command_keeps_id()
+    {
+        let error = parse_request(r#"{"id": 3, "command": "TasStart"}"#).unwrap_err();
+        assert_eq!(error.id, Some(3));
+        assert!(matches!(error.response, Response::Error { .. }));
+    }
+
+    #[test]
+    pub fn parse_subscribe_without_filter()
+    {
+        let frame = parse_request(r#"{"id": 1, "command": "SubscribeEventFlags"}"#).unwrap();
+        assert_eq!(frame.request, Request::SubscribeEventFlags { filter: EventFlagFilter::default() });
+    }
+
+    #[test]
+    pub fn parse_batched_event_flag_query()
+    {
+        let frame = parse_request(r#"{"id": 2, "command": "GetEventFlags", "flags": [100, 200]}"#).unwrap();
+        assert_eq!(frame.request, Request::GetEventFlags { flags: vec![100, 200] });
+
+        let frame = parse_request(r#"{"id": 3, "command": "GetEventFlagQuantities", "flags": [{"flag": 100, "bit_count": 8}]}"#).unwrap();
+        assert_eq!(frame.request, Request::GetEventFlagQuantities { flags: vec![EventFlagQuantityQuery { flag: 100, bit_count: 8 }] });
+    }
+
+    #[test]
+    pub fn serialize_event_flag_notification()
+    {
+        let response = Response::EventFlag { subscription: 2, time: String::from("t"), flag: 100, value: EventFlagValue::Quantity(3), name: None };
+        let line = ResponseFrame::new(None, response).to_line();
+        assert_eq!(line, "{\"id\":null,\"type\":\"EventFlag\",\"subscription\":2,\"time\":\"t\",\"flag\":100,\"value\":3}\n");
+
+        let response = Response::EventFlag { subscription: 2, time: String::from("t"), flag: 171, value: EventFlagValue::State(true), name: Some(String::from("Godrick's Great Rune")) };
+        let line = ResponseFrame::new(None, response).to_line();
+        assert_eq!(line, "{\"id\":null,\"type\":\"EventFlag\",\"subscription\":2,\"time\":\"t\",\"flag\":171,\"value\":true,\"name\":\"Godrick's Great Rune\"}\n");
+    }
+
+    #[test]
+    pub fn serialize_response()
+    {
+        let line = ResponseFrame::new(Some(7), Response::Pong).to_line();
+        assert_eq!(line, "{\"id\":7,\"type\":\"Pong\"}\n");
+    }
+
+    #[test]
+    pub fn serialize_diagnostics_without_report()
+    {
+        let version = VersionCheck { version: Some(Version::new(1, 15, 2, 0)), capabilities: None, warnings: Vec::new() };
+        let line = ResponseFrame::new(Some(4), Response::Diagnostics { version, report: None }).to_line();
+        assert_eq!(line, "{\"id\":4,\"type\":\"Diagnostics\",\"version\":{\"version\":\"1.15.2.0\",\"capabilities\":null,\"warnings\":[]},\"report\":null}\n");
+    }
+}
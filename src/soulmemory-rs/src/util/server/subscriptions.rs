@@ -0,0 +1,147 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::{Deserialize, Serialize};
+use crate::util::server::ClientId;
+
+///Inclusive range of event flag ids
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct EventFlagRange
+{
+    pub start: u32,
+    pub end: u32,
+}
+
+///Server side filter for an event flag subscription.
+///Flags in the deny list are always dropped. When no ranges and no allow list are given, every other flag passes,
+///otherwise a flag has to be inside one of the ranges or in the allow list.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(default)]
+pub struct EventFlagFilter
+{
+    pub ranges: Vec<EventFlagRange>,
+    pub allow: Vec<u32>,
+    pub deny: Vec<u32>,
+}
+
+impl EventFlagFilter
+{
+    pub fn matches(&self, flag: u32) -> bool
+    {
+        if self.deny.contains(&flag)
+        {
+            return false;
+        }
+
+        if self.ranges.is_empty() && self.allow.is_empty()
+        {
+            return true;
+        }
+
+        return self.allow.contains(&flag) || self.ranges.iter().any(|r| r.start <= flag && flag <= r.end);
+    }
+}
+
+pub type SubscriptionId = u64;
+
+pub struct EventFlagSubscription
+{
+    pub id: SubscriptionId,
+    pub client: ClientId,
+    pub filter: EventFlagFilter,
+}
+
+pub struct Subscriptions
+{
+    next_id: SubscriptionId,
+    event_flags: Vec<EventFlagSubscription>,
+}
+
+impl Subscriptions
+{
+    pub fn new() -> Self
+    {
+        Subscriptions
+        {
+            next_id: 0,
+            event_flags: Vec::new(),
+        }
+    }
+
+    pub fn subscribe_event_flags(&mut self, client: ClientId, filter: EventFlagFilter) -> SubscriptionId
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.event_flags.push(EventFlagSubscription{ id, client, filter });
+        return id;
+    }
+
+    ///Returns false when the subscription does not exist or belongs to another client
+    pub fn unsubscribe(&mut self, client: ClientId, id: SubscriptionId) -> bool
+    {
+        let count = self.event_flags.len();
+        self.event_flags.retain(|s| !(s.id == id && s.client == client));
+        return self.event_flags.len() != count;
+    }
+
+    pub fn remove_client(&mut self, client: ClientId)
+    {
+        self.event_flags.retain(|s| s.client != client);
+    }
+
+    pub fn event_flags(&self) -> &Vec<EventFlagSubscription>
+    {
+        return &self.event_flags;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::util::server::subscriptions::*;
+
+    #[test]
+    pub fn empty_filter_matches_everything()
+    {
+        let filter = EventFlagFilter::default();
+        assert!(filter.matches(0));
+        assert!(filter.matches(u32::MAX));
+    }
+
+    #[test]
+    pub fn filter_ranges_allow_and_deny()
+    {
+        let filter: EventFlagFilter = serde_json::from_str(r#"{"ranges": [{"start": 100, "end": 200}], "allow": [5], "deny": [150]}"#).unwrap();
+        assert!(filter.matches(100));
+        assert!(filter.matches(200));
+        assert!(filter.matches(5));
+        assert!(!filter.matches(150));
+        assert!(!filter.matches(201));
+    }
+
+    #[test]
+    pub fn unsubscribe_only_own_subscriptions()
+    {
+        let mut subscriptions = Subscriptions::new();
+        let a = subscriptions.subscribe_event_flags(1, EventFlagFilter::default());
+        let b = subscriptions.subscribe_event_flags(2, EventFlagFilter::default());
+        assert!(!subscriptions.unsubscribe(2, a));
+        assert!(subscriptions.unsubscribe(1, a));
+
+        subscriptions.remove_client(2);
+        assert!(subscriptions.event_flags().iter().all(|s| s.id != b));
+    }
+}
@@ -14,22 +14,24 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::ops::DerefMut;
+use std::ops::{Deref, DerefMut};
 use imgui::{TreeNodeFlags, Ui};
 use crate::games::*;
 use crate::widgets::widget::Widget;
 use crate::tas::toggle_mode::ToggleMode;
+use crate::util::config::Config;
 
 pub struct AiToggleWidget
 {
     selected_toggle_mode_index: u32,
+    config_changed: bool,
 }
 
 impl AiToggleWidget
 {
     pub fn new() -> Self
     {
-        AiToggleWidget { selected_toggle_mode_index: 0}
+        AiToggleWidget { selected_toggle_mode_index: 0, config_changed: false }
     }
 }
 
@@ -46,7 +48,15 @@ impl Widget for AiToggleWidget
                 ui.text(format!("{}", dsr.get_ai_timer_value()));
 
                 let _a = ui.push_item_width(100.0f32);
-                ui.input_float("auto toggle timing", &mut dsr.ai_timer_toggle_threshold).build();
+                self.config_changed |= ui.input_float("auto toggle timing", &mut dsr.ai_timer_toggle_threshold).build();
+
+                //The mode can also be changed by trigger rules
+                self.selected_toggle_mode_index = match dsr.ai_timer_toggle_mode
+                {
+                    ToggleMode::None => 0,
+                    ToggleMode::Right => 1,
+                    ToggleMode::Left => 2,
+                };
 
                 ui.text("Auto toggle mode:");
                 ui.radio_button("None", &mut self.selected_toggle_mode_index, 0);
@@ -63,4 +73,25 @@ impl Widget for AiToggleWidget
             }
         }
     }
+
+    fn load_config(&mut self, game: &mut Box<dyn Game>, config: &Config)
+    {
+        if let (Some(dsr), Some(threshold)) = (GameExt::get_game_mut::<DarkSoulsRemastered>(game.deref_mut()), config.ai_timer_toggle_threshold)
+        {
+            dsr.ai_timer_toggle_threshold = threshold;
+        }
+    }
+
+    fn save_config(&self, game: &mut Box<dyn Game>, config: &mut Config)
+    {
+        if let Some(dsr) = GameExt::get_game_ref::<DarkSoulsRemastered>(game.deref().deref())
+        {
+            config.ai_timer_toggle_threshold = Some(dsr.ai_timer_toggle_threshold);
+        }
+    }
+
+    fn take_config_changed(&mut self) -> bool
+    {
+        return std::mem::take(&mut self.config_changed);
+    }
 }
\ No newline at end of file
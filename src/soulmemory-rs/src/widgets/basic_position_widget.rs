@@ -14,30 +14,100 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+
+use std::path::PathBuf;
 use imgui::{TreeNodeFlags, Ui};
-use log::info;
+use log::{info, warn};
 use crate::games::*;
-use crate::widgets::widget::Widget;
+use crate::games::traits::player_position::MapId;
+use crate::positions::teleport_presets::TeleportPresets;
+use crate::widgets::widget::{clipboard_import_export, Widget};
+use crate::util::config::{Config, SavedPosition};
 use crate::util::vector3f::Vector3f;
 
 pub struct PlayerPositionWidget
 {
     position_input_vec: Vector3f,
+    position_input_rotation: Option<f32>,
     position_input_text: String,
-    positions: Vec<(String, Vector3f)>,
+    presets_path: PathBuf,
+    presets: TeleportPresets,
+    //False when the presets file could not be read, it is not overwritten until it loads again
+    presets_loaded: bool,
+    //Positions from the config that could not be moved to the presets file, kept in the config
+    legacy_positions: Vec<SavedPosition>,
+    //Set when positions were moved out of the config, so it gets saved without them
+    config_changed: bool,
+    error: Option<String>,
 }
 
 impl PlayerPositionWidget
 {
-    pub fn new() -> Self
+    pub fn new(process_name: &str) -> Self
     {
-        PlayerPositionWidget
+        let mut widget = PlayerPositionWidget
         {
             position_input_vec: Vector3f::default(),
+            position_input_rotation: None,
             position_input_text: String::new(),
-            positions: Vec::new(),
+            presets_path: TeleportPresets::path(process_name),
+            presets: TeleportPresets::new(),
+            presets_loaded: false,
+            legacy_positions: Vec::new(),
+            config_changed: false,
+            error: None,
+        };
+        widget.load_presets();
+        widget
+    }
+
+    fn load_presets(&mut self)
+    {
+        match TeleportPresets::load(&self.presets_path)
+        {
+            Ok(presets) =>
+            {
+                self.presets = presets;
+                self.presets_loaded = true;
+                self.error = None;
+            }
+            Err(e) =>
+            {
+                warn!("failed to load teleport presets from {}: {}", self.presets_path.display(), e);
+                self.presets_loaded = false;
+                self.error = Some(format!("failed to load {}: {}", self.presets_path.display(), e));
+            }
         }
     }
+
+    fn save_presets(&mut self) -> bool
+    {
+        if !self.presets_loaded
+        {
+            self.error = Some(format!("not saved, fix {} and reload first", self.presets_path.display()));
+            return false;
+        }
+
+        match self.presets.save(&self.presets_path)
+        {
+            Ok(()) =>
+            {
+                self.error = None;
+                true
+            }
+            Err(e) =>
+            {
+                warn!("failed to save teleport presets to {}: {}", self.presets_path.display(), e);
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+
+    fn map_name(map: Option<MapId>) -> String
+    {
+        return map.map(|m| m.to_string()).unwrap_or_else(|| String::from("no map"));
+    }
 }
 
 
@@ -48,30 +118,47 @@ impl Widget for PlayerPositionWidget
         if let Some(position) = game.player_position()
         {
             let current_position = position.get_position();
+            let current_rotation = position.get_rotation();
+            let current_map = position.get_map_id();
 
             if ui.collapsing_header("positions", TreeNodeFlags::FRAMED)
             {
                 //Display current pos
-                ui.text("current position:");
+                ui.text(format!("current position ({}):", Self::map_name(current_map)));
                 ui.text(format!("{:.2}", current_position.x));
                 ui.same_line();
                 ui.text(format!("{:.2}", current_position.y));
                 ui.same_line();
                 ui.text(format!("{:.2}", current_position.z));
+                if let Some(rotation) = current_rotation
+                {
+                    ui.same_line();
+                    ui.text(format!("rotation {:.2}", rotation));
+                }
 
-                //Add positions to list
+                //Add positions to the presets of the current map
                 if ui.button("import")
                 {
-                    self.position_input_vec.x = current_position.x;
-                    self.position_input_vec.y = current_position.y;
-                    self.position_input_vec.z = current_position.z;
+                    self.position_input_vec = current_position;
+                    self.position_input_rotation = current_rotation;
                 }
                 ui.same_line();
                 if ui.button("add")
                 {
-                    self.positions.push((self.position_input_text.clone(), self.position_input_vec.clone()));
+                    self.presets.add(current_map, SavedPosition { name: self.position_input_text.clone(), position: self.position_input_vec, rotation: self.position_input_rotation });
+                    self.save_presets();
                     self.position_input_text.clear();
                     self.position_input_vec = Vector3f::default();
+                    self.position_input_rotation = None;
+                }
+                ui.same_line();
+                if ui.button("reload")
+                {
+                    self.load_presets();
+                }
+                if ui.is_item_hovered()
+                {
+                    ui.tooltip_text(format!("Read {} again, for presets edited by hand.", self.presets_path.display()));
                 }
 
                 ui.input_text("description: ", &mut self.position_input_text).build();
@@ -84,39 +171,101 @@ impl Widget for PlayerPositionWidget
                 let _c = ui.push_item_width(100f32);
                 ui.input_float("z", &mut self.position_input_vec.z).build();
 
-                //Display list of positions
+                //Share the presets of the current map
+                let mut shared = self.presets.presets(current_map).cloned().unwrap_or_default();
+                clipboard_import_export(ui, &mut shared);
+                if self.presets.add_all(current_map, shared) > 0
+                {
+                    self.save_presets();
+                }
+
+                if let Some(error) = &self.error
+                {
+                    ui.text_colored([1.0f32, 0.3f32, 0.3f32, 1.0f32], error);
+                }
+
+                //Display the presets per map, only presets of the current map can be restored
+                let mut delete = None;
                 ui.child_window("positions_scrollable")
                     .size([ui.content_region_avail()[0], 400.0f32])
                     .build(||
                         {
-                            let mut delete_index = None;
-                            for i in 0..self.positions.len()
+                            for group in self.presets.groups()
                             {
-                                ui.text(&self.positions[i].0);
-                                if ui.button("restore")
-                                {
-                                    info!("restore {}", i);
-                                    position.set_position(&self.positions[i].1);
-                                }
-                                ui.same_line();
-                                if ui.button("delete")
-                                {
-                                    delete_index = Some(i);
-                                }
+                                let is_current = group.map == current_map;
+                                let label = format!("{} ({}){}", Self::map_name(group.map), group.presets.len(), if is_current { ", current" } else { "" });
+                                let flags = if is_current { TreeNodeFlags::DEFAULT_OPEN } else { TreeNodeFlags::empty() };
 
-                                ui.text(format!("{:.2}", &self.positions[i].1.x));
-                                ui.same_line();
-                                ui.text(format!("{:.2}", &self.positions[i].1.y));
-                                ui.same_line();
-                                ui.text(format!("{:.2}", &self.positions[i].1.z));
-                            }
+                                if let Some(_node) = ui.tree_node_config(&label).flags(flags).push()
+                                {
+                                    for (i, preset) in group.presets.iter().enumerate()
+                                    {
+                                        let _id = ui.push_id(i.to_string());
+                                        ui.text(&preset.name);
+                                        if is_current
+                                        {
+                                            if ui.button("restore")
+                                            {
+                                                info!("restore {} in {}", preset.name, Self::map_name(group.map));
+                                                position.set_position(&preset.position);
+                                                if let Some(rotation) = preset.rotation
+                                                {
+                                                    position.set_rotation(rotation);
+                                                }
+                                            }
+                                            ui.same_line();
+                                        }
+                                        if ui.button("delete")
+                                        {
+                                            delete = Some((group.map, i));
+                                        }
 
-                            if let Some(index) = delete_index
-                            {
-                                self.positions.remove(index);
+                                        ui.text(format!("{:.2}", preset.position.x));
+                                        ui.same_line();
+                                        ui.text(format!("{:.2}", preset.position.y));
+                                        ui.same_line();
+                                        ui.text(format!("{:.2}", preset.position.z));
+                                    }
+                                }
                             }
                         });
+
+                if let Some((map, index)) = delete
+                {
+                    self.presets.remove(map, index);
+                    self.save_presets();
+                }
             }
         }
     }
-}
\ No newline at end of file
+
+    fn load_config(&mut self, _game: &mut Box<dyn Game>, config: &Config)
+    {
+        if config.positions.is_empty()
+        {
+            return;
+        }
+
+        //Positions from the config don't have a map, they were saved before maps were known
+        self.presets.add_all(None, config.positions.clone());
+        if self.save_presets()
+        {
+            info!("moved {} positions from the config to {}", config.positions.len(), self.presets_path.display());
+            self.config_changed = true;
+        }
+        else
+        {
+            self.legacy_positions = config.positions.clone();
+        }
+    }
+
+    fn save_config(&self, _game: &mut Box<dyn Game>, config: &mut Config)
+    {
+        config.positions = self.legacy_positions.clone();
+    }
+
+    fn take_config_changed(&mut self) -> bool
+    {
+        return std::mem::take(&mut self.config_changed);
+    }
+}
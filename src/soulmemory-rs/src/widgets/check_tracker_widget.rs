@@ -0,0 +1,114 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs;
+use std::path::Path;
+use chrono::Local;
+use imgui::{ProgressBar, TreeNodeFlags, Ui};
+use crate::games::*;
+use crate::games::traits::buffered_event_flags::EventFlag;
+use crate::trackers::checks::CheckTracker;
+use crate::util::{game_key, DATA_DIRECTORY};
+use crate::widgets::widget::Widget;
+
+pub struct CheckTrackerWidget
+{
+    tracker: CheckTracker,
+    game: String,
+    export_message: String,
+}
+
+impl CheckTrackerWidget
+{
+    pub fn new(process_name: &str) -> Self
+    {
+        CheckTrackerWidget
+        {
+            tracker: CheckTracker::load(process_name),
+            game: game_key(process_name),
+            export_message: String::new(),
+        }
+    }
+
+    ///Write the timeline as csv and json to the checks directory, returns the path of the csv
+    fn save_timeline(&self) -> Result<String, String>
+    {
+        let directory = Path::new(DATA_DIRECTORY).join("checks");
+        fs::create_dir_all(&directory).map_err(|e| e.to_string())?;
+
+        let base = directory.join(format!("{}_{}", self.game, Local::now().format("%Y%m%d-%H%M%S")));
+        let csv_path = base.with_extension("csv");
+        fs::write(&csv_path, self.tracker.timeline_csv()).map_err(|e| e.to_string())?;
+        fs::write(base.with_extension("json"), self.tracker.timeline_json()).map_err(|e| e.to_string())?;
+        return Ok(csv_path.display().to_string());
+    }
+}
+
+impl Widget for CheckTrackerWidget
+{
+    fn render(&mut self, _game: &mut Box<dyn Game>, ui: &Ui)
+    {
+        if self.tracker.is_empty()
+        {
+            return;
+        }
+
+        if ui.collapsing_header("checks", TreeNodeFlags::FRAMED)
+        {
+            if ui.button("new run")
+            {
+                self.tracker.reset();
+            }
+            ui.same_line();
+            if ui.button("copy timeline")
+            {
+                ui.set_clipboard_text(self.tracker.timeline_csv());
+            }
+            ui.same_line();
+            if ui.button("save timeline")
+            {
+                self.export_message = match self.save_timeline()
+                {
+                    Ok(path) => format!("saved to {}", path),
+                    Err(e) => format!("failed to save timeline: {}", e),
+                };
+            }
+            ui.text(&self.export_message);
+
+            for group in self.tracker.progress()
+            {
+                ProgressBar::new(group.collected as f32 / group.total as f32)
+                    .overlay_text(format!("{} {}/{}", group.group, group.collected, group.total))
+                    .build(ui);
+            }
+
+            ui.child_window("checks_timeline_scrollable")
+                .size([ui.content_region_avail()[0], 200.0f32])
+                .build(||
+            {
+                for check in self.tracker.timeline().iter().rev()
+                {
+                    ui.text(format!("{} - {} ({})", check.collected_at.format("%H:%M:%S"), check.name, check.group));
+                }
+            });
+        }
+    }
+
+    fn on_event_flags(&mut self, event_flags: &[EventFlag])
+    {
+        self.tracker.on_event_flags(event_flags);
+    }
+}
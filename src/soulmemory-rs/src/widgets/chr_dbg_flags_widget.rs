@@ -14,43 +14,31 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::ops::DerefMut;
 use imgui::{TreeNodeFlags, Ui};
-use crate::games::{GetSetChrDbgFlags};
 use crate::games::*;
 use crate::widgets::widget::Widget;
 
-pub struct ChrDbgFlagsWidget
-{
-    flags: Vec<(u32, String, bool)>,
-    init: bool,
-}
+pub struct ChrDbgFlagsWidget {}
 
 impl ChrDbgFlagsWidget
 {
-    pub fn new() -> Self{ ChrDbgFlagsWidget { flags: Vec::new(), init: false} }
+    pub fn new() -> Self{ ChrDbgFlagsWidget {} }
 }
 
 impl Widget for ChrDbgFlagsWidget
 {
     fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui)
     {
-        if let Some(sekiro) = GameExt::get_game_mut::<Sekiro>(game.deref_mut())
+        if let Some(chr_dbg_flags) = game.chr_dbg_flags()
         {
-            if !self.init
-            {
-                self.flags = sekiro.get_flags();
-                self.init = true;
-            }
-
             if ui.collapsing_header("chr dbg", TreeNodeFlags::FRAMED)
             {
-                for f in self.flags.iter_mut()
+                //Re-read every frame, the game or another tool can change the flags as well
+                for mut f in chr_dbg_flags.get_flags()
                 {
-
                     if ui.checkbox(&f.1, &mut f.2)
                     {
-                        sekiro.set_flag(f.0, f.2);
+                        chr_dbg_flags.set_flag(f.0, f.2);
                     }
                 }
             }
@@ -0,0 +1,121 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use imgui::{TableFlags, TreeNodeFlags, Ui};
+use soulmemory_common::version::VersionCheck;
+use crate::games::*;
+use crate::widgets::widget::Widget;
+
+pub struct DiagnosticsWidget
+{
+    version_check: VersionCheck,
+}
+
+impl DiagnosticsWidget
+{
+    pub fn new(version_check: VersionCheck) -> Self { DiagnosticsWidget{ version_check } }
+}
+
+impl Widget for DiagnosticsWidget
+{
+    fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui)
+    {
+        //Shown even when the panel is closed, an unsupported version explains most other problems
+        for warning in &self.version_check.warnings
+        {
+            ui.text_colored([1.0f32, 0.85f32, 0.0f32, 1.0f32], warning);
+        }
+
+        if ui.collapsing_header("diagnostics", TreeNodeFlags::FRAMED)
+        {
+            match self.version_check.version
+            {
+                Some(version) => ui.text(format!("game version: {}", version)),
+                None => ui.text("game version: unknown"),
+            }
+            if game.supported_versions().is_empty()
+            {
+                ui.text("supported versions: not declared");
+            }
+            for support in game.supported_versions()
+            {
+                ui.text(format!("supported: {} - {}", support.min_version, support.max_version));
+            }
+
+            //Only games that resolve from a pattern table have a report
+            let report = match game.resolution_report()
+            {
+                Some(report) => report,
+                None => return,
+            };
+
+            ui.separator();
+            let unavailable = report.unavailable();
+            if unavailable.is_empty()
+            {
+                ui.text("every capability is available");
+            }
+            else
+            {
+                ui.text_colored([1.0f32, 0.3f32, 0.3f32, 1.0f32], format!("unavailable: {}", unavailable.join(", ")));
+            }
+
+            if ui.button("copy report")
+            {
+                let version = self.version_check.version.map(|version| version.to_string()).unwrap_or_else(|| String::from("unknown"));
+                ui.set_clipboard_text(format!("game version: {}\n{}", version, report.to_text()));
+            }
+            if ui.is_item_hovered()
+            {
+                ui.tooltip_text("Copy the report to the clipboard, to paste into a bug report.");
+            }
+
+            if let Some(_table_token) = ui.begin_table_with_flags("symbols", 4, TableFlags::RESIZABLE)
+            {
+                ui.table_setup_column("symbol");
+                ui.table_setup_column("address");
+                ui.table_setup_column("matches");
+                ui.table_setup_column("pattern");
+                ui.table_headers_row();
+
+                for symbol in report.symbols.iter()
+                {
+                    ui.table_next_column();
+                    ui.text(&symbol.name);
+
+                    ui.table_next_column();
+                    match symbol.address
+                    {
+                        Some(address) => ui.text(format!("0x{:x}", address)),
+                        None => ui.text_colored([1.0f32, 0.3f32, 0.3f32, 1.0f32], "missing"),
+                    }
+
+                    ui.table_next_column();
+                    ui.text(format!("{}", symbol.match_count));
+
+                    ui.table_next_column();
+                    ui.text(symbol.patterns.join(" | "));
+                }
+            }
+
+            for failure in report.failures.iter()
+            {
+                ui.text(format!("{} ({}): {}", failure.capability, failure.name, failure.error));
+            }
+        }
+    }
+}
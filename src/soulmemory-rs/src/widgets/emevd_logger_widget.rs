@@ -14,18 +14,25 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use imgui::{TreeNodeFlags, Ui};
 use crate::darkscript3::sekiro_emedf::Emedf;
 use crate::games::*;
 use crate::games::traits::buffered_emevd_logger::BufferedEmevdCall;
+use crate::util::config::Config;
 use crate::widgets::widget::Widget;
 
 pub struct EmevdLoggerWidget
 {
     pub log: String,
     pub group_filer: Vec<bool>,
+    //Main class index of each entry in group_filer
+    pub group_indices: Vec<u64>,
+    //Filters from the config, applied once the emedf is known
+    pub saved_group_filters: BTreeMap<u64, bool>,
     pub auto_scroll: bool,
     pub init: bool,
+    config_changed: bool,
 }
 
 impl EmevdLoggerWidget
@@ -55,8 +62,11 @@ impl EmevdLoggerWidget
         {
             log: String::new(),
             group_filer: Vec::new(),
+            group_indices: Vec::new(),
+            saved_group_filters: BTreeMap::new(),
             auto_scroll: true,
             init: false,
+            config_changed: false,
         }
     }
 
@@ -68,7 +78,8 @@ impl EmevdLoggerWidget
         }
 
         //init group filters
-        self.group_filer = vec![true; emedf.main_classes.len()];
+        self.group_indices = emedf.main_classes.iter().map(|c| c.index).collect();
+        self.group_filer = self.group_indices.iter().map(|i| *self.saved_group_filters.get(i).unwrap_or(&true)).collect();
 
 
         self.init = true;
@@ -77,6 +88,25 @@ impl EmevdLoggerWidget
 
 impl Widget for EmevdLoggerWidget
 {
+    fn load_config(&mut self, _game: &mut Box<dyn Game>, config: &Config)
+    {
+        self.saved_group_filters = config.emevd_group_filters.clone();
+    }
+
+    fn save_config(&self, _game: &mut Box<dyn Game>, config: &mut Config)
+    {
+        config.emevd_group_filters = self.saved_group_filters.clone();
+        for (index, show) in self.group_indices.iter().zip(self.group_filer.iter())
+        {
+            config.emevd_group_filters.insert(*index, *show);
+        }
+    }
+
+    fn take_config_changed(&mut self) -> bool
+    {
+        return std::mem::take(&mut self.config_changed);
+    }
+
     fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui)
     {
         if let Some(buffered_emevd_logger) = game.buffered_emevd_logger()
@@ -96,7 +126,7 @@ impl Widget for EmevdLoggerWidget
                 {
                     for i in 0..emedf.main_classes.len()
                     {
-                        ui.checkbox(format!("{} - {}", emedf.main_classes[i].index, emedf.main_classes[i].name), &mut self.group_filer[i]);
+                        self.config_changed |= ui.checkbox(format!("{} - {}", emedf.main_classes[i].index, emedf.main_classes[i].name), &mut self.group_filer[i]);
                     }
                 }
 
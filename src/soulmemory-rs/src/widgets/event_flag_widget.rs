@@ -14,15 +14,23 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::Arc;
 use imgui::{TableFlags, TreeNodeFlags, Ui};
-use crate::games::traits::buffered_event_flags::{BufferedEventFlags, EventFlag, EventFlagValue};
+use log::info;
+use crate::event_flags::names::EventFlagNames;
+use crate::event_flags::snapshot::{EventFlagChange, EventFlagSnapshot};
+use crate::games::traits::buffered_event_flags::{EventFlag, EventFlagValue};
 use crate::games::*;
-use crate::widgets::widget::Widget;
+use crate::util::config::{Config, WatchedEventFlag};
+use crate::util::server::EventFlagRange;
+use crate::widgets::widget::{clipboard_import_export, Widget};
 
 const EVENT_FLAG_SCROLL_REGION_HEIGHT: f32 = 400.0f32;
+const NAME_SEARCH_MAX_RESULTS: usize = 200;
 
 pub struct EventFlagWidget
 {
+    names: Arc<EventFlagNames>,
     copy_fade: f32,
     selected_log_mode_index: u32,
     unique_event_flags: Vec<EventFlag>,
@@ -32,15 +40,27 @@ pub struct EventFlagWidget
     blacklisted_flags: Vec<u32>,
     blacklist_flag_input: String,
 
-    watched_flags: Vec<u32>,
+    watched_flags: Vec<WatchedEventFlag>,
     watch_flag_input: String,
+    watch_bit_count_input: i32,
+
+    name_search_input: String,
+    //Blacklist or watched flags changed since the config was last collected
+    config_changed: bool,
+
+    snapshot_start_input: String,
+    snapshot_end_input: String,
+    snapshot: Option<EventFlagSnapshot>,
+    snapshot_changes: Vec<EventFlagChange>,
+    snapshot_error: Option<String>,
 }
 
 impl EventFlagWidget
 {
-    pub fn new() -> Self{
+    pub fn new(names: Arc<EventFlagNames>) -> Self{
         EventFlagWidget
         {
+            names,
             copy_fade: 0.0f32,
             selected_log_mode_index: 1, //Select unqiue flags by default
             unique_event_flags: Vec::new(),
@@ -51,6 +71,16 @@ impl EventFlagWidget
 
             watched_flags: Vec::new(),
             watch_flag_input: String::new(),
+            watch_bit_count_input: 0,
+
+            name_search_input: String::new(),
+            config_changed: false,
+
+            snapshot_start_input: String::new(),
+            snapshot_end_input: String::new(),
+            snapshot: None,
+            snapshot_changes: Vec::new(),
+            snapshot_error: None,
         }
     }
 
@@ -67,11 +97,12 @@ impl EventFlagWidget
                 .size(ui.content_region_avail())
                 .build(||
             {
-                if let Some(_table_token) = ui.begin_table_with_flags("event flags", 3, TableFlags::HIDEABLE | TableFlags::RESIZABLE)
+                if let Some(_table_token) = ui.begin_table_with_flags("event flags", 4, TableFlags::HIDEABLE | TableFlags::RESIZABLE)
                 {
                     ui.table_setup_column("time");
                     ui.table_setup_column("flag");
                     ui.table_setup_column("value");
+                    ui.table_setup_column("name");
                     ui.table_headers_row();
 
                     let mut index = 0;
@@ -111,6 +142,17 @@ impl EventFlagWidget
                             EventFlagValue::Quantity(quantity) => ui.text_colored([1.0f32, 1.0f32, 1.0f32, 1.0f32], quantity.to_string()),
                         }
 
+                        //name
+                        ui.table_next_column();
+                        if let Some(name) = self.names.lookup(f.flag)
+                        {
+                            ui.text(&name.name);
+                            if ui.is_item_hovered()
+                            {
+                                ui.tooltip_text(format!("{}\n{}", name.category, name.area));
+                            }
+                        }
+
                         index += 1;
                     }
                 }
@@ -130,7 +172,12 @@ impl EventFlagWidget
     {
         if let Some(blacklist) = ui.tab_item("blacklist")
         {
-            Self::flag_input_to_vec(ui, &mut self.blacklist_flag_input, &mut self.blacklisted_flags);
+            self.config_changed |= clipboard_import_export(ui, &mut self.blacklisted_flags);
+            if let Some(flag) = Self::flag_input(ui, &mut self.blacklist_flag_input)
+            {
+                self.blacklisted_flags.push(flag);
+                self.config_changed = true;
+            }
 
             ui.child_window("blacklist_event_flags_scrollable")
                 .size([ui.content_region_avail()[0], EVENT_FLAG_SCROLL_REGION_HEIGHT])
@@ -149,11 +196,13 @@ impl EventFlagWidget
                         delete_flag_index = Some(i);
                     }
                     id.end();
+                    self.name_text(ui, self.blacklisted_flags[i]);
                 }
 
                 if let Some(index) = delete_flag_index
                 {
                     self.blacklisted_flags.remove(index);
+                    self.config_changed = true;
                 }
             });
 
@@ -166,7 +215,22 @@ impl EventFlagWidget
         //Watch event flags
         if let Some(watch) = ui.tab_item("watch")
         {
-            Self::flag_input_to_vec(ui, &mut self.watch_flag_input, &mut self.watched_flags);
+            self.config_changed |= clipboard_import_export(ui, &mut self.watched_flags);
+
+            let width_token = ui.push_item_width(100.0f32);
+            ui.input_int("bit count", &mut self.watch_bit_count_input).build();
+            width_token.end();
+            if ui.is_item_hovered()
+            {
+                ui.tooltip_text("Leave at 0 for regular flags. Set the width of the flag in bits to watch a quantity flag.");
+            }
+
+            if let Some(flag) = Self::flag_input(ui, &mut self.watch_flag_input)
+            {
+                let bit_count = self.watch_bit_count_input.clamp(0, 32) as u8;
+                self.watched_flags.push(WatchedEventFlag { flag, bit_count: if bit_count > 1 { Some(bit_count) } else { None } });
+                self.config_changed = true;
+            }
 
             ui.child_window("watch_event_flags_scrollable")
                 .size([ui.content_region_avail()[0], EVENT_FLAG_SCROLL_REGION_HEIGHT])
@@ -175,21 +239,46 @@ impl EventFlagWidget
                 let mut delete_flag_index = None;
                 for i in 0..self.watched_flags.len()
                 {
-                    ui.text(format!("{: >10}", self.watched_flags[i].to_string()));
+                    let watched = &self.watched_flags[i];
+                    ui.text(format!("{: >10}", watched.flag.to_string()));
                     ui.same_line();
 
                     if let Some(buffered_event_flags) = game.event_flags()
                     {
-                        let flag_val = buffered_event_flags.get_event_flag_state(self.watched_flags[i]);
-                        ui.text(format!("{: >5}", flag_val));
+                        let id = ui.push_id(i.to_string());
+                        match watched.bit_count
+                        {
+                            Some(bit_count) =>
+                            {
+                                let flag_val = match buffered_event_flags.get_event_flag_quantity(watched.flag, bit_count)
+                                {
+                                    Some(quantity) => quantity.to_string(),
+                                    None => String::from("unsupported"),
+                                };
+                                ui.text(format!("{: >5}", flag_val));
+                            }
+                            None =>
+                            {
+                                let flag_val = buffered_event_flags.get_event_flag_state(watched.flag);
+                                ui.text(format!("{: >5}", flag_val));
+                                ui.same_line();
+                                if ui.button("toggle")
+                                {
+                                    if let Err(e) = buffered_event_flags.set_event_flag_state(watched.flag, !flag_val)
+                                    {
+                                        info!("failed to set event flag {}: {}", watched.flag, e);
+                                    }
+                                }
+                            }
+                        }
                         ui.same_line();
 
-                        let id = ui.push_id(i.to_string());
                         if ui.button("delete")
                         {
                             delete_flag_index = Some(i);
                         }
                         id.end();
+                        self.name_text(ui, watched.flag);
                     }
 
                 }
@@ -197,6 +286,7 @@ impl EventFlagWidget
                 if let Some(index) = delete_flag_index
                 {
                     self.watched_flags.remove(index);
+                    self.config_changed = true;
                 }
             });
 
@@ -204,7 +294,184 @@ impl EventFlagWidget
         }
     }
 
-    fn flag_input_to_vec(ui: &Ui, input_string: &mut String, vec: &mut Vec<u32>)
+    fn tab_names(&mut self, ui: &Ui)
+    {
+        if let Some(names) = ui.tab_item("names")
+        {
+            if self.names.is_empty()
+            {
+                ui.text("No event flag names available for this game.");
+                names.end();
+                return;
+            }
+
+            ui.input_text("search", &mut self.name_search_input).build();
+
+            ui.child_window("names_event_flags_scrollable")
+                .size([ui.content_region_avail()[0], EVENT_FLAG_SCROLL_REGION_HEIGHT])
+                .build(||
+            {
+                if self.name_search_input.is_empty()
+                {
+                    return;
+                }
+
+                let names = Arc::clone(&self.names);
+                let results = names.search(&self.name_search_input);
+                if results.len() > NAME_SEARCH_MAX_RESULTS
+                {
+                    ui.text(format!("showing {} of {} results", NAME_SEARCH_MAX_RESULTS, results.len()));
+                }
+
+                for (i, named) in results.into_iter().take(NAME_SEARCH_MAX_RESULTS).enumerate()
+                {
+                    let id = ui.push_id(i.to_string());
+                    match named.end
+                    {
+                        Some(end) => ui.text(format!("{}-{}", named.flag, end)),
+                        None =>
+                        {
+                            ui.text(format!("{: >10}", named.flag));
+                            ui.same_line();
+                            if ui.button("watch")
+                            {
+                                self.watched_flags.push(WatchedEventFlag { flag: named.flag, bit_count: None });
+                                self.config_changed = true;
+                            }
+                            ui.same_line();
+                            if ui.button("blacklist")
+                            {
+                                self.blacklisted_flags.push(named.flag);
+                                self.config_changed = true;
+                            }
+                        }
+                    }
+                    id.end();
+                    ui.same_line();
+                    ui.text(&named.name);
+                    if ui.is_item_hovered()
+                    {
+                        ui.tooltip_text(format!("{}\n{}", named.category, named.area));
+                    }
+                }
+            });
+
+            names.end();
+        }
+    }
+
+    fn tab_snapshot(&mut self, ui: &Ui, game: &mut Box<dyn Game>)
+    {
+        if let Some(snapshot) = ui.tab_item("snapshot")
+        {
+            let width_token = ui.push_item_width(100.0f32);
+            ui.input_text("start", &mut self.snapshot_start_input).build();
+            ui.same_line();
+            ui.input_text("end", &mut self.snapshot_end_input).build();
+            width_token.end();
+
+            let start = self.snapshot_start_input.parse::<u32>().ok();
+            ui.same_line();
+            ui.disabled(start.is_none(), ||
+            {
+                if ui.button("block")
+                {
+                    let block = EventFlagRange::block(start.unwrap());
+                    self.snapshot_start_input = block.start.to_string();
+                    self.snapshot_end_input = block.end.to_string();
+                }
+            });
+            if ui.is_item_hovered()
+            {
+                ui.tooltip_text("Expand start to the whole flag block it is in.");
+            }
+
+            let range = match (start, self.snapshot_end_input.parse::<u32>())
+            {
+                (Some(start), Ok(end)) => Some(EventFlagRange { start, end }),
+                _ => None,
+            };
+
+            ui.disabled(range.is_none(), ||
+            {
+                if ui.button("take snapshot")
+                {
+                    if let Some(event_flags) = game.event_flags()
+                    {
+                        match EventFlagSnapshot::take(&[range.unwrap()], |f| event_flags.get_event_flag_state(f))
+                        {
+                            Ok(taken) =>
+                            {
+                                self.snapshot = Some(taken);
+                                self.snapshot_error = None;
+                            }
+                            Err(e) => self.snapshot_error = Some(e),
+                        }
+                        self.snapshot_changes.clear();
+                    }
+                }
+            });
+
+            if let Some(error) = &self.snapshot_error
+            {
+                ui.text_colored([1.0f32, 0.0f32, 0.0f32, 1.0f32], error);
+            }
+
+            if let Some(baseline) = &self.snapshot
+            {
+                ui.text(format!("{} flags at {}", baseline.len(), baseline.time.format("%H:%M:%S")));
+                ui.same_line();
+                if ui.button("diff")
+                {
+                    if let Some(event_flags) = game.event_flags()
+                    {
+                        if let Ok(current) = EventFlagSnapshot::take(&baseline.ranges, |f| event_flags.get_event_flag_state(f))
+                        {
+                            self.snapshot_changes = baseline.diff(&current);
+                        }
+                    }
+                }
+                if ui.is_item_hovered()
+                {
+                    ui.tooltip_text("List every flag that changed since the snapshot was taken.");
+                }
+            }
+
+            ui.child_window("snapshot_event_flags_scrollable")
+                .size([ui.content_region_avail()[0], EVENT_FLAG_SCROLL_REGION_HEIGHT])
+                .build(||
+            {
+                for (i, change) in self.snapshot_changes.iter().enumerate()
+                {
+                    ui.text(format!("{: >10} {: >5} -> {: <5}", change.flag, change.before, change.after));
+                    ui.same_line();
+
+                    let id = ui.push_id(i.to_string());
+                    if ui.button("watch")
+                    {
+                        self.watched_flags.push(WatchedEventFlag { flag: change.flag, bit_count: None });
+                        self.config_changed = true;
+                    }
+                    id.end();
+                    self.name_text(ui, change.flag);
+                }
+            });
+
+            snapshot.end();
+        }
+    }
+
+    fn name_text(&self, ui: &Ui, flag: u32)
+    {
+        if let Some(name) = self.names.name(flag)
+        {
+            ui.same_line();
+            ui.text(name);
+        }
+    }
+
+    ///Draws the flag input with an add button, returns the flag when add is clicked
+    fn flag_input(ui: &Ui, input_string: &mut String) -> Option<u32>
     {
         ui.input_text("flag", input_string).build();
         ui.same_line();
@@ -220,20 +487,21 @@ impl EventFlagWidget
             disabled = true;
         }
 
+        let mut added = None;
         ui.disabled(disabled, ||
         {
             if ui.button("add")
             {
-                vec.push(event_flag);
+                added = Some(event_flag);
                 input_string.clear();
             }
         });
+        return added;
     }
 
-    fn buffer_flags(&mut self, event_flags: &mut Box<&mut dyn BufferedEventFlags>)
+    fn buffer_flags(&mut self, new_flags: &[EventFlag])
     {
-        let new_flags = event_flags.get_buffered_flags();
-        for f in new_flags
+        for f in new_flags.iter().copied()
         {
             match self.selected_log_mode_index
             {
@@ -276,10 +544,8 @@ impl Widget for EventFlagWidget
 {
     fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui)
     {
-        if let Some(mut event_flags) = game.event_flags()
+        if game.event_flags().is_some()
         {
-            self.buffer_flags(&mut event_flags);
-
             if ui.collapsing_header("event flags", TreeNodeFlags::FRAMED)
             {
                 ui.text("Log mode:");
@@ -312,9 +578,33 @@ impl Widget for EventFlagWidget
                     self.tab_event_flag_log(ui, game);
                     self.tab_blacklist(ui, game);
                     self.tab_watch_event_flags(ui, game);
+                    self.tab_names(ui);
+                    self.tab_snapshot(ui, game);
                     tab_bar.end();
                 };
             }
         }
     }
+
+    fn on_event_flags(&mut self, event_flags: &[EventFlag])
+    {
+        self.buffer_flags(event_flags);
+    }
+
+    fn load_config(&mut self, _game: &mut Box<dyn Game>, config: &Config)
+    {
+        self.blacklisted_flags = config.event_flag_blacklist.clone();
+        self.watched_flags = config.watched_event_flags.clone();
+    }
+
+    fn save_config(&self, _game: &mut Box<dyn Game>, config: &mut Config)
+    {
+        config.event_flag_blacklist = self.blacklisted_flags.clone();
+        config.watched_event_flags = self.watched_flags.clone();
+    }
+
+    fn take_config_changed(&mut self) -> bool
+    {
+        return std::mem::take(&mut self.config_changed);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::ops::Deref;
+use chrono::{DateTime, Local};
+use imgui::{TableFlags, TreeNodeFlags, Ui};
+use crate::games::*;
+use crate::widgets::widget::Widget;
+
+pub struct GreatRuneWidget
+{
+
+}
+
+impl GreatRuneWidget
+{
+    pub fn new() -> Self { GreatRuneWidget{} }
+
+    fn status_text(ui: &Ui, state: bool, at: &Option<DateTime<Local>>)
+    {
+        match (state, at)
+        {
+            (true, Some(time)) => ui.text_colored([0.0f32, 1.0f32, 0.0f32, 1.0f32], time.format("%H:%M:%S").to_string()),
+            (true, None) => ui.text_colored([0.0f32, 1.0f32, 0.0f32, 1.0f32], "yes"),
+            (false, _) => ui.text_colored([0.5f32, 0.5f32, 0.5f32, 1.0f32], "no"),
+        }
+    }
+}
+
+impl Widget for GreatRuneWidget
+{
+    fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui)
+    {
+        if let Some(elden_ring) = GameExt::get_game_ref::<EldenRing>(game.deref().deref())
+        {
+            if ui.collapsing_header("great runes", TreeNodeFlags::FRAMED)
+            {
+                let runes = elden_ring.great_runes();
+                let obtained = runes.iter().filter(|r| r.obtained).count();
+                ui.text(format!("obtained: {}/{}", obtained, runes.len()));
+                if ui.is_item_hovered()
+                {
+                    ui.tooltip_text("Times are shown for runes obtained while soulmemory-rs was running.");
+                }
+
+                if let Some(_table_token) = ui.begin_table_with_flags("great runes", 2, TableFlags::RESIZABLE)
+                {
+                    ui.table_setup_column("rune");
+                    ui.table_setup_column("obtained");
+                    ui.table_headers_row();
+
+                    for rune in runes.iter()
+                    {
+                        ui.table_next_column();
+                        ui.text(&rune.name);
+
+                        ui.table_next_column();
+                        Self::status_text(ui, rune.obtained, &rune.obtained_at);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Instant;
+use imgui::{TreeNodeFlags, Ui};
+use crate::games::*;
+use crate::util::format_milliseconds;
+use crate::widgets::widget::Widget;
+
+pub struct InGameTimeWidget
+{
+    //Real time and IGT when the comparison was last reset
+    reference: Option<(Instant, u32)>,
+}
+
+impl InGameTimeWidget
+{
+    pub fn new() -> Self
+    {
+        InGameTimeWidget { reference: None }
+    }
+}
+
+impl Widget for InGameTimeWidget
+{
+    fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui)
+    {
+        let igt = match game.in_game_time()
+        {
+            Some(in_game_time) => in_game_time.get_in_game_time_milliseconds(),
+            None => return,
+        };
+
+        if ui.collapsing_header("in game time", TreeNodeFlags::FRAMED)
+        {
+            let (instant, reference_igt) = *self.reference.get_or_insert((Instant::now(), igt));
+            let real_ms = instant.elapsed().as_millis() as u64;
+            let igt_ms = igt.saturating_sub(reference_igt) as u64;
+
+            ui.text(format!("igt      : {}", format_milliseconds(igt as u64)));
+            ui.text(format!("real time: {}", format_milliseconds(real_ms)));
+            ui.text(format!("igt since reset: {}", format_milliseconds(igt_ms)));
+            //Loads, menus and quitouts that the IGT doesn't count
+            ui.text(format!("not counted    : {}", format_milliseconds(real_ms.saturating_sub(igt_ms))));
+
+            if ui.button("reset")
+            {
+                self.reference = Some((Instant::now(), igt));
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::{Arc, Mutex};
+use imgui::{TreeNodeFlags, Ui};
+use crate::games::*;
+use crate::splits::livesplit::LiveSplitOutput;
+use crate::util::config::{Config, LiveSplitSettings};
+use crate::widgets::widget::Widget;
+
+pub struct LiveSplitWidget
+{
+    output: Arc<Mutex<LiveSplitOutput>>,
+    settings: LiveSplitSettings,
+    address_input: String,
+    config_changed: bool,
+}
+
+impl LiveSplitWidget
+{
+    pub fn new(output: Arc<Mutex<LiveSplitOutput>>) -> Self
+    {
+        let settings = LiveSplitSettings::default();
+        LiveSplitWidget { output, address_input: settings.address.clone(), settings, config_changed: false }
+    }
+
+    fn apply(&self)
+    {
+        self.output.lock().unwrap().configure(self.settings.enabled.then_some(self.settings.address.as_str()));
+    }
+}
+
+impl Widget for LiveSplitWidget
+{
+    fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui)
+    {
+        if ui.collapsing_header("livesplit", TreeNodeFlags::FRAMED)
+        {
+            if ui.checkbox("send to livesplit server", &mut self.settings.enabled)
+            {
+                self.apply();
+                self.config_changed = true;
+            }
+
+            let _a = ui.push_item_width(150.0f32);
+            ui.input_text("##livesplit-address", &mut self.address_input).build();
+            ui.same_line();
+            if ui.button("apply")
+            {
+                self.settings.address = self.address_input.trim().to_string();
+                self.apply();
+                self.config_changed = true;
+            }
+
+            let output = self.output.lock().unwrap();
+            if output.is_enabled()
+            {
+                ui.text(if output.is_connected() { "connected" } else { "not connected, retrying" });
+            }
+            let game_time = match (game.in_game_time().is_some(), game.loading_state().is_some())
+            {
+                (true, _) => "game time: in game time",
+                (false, true) => "game time: real time without loads",
+                (false, false) => "game time: not available for this game",
+            };
+            ui.text(game_time);
+        }
+    }
+
+    fn load_config(&mut self, _game: &mut Box<dyn Game>, config: &Config)
+    {
+        self.settings = config.livesplit.clone();
+        self.address_input = self.settings.address.clone();
+        self.apply();
+    }
+
+    fn save_config(&self, _game: &mut Box<dyn Game>, config: &mut Config)
+    {
+        config.livesplit = self.settings.clone();
+    }
+
+    fn take_config_changed(&mut self) -> bool
+    {
+        return std::mem::take(&mut self.config_changed);
+    }
+}
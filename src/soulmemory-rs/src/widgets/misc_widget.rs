@@ -16,24 +16,65 @@
 
 use crate::widgets::widget::Widget;
 use imgui::{TreeNodeFlags, Ui};
+use log::warn;
 use windows::Win32::UI::Input::KeyboardAndMouse::VK_OEM_5;
 use crate::games::*;
-use std::ops::DerefMut;
+use crate::util::config::Config;
 
 pub struct MiscWidget
 {
-
+    quitout_hotkey: u32,
+    hotkey_was_down: bool,
+    //Waiting for the next key press to become the quitout hotkey
+    capturing_hotkey: bool,
+    quitout_error: String,
+    config_changed: bool,
 }
 
 impl MiscWidget
 {
-    pub fn new() -> Self { MiscWidget{}}
+    pub fn new() -> Self
+    {
+        MiscWidget
+        {
+            quitout_hotkey: VK_OEM_5.0 as u32,
+            hotkey_was_down: false,
+            capturing_hotkey: false,
+            quitout_error: String::new(),
+            config_changed: false,
+        }
+    }
+
+    fn key_name(vk: u32) -> String
+    {
+        return match vk
+        {
+            0x30..=0x39 | 0x41..=0x5a => char::from_u32(vk).map(String::from).unwrap_or_default(),
+            0x60..=0x69 => format!("numpad {}", vk - 0x60),
+            0x70..=0x87 => format!("F{}", vk - 0x6f),
+            0xdc => String::from("\\"),
+            _ => format!("key 0x{:02x}", vk),
+        };
+    }
+
+    fn is_down(ui: &Ui, vk: u32) -> bool
+    {
+        return ui.io().keys_down.get(vk as usize).copied().unwrap_or(false);
+    }
 
-    fn render_misc_sekiro(&mut self, sekiro: &mut Sekiro, ui: &Ui)
+    fn quitout(&mut self, game: &mut Box<dyn Game>)
     {
-        if ui.button("quitout") || ui.io().keys_down[VK_OEM_5.0 as usize]
+        if let Some(quitout) = game.quitout()
         {
-            sekiro.request_quitout();
+            self.quitout_error = match quitout.request_quitout()
+            {
+                Ok(()) => String::new(),
+                Err(e) =>
+                {
+                    warn!("quitout failed: {}", e);
+                    e
+                }
+            };
         }
     }
 }
@@ -42,12 +83,70 @@ impl Widget for MiscWidget
 {
     fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui)
     {
-        if let Some(sekiro) = GameExt::get_game_mut::<Sekiro>(game.deref_mut())
+        if game.quitout().is_none()
+        {
+            return;
+        }
+
+        if self.capturing_hotkey
         {
-            if ui.collapsing_header("misc", TreeNodeFlags::FRAMED)
+            //Skip the mouse buttons, the capture starts with a click
+            if let Some(vk) = (0x08..0xff).find(|vk| Self::is_down(ui, *vk))
             {
-                self.render_misc_sekiro(sekiro, ui);
+                self.quitout_hotkey = vk;
+                self.capturing_hotkey = false;
+                self.config_changed = true;
+                self.hotkey_was_down = true;
+            }
+        }
+        else
+        {
+            //Only on the key press, holding the key should not quit out again after loading
+            let down = Self::is_down(ui, self.quitout_hotkey);
+            if down && !self.hotkey_was_down
+            {
+                self.quitout(game);
+            }
+            self.hotkey_was_down = down;
+        }
+
+        if ui.collapsing_header("misc", TreeNodeFlags::FRAMED)
+        {
+            if ui.button("quitout")
+            {
+                self.quitout(game);
+            }
+            ui.same_line();
+            let label = if self.capturing_hotkey { String::from("press a key...") } else { format!("hotkey: {}", Self::key_name(self.quitout_hotkey)) };
+            if ui.button(label)
+            {
+                self.capturing_hotkey = true;
+            }
+            if !self.quitout_error.is_empty()
+            {
+                ui.text(&self.quitout_error);
             }
         }
     }
-}
\ No newline at end of file
+
+    fn load_config(&mut self, _game: &mut Box<dyn Game>, config: &Config)
+    {
+        if let Some(hotkey) = config.quitout_hotkey
+        {
+            self.quitout_hotkey = hotkey;
+        }
+    }
+
+    fn save_config(&self, game: &mut Box<dyn Game>, config: &mut Config)
+    {
+        if game.quitout().is_some()
+        {
+            config.quitout_hotkey = Some(self.quitout_hotkey);
+        }
+    }
+
+    fn take_config_changed(&mut self) -> bool
+    {
+        return std::mem::take(&mut self.config_changed);
+    }
+}
@@ -20,4 +20,11 @@ pub(crate) mod ai_toggle_widget;
 pub(crate) mod basic_position_widget;
 pub(crate) mod chr_dbg_flags_widget;
 pub(crate) mod misc_widget;
-pub(crate) mod emevd_logger_widget;
\ No newline at end of file
+pub(crate) mod emevd_logger_widget;
+pub(crate) mod great_rune_widget;
+pub(crate) mod check_tracker_widget;
+pub(crate) mod splits_widget;
+pub(crate) mod livesplit_widget;
+pub(crate) mod in_game_time_widget;
+pub(crate) mod trace_widget;
+pub(crate) mod diagnostics_widget;
@@ -0,0 +1,89 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::{Arc, Mutex};
+use imgui::{TreeNodeFlags, Ui};
+use crate::games::*;
+use crate::splits::splitter::Splitter;
+use crate::util::format_milliseconds;
+use crate::widgets::widget::Widget;
+
+pub struct SplitsWidget
+{
+    splitter: Arc<Mutex<Splitter>>,
+}
+
+impl SplitsWidget
+{
+    pub fn new(splitter: Arc<Mutex<Splitter>>) -> Self
+    {
+        SplitsWidget { splitter }
+    }
+}
+
+impl Widget for SplitsWidget
+{
+    fn render(&mut self, _game: &mut Box<dyn Game>, ui: &Ui)
+    {
+        let mut splitter = self.splitter.lock().unwrap();
+        if splitter.splits().is_empty()
+        {
+            return;
+        }
+
+        if ui.collapsing_header("splits", TreeNodeFlags::FRAMED)
+        {
+            ui.text(format_milliseconds(splitter.elapsed_ms()));
+
+            if ui.button("start")
+            {
+                splitter.start();
+            }
+            ui.same_line();
+            if ui.button("split")
+            {
+                splitter.split();
+            }
+            ui.same_line();
+            if ui.button("skip")
+            {
+                splitter.skip();
+            }
+            ui.same_line();
+            if ui.button("undo")
+            {
+                splitter.undo();
+            }
+            ui.same_line();
+            if ui.button("reset")
+            {
+                splitter.reset();
+            }
+
+            let state = splitter.state();
+            for (i, name) in state.splits.iter().enumerate()
+            {
+                match state.times.get(i)
+                {
+                    Some(time) if time.skipped => ui.text_colored([0.5f32, 0.5f32, 0.5f32, 1.0f32], format!("{: <30} -", name)),
+                    Some(time) => ui.text(format!("{: <30} {}", name, format_milliseconds(time.elapsed_ms))),
+                    None if state.running && i == state.current => ui.text_colored([1.0f32, 0.85f32, 0.0f32, 1.0f32], format!("{: <30} ...", name)),
+                    None => ui.text(name),
+                }
+            }
+        }
+    }
+}
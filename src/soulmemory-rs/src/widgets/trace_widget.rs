@@ -0,0 +1,159 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use imgui::{TreeNodeFlags, Ui};
+use crate::games::*;
+use crate::positions::trace::{TraceRecorder, Track};
+use crate::util::format_milliseconds;
+use crate::widgets::widget::Widget;
+
+pub struct TraceWidget
+{
+    recorder: Arc<Mutex<TraceRecorder>>,
+    process_name: String,
+    //Tracks in the traces directory, refreshed when the header is opened or a track is saved
+    tracks: Vec<PathBuf>,
+    tracks_listed: bool,
+    message: String,
+}
+
+impl TraceWidget
+{
+    pub fn new(recorder: Arc<Mutex<TraceRecorder>>, process_name: &str) -> Self
+    {
+        TraceWidget
+        {
+            recorder,
+            process_name: process_name.to_string(),
+            tracks: Vec::new(),
+            tracks_listed: false,
+            message: String::new(),
+        }
+    }
+
+    fn list_tracks(&mut self)
+    {
+        self.tracks = fs::read_dir(Track::directory())
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "csv")).collect())
+            .unwrap_or_default();
+        self.tracks.sort();
+        self.tracks_listed = true;
+    }
+}
+
+impl Widget for TraceWidget
+{
+    fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui)
+    {
+        if game.player_position().is_none()
+        {
+            return;
+        }
+
+        if ui.collapsing_header("trace", TreeNodeFlags::FRAMED)
+        {
+            if !self.tracks_listed
+            {
+                self.list_tracks();
+            }
+
+            let mut recorder = self.recorder.lock().unwrap();
+            if recorder.is_recording()
+            {
+                if ui.button("stop")
+                {
+                    recorder.stop();
+                    let path = Track::new_path(&self.process_name);
+                    self.message = match recorder.track().save(&path)
+                    {
+                        Ok(()) => format!("saved {}", path.display()),
+                        Err(e) => format!("failed to save {}: {}", path.display(), e),
+                    };
+                    self.tracks_listed = false;
+                }
+            }
+            else if ui.button("record")
+            {
+                recorder.start(Instant::now());
+                self.message.clear();
+            }
+            ui.same_line();
+            ui.text(format!("{} points", recorder.track().points().len()));
+
+            if !self.message.is_empty()
+            {
+                ui.text(&self.message);
+            }
+
+            //Live comparison against the reference
+            match recorder.reference_name()
+            {
+                Some(name) =>
+                {
+                    ui.text(format!("reference: {}", name));
+                    ui.same_line();
+                    if ui.button("clear")
+                    {
+                        recorder.clear_reference();
+                    }
+                }
+                None => ui.text("reference: none"),
+            }
+            if let Some(deviation) = recorder.deviation()
+            {
+                ui.text(format!("distance: {:.2}", deviation.distance));
+                let delta = format_milliseconds(deviation.time_delta_ms.unsigned_abs());
+                match deviation.time_delta_ms
+                {
+                    d if d > 0 => ui.text_colored([1.0f32, 0.3f32, 0.3f32, 1.0f32], format!("behind: {}", delta)),
+                    _ => ui.text_colored([0.0f32, 1.0f32, 0.0f32, 1.0f32], format!("ahead: {}", delta)),
+                }
+            }
+
+            //Recorded tracks, any of them can be the reference
+            if ui.button("refresh list")
+            {
+                self.tracks_listed = false;
+            }
+            let mut load = None;
+            for (i, path) in self.tracks.iter().enumerate()
+            {
+                let _id = ui.push_id(i.to_string());
+                if ui.button("use as reference")
+                {
+                    load = Some(path.clone());
+                }
+                ui.same_line();
+                ui.text(path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default());
+            }
+
+            if let Some(path) = load
+            {
+                let name = path.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                match Track::load(&path)
+                {
+                    Ok(track) => recorder.set_reference(name, track),
+                    Err(e) => self.message = format!("failed to load {}: {}", path.display(), e),
+                }
+            }
+        }
+    }
+}
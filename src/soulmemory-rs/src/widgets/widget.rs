@@ -15,9 +15,66 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use imgui::Ui;
+use log::info;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use crate::games::Game;
+use crate::games::traits::buffered_event_flags::EventFlag;
+use crate::util::config::{export, import, Config};
 
 pub trait Widget
 {
     fn render(&mut self, game: &mut Box<dyn Game>, ui: &Ui);
-}
\ No newline at end of file
+
+    ///Called from the main loop with the event flags that have been buffered since the last refresh
+    fn on_event_flags(&mut self, _event_flags: &[EventFlag]) {}
+
+    ///Restore the settings this widget owns from the persisted config
+    fn load_config(&mut self, _game: &mut Box<dyn Game>, _config: &Config) {}
+
+    ///Write the settings this widget owns into the config, the app saves it when anything changed
+    fn save_config(&self, _game: &mut Box<dyn Game>, _config: &mut Config) {}
+
+    ///True when the settings this widget owns changed since the last call, the config is only collected from the widgets then
+    fn take_config_changed(&mut self) -> bool { false }
+}
+///Draws export and import buttons that copy a list to and from the clipboard as json. Imported entries are appended, duplicates are skipped.
+///Returns true when entries were imported.
+pub fn clipboard_import_export<T: Serialize + DeserializeOwned + PartialEq>(ui: &Ui, values: &mut Vec<T>) -> bool
+{
+    let mut imported_any = false;
+    if ui.button("export")
+    {
+        ui.set_clipboard_text(export(values));
+    }
+    if ui.is_item_hovered()
+    {
+        ui.tooltip_text("Copy this list to the clipboard to share it.");
+    }
+
+    ui.same_line();
+    if ui.button("import")
+    {
+        match ui.clipboard_text().map(|text| import::<Vec<T>>(&text))
+        {
+            Some(Ok(imported)) =>
+            {
+                for value in imported
+                {
+                    if !values.contains(&value)
+                    {
+                        values.push(value);
+                        imported_any = true;
+                    }
+                }
+            }
+            Some(Err(e)) => info!("failed to import from clipboard: {}", e),
+            None => {}
+        }
+    }
+    if ui.is_item_hovered()
+    {
+        ui.tooltip_text("Add the entries from a list on the clipboard.");
+    }
+    return imported_any;
+}
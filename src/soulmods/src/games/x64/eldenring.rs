@@ -16,13 +16,14 @@
 
 use std::{thread, time::Duration};
 
-use ilhook::x64::{Hooker, HookType, Registers, CallbackOption, HookFlags, HookPoint};
-use mem_rs::prelude::*;
-
-use log::info;
+use ilhook::x64::{HookType, Registers, HookPoint};
+use log::{error, info};
+use soulmemory_common::tables::PatternTable;
 
 use crate::util::GLOBAL_VERSION;
 use crate::util::Version;
+use crate::util::{load_table, scan_symbols};
+use crate::games::x64::hook_symbol;
 
 struct FpsOffsets
 {
@@ -64,90 +65,66 @@ pub static mut ER_FRAME_ADVANCE_ENABLED: bool = false;
 pub static mut ER_FRAME_RUNNING: bool = false;
 
 
-#[allow(unused_assignments)]
+//The function the FPS patches hook was adjusted slightly with the release of the DLC, the patterns and the offsets
+//into the flipper struct have a variant for before and after it
+const PATTERN_TABLE: &str = include_str!("../../../tables/eldenring.json");
+
+fn fps_offsets(table: &PatternTable<Version>, version: &Version) -> Result<FpsOffsets, String>
+{
+    let value = |name: &str| table.value(name, Some(version)).map(|v| v as isize);
+    return Ok(FpsOffsets {
+        target_frame_delta: value("target_frame_delta")?,
+        frame_delta: value("frame_delta")?,
+        timestamp_previous: value("timestamp_previous")?,
+        timestamp_current: value("timestamp_current")?,
+    });
+}
+
 pub fn init_eldenring()
 {
     unsafe
     {
         info!("version: {}", GLOBAL_VERSION);
-        
-        // Get ER process
-        let mut process = Process::new("eldenring.exe");
-        process.refresh().unwrap();
 
-        // AoB scan for timer patch
-        let fn_increment_igt_address = process.scan_abs("increment igt", "48 c7 44 24 20 fe ff ff ff 0f 29 74 24 40 0f 28 f0 48 8b 0d ? ? ? ? 0f 28 c8 f3 0f 59 0d ? ? ? ?", 35, Vec::new()).unwrap().get_base_address();
-        info!("increment IGT at 0x{:x}", fn_increment_igt_address);
+        // Patterns and offsets for this version
+        let table = match load_table("eldenring", PATTERN_TABLE)
+        {
+            Ok(table) => table,
+            Err(e) =>
+            {
+                error!("failed to load the pattern table: {}", e);
+                return;
+            }
+        };
+
+        // AoB scan for all patches at once, a symbol that isn't found only skips its own patch
+        let report = scan_symbols(&GLOBAL_VERSION, &table.symbols);
 
         // Enable timer patch
-        IGT_HOOK = Some(Hooker::new(fn_increment_igt_address, HookType::JmpBack(increment_igt), CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
+        IGT_HOOK = hook_symbol(&report, "increment igt", HookType::JmpBack(increment_igt));
 
-        // AoBs for FPS patches
-        let mut fps_aob = "";
-        let mut fps_history_aob = "";
-        let mut fps_custom_limit_aob = "";
+        // Enable frame advance patch
+        FRAME_ADVANCE_HOOK = hook_symbol(&report, "frame_advance", HookType::JmpBack(frame_advance));
 
-        // Adjust AoBs and offsets for FPS patch based on version
-        // The relevant function was adjusted slightly with the release of the DLC
-        if (GLOBAL_VERSION > Version { major: 2, minor: 0, build: 1, revision: 0 })
+        // The FPS patches need the flipper offsets for this version
+        FPS_OFFSETS = match fps_offsets(&table, &GLOBAL_VERSION)
         {
-            info!("Post-DLC version detected.");
-
-            FPS_OFFSETS = FpsOffsets {
-                target_frame_delta: 0x1c,
-                frame_delta: 0x264,
-                timestamp_previous: 0x20,
-                timestamp_current: 0x28,
-            };
-
-            fps_aob = "8b 83 64 02 00 00 89 83 b4 02 00 00";
-            fps_history_aob = "48 89 04 cb 0f b6 83 74 02 00 00 89 44 cb 08";
-            fps_custom_limit_aob = "0F 57 F6 44 38 BB D0 02 00 00";
-        }
-        else
-        {
-            info!("Pre-DLC version detected.");
-
-            FPS_OFFSETS = FpsOffsets {
-                target_frame_delta: 0x20,
-                frame_delta: 0x26c,
-                timestamp_previous: 0x28,
-                timestamp_current: 0x30,
-            };
-
-            fps_aob = "8b 83 6c 02 00 00 89 83 bc 02 00 00";
-            fps_history_aob = "48 89 44 cb 68 0f b6 83 7c 02 00 00 89 44 cb 70";
-            fps_custom_limit_aob = "0F 57 F6 44 38 BB D8 02 00 00";
-        }
-
-        // AoB scan for FPS patch
-        let fn_fps_address = process.scan_abs("fps", fps_aob, 0, Vec::new()).unwrap().get_base_address();
-        info!("FPS at 0x{:x}", fn_fps_address);
+            Ok(offsets) => offsets,
+            Err(e) =>
+            {
+                error!("skipping the fps patches: {}", e);
+                return;
+            }
+        };
 
         // Enable FPS patch
-        FPS_HOOK = Some(Hooker::new(fn_fps_address, HookType::JmpBack(fps), CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
-
-        // AoB scan for FPS history patch
-        let fn_fps_history_address = process.scan_abs("fps history", fps_history_aob, 0, Vec::new()).unwrap().get_base_address();
-        info!("FPS history at 0x{:x}", fn_fps_history_address);
+        FPS_HOOK = hook_symbol(&report, "fps", HookType::JmpBack(fps));
 
         // Enable FPS history patch
-        FPS_HISTORY_HOOK = Some(Hooker::new(fn_fps_history_address, HookType::JmpBack(fps_history), CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
-
-        // AoB scan for FPS custom limit patch
-        let fn_fps_custom_limit_address = process.scan_abs("fps custom limit", fps_custom_limit_aob, 0, Vec::new()).unwrap().get_base_address();
-        info!("FPS custom limit at 0x{:x}", fn_fps_custom_limit_address);
+        FPS_HISTORY_HOOK = hook_symbol(&report, "fps history", HookType::JmpBack(fps_history));
 
         // Enable FPS custom limit patch
-        FPS_CUSTOM_LIMIT_HOOK = Some(Hooker::new(fn_fps_custom_limit_address, HookType::JmpBack(fps_custom_limit), CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
-    
-
-        // AoB scan for frame advance patch
-        let fn_frame_advance_address = process.scan_abs("frame_advance", "e8 ? ? ? ? e8 ? ? ? ? 84 c0 74 4f", 21, Vec::new()).unwrap().get_base_address();
-        info!("Frame advance at 0x{:x}", fn_frame_advance_address);
-
-        // Enable frame advance patch
-        FRAME_ADVANCE_HOOK = Some(Hooker::new(fn_frame_advance_address, HookType::JmpBack(frame_advance), CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
+        FPS_CUSTOM_LIMIT_HOOK = hook_symbol(&report, "fps custom limit", HookType::JmpBack(fps_custom_limit));
     }
 }
 
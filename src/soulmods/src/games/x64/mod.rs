@@ -18,4 +18,22 @@ pub(crate) mod armoredcore6;
 pub(crate) mod darksouls2scholarofthefirstsin;
 pub(crate) mod darksouls3;
 pub(crate) mod eldenring;
-pub(crate) mod sekiro;
\ No newline at end of file
+pub(crate) mod sekiro;
+use ilhook::x64::{CallbackOption, HookFlags, HookPoint, HookType, Hooker};
+use log::warn;
+use soulmemory_common::scanner::ScanReport;
+
+///Hook a function found by the scan. A symbol that wasn't found only skips its own patch.
+pub(crate) unsafe fn hook_symbol(report: &ScanReport, name: &str, hook_type: HookType) -> Option<HookPoint>
+{
+    let address = match report.address(name)
+    {
+        Ok(address) => address,
+        Err(e) =>
+        {
+            warn!("skipping the {} patch: {}", name, e);
+            return None;
+        }
+    };
+    return Some(Hooker::new(address, hook_type, CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
+}
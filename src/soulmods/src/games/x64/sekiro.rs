@@ -16,12 +16,31 @@
 
 use std::{thread, time::Duration};
 
-use ilhook::x64::{Hooker, HookType, Registers, CallbackOption, HookFlags, HookPoint};
-use mem_rs::prelude::*;
-
-use log::info;
+use ilhook::x64::{HookType, Registers, HookPoint};
+use log::{error, info};
+use soulmemory_common::tables::PatternTable;
 
 use crate::util::GLOBAL_VERSION;
+use crate::util::Version;
+use crate::util::{load_table, scan_symbols};
+use crate::games::x64::hook_symbol;
+
+struct FpsOffsets
+{
+    target_frame_delta: isize,
+    frame_delta: isize,
+    frame_delta_copy: isize,
+    timestamp_previous: isize,
+    timestamp_current: isize,
+}
+
+static mut FPS_OFFSETS: FpsOffsets = FpsOffsets {
+    target_frame_delta: 0x0,
+    frame_delta: 0x0,
+    frame_delta_copy: 0x0,
+    timestamp_previous: 0x0,
+    timestamp_current: 0x0,
+};
 
 static mut FPS_HOOK: Option<HookPoint> = None;
 static mut FPS_HISTORY_HOOK: Option<HookPoint> = None;
@@ -46,48 +65,62 @@ pub static mut SEKIRO_FRAME_ADVANCE_ENABLED: bool = false;
 pub static mut SEKIRO_FRAME_RUNNING: bool = false;
 
 
-#[allow(unused_assignments)]
+const PATTERN_TABLE: &str = include_str!("../../../tables/sekiro.json");
+
+fn fps_offsets(table: &PatternTable<Version>, version: &Version) -> Result<FpsOffsets, String>
+{
+    let value = |name: &str| table.value(name, Some(version)).map(|v| v as isize);
+    return Ok(FpsOffsets {
+        target_frame_delta: value("target_frame_delta")?,
+        frame_delta: value("frame_delta")?,
+        frame_delta_copy: value("frame_delta_copy")?,
+        timestamp_previous: value("timestamp_previous")?,
+        timestamp_current: value("timestamp_current")?,
+    });
+}
+
 pub fn init_sekiro()
 {
     unsafe
     {
         info!("version: {}", GLOBAL_VERSION);
-        
-        // Get Sekiro process
-        let mut process = Process::new("sekiro.exe");
-        process.refresh().unwrap();
 
+        // Patterns and offsets for this version
+        let table = match load_table("sekiro", PATTERN_TABLE)
+        {
+            Ok(table) => table,
+            Err(e) =>
+            {
+                error!("failed to load the pattern table: {}", e);
+                return;
+            }
+        };
 
-        // AoB scan for FPS patch
-        let fn_fps_address = process.scan_abs("fps", "f3 0f 58 93 64 02 00 00 41 0f 2f d4", 0, Vec::new()).unwrap().get_base_address();
-        info!("FPS at 0x{:x}", fn_fps_address);
+        // AoB scan for all patches at once, a symbol that isn't found only skips its own patch
+        let report = scan_symbols(&GLOBAL_VERSION, &table.symbols);
 
-        // Enable FPS patch
-        FPS_HOOK = Some(Hooker::new(fn_fps_address, HookType::JmpBack(fps), CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
+        // Enable frame advance patch
+        FRAME_ADVANCE_HOOK = hook_symbol(&report, "frame_advance", HookType::JmpBack(frame_advance));
 
+        // The FPS patches need the flipper offsets for this version
+        FPS_OFFSETS = match fps_offsets(&table, &GLOBAL_VERSION)
+        {
+            Ok(offsets) => offsets,
+            Err(e) =>
+            {
+                error!("skipping the fps patches: {}", e);
+                return;
+            }
+        };
 
-        // AoB scan for FPS history patch
-        let fn_fps_history_address = process.scan_abs("fps history", "48 89 04 cb 0f b6 83 78 02 00 00", 0, Vec::new()).unwrap().get_base_address();
-        info!("FPS history at 0x{:x}", fn_fps_history_address);
+        // Enable FPS patch
+        FPS_HOOK = hook_symbol(&report, "fps", HookType::JmpBack(fps));
 
         // Enable FPS history patch
-        FPS_HISTORY_HOOK = Some(Hooker::new(fn_fps_history_address, HookType::JmpBack(fps_history), CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
-
-
-        // AoB scan for FPS custom limit patch
-        let fn_fps_custom_limit_address = process.scan_abs("fps custom limit", "e8 ? ? ? ? 84 c0 74 0a c7 83 74 02 00 00 1e 00 00 00", 0, Vec::new()).unwrap().get_base_address();
-        info!("FPS custom limit at 0x{:x}", fn_fps_custom_limit_address);
+        FPS_HISTORY_HOOK = hook_symbol(&report, "fps history", HookType::JmpBack(fps_history));
 
         // Enable FPS custom limit patch
-        FPS_CUSTOM_LIMIT_HOOK = Some(Hooker::new(fn_fps_custom_limit_address, HookType::JmpBack(fps_custom_limit), CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
-    
-
-        // AoB scan for frame advance patch
-        let fn_frame_advance_address = process.scan_abs("frame_advance", "e8 ? ? ? ? 84 c0 74 4e 66 0f 1f 44 00 00", 15, Vec::new()).unwrap().get_base_address();
-        info!("Frame advance at 0x{:x}", fn_frame_advance_address);
-
-        // Enable frame advance patch
-        FRAME_ADVANCE_HOOK = Some(Hooker::new(fn_frame_advance_address, HookType::JmpBack(frame_advance), CallbackOption::None, 0, HookFlags::empty()).hook().unwrap());
+        FPS_CUSTOM_LIMIT_HOOK = hook_symbol(&report, "fps custom limit", HookType::JmpBack(fps_custom_limit));
     }
 }
 
@@ -102,11 +135,11 @@ unsafe extern "win64" fn fps(registers: *mut Registers, _:usize)
     {
         let ptr_flipper = (*registers).rbx as *const u8; // Flipper struct - Contains all the stuff we need
 
-        let ptr_target_frame_delta = ptr_flipper.offset(0x18) as *mut f32; // Target frame delta - Set in a switch/case at the start
-        let ptr_timestamp_previous = ptr_flipper.offset(0x20) as *mut u64; // Previous frames timestamp
-        let ptr_timestamp_current = ptr_flipper.offset(0x28) as *mut u64; // Current frames timestamp
-        let ptr_frame_delta = ptr_flipper.offset(0x264) as *mut f32; // Current frames frame delta
-        let ptr_frame_delta_copy = ptr_flipper.offset(0x2b8) as *mut f32; // Current frames frame delta - Copy that's assigned differently than in ER, so we assign it manually too
+        let ptr_target_frame_delta = ptr_flipper.offset(FPS_OFFSETS.target_frame_delta) as *mut f32; // Target frame delta - Set in a switch/case at the start
+        let ptr_timestamp_previous = ptr_flipper.offset(FPS_OFFSETS.timestamp_previous) as *mut u64; // Previous frames timestamp
+        let ptr_timestamp_current = ptr_flipper.offset(FPS_OFFSETS.timestamp_current) as *mut u64; // Current frames timestamp
+        let ptr_frame_delta = ptr_flipper.offset(FPS_OFFSETS.frame_delta) as *mut f32; // Current frames frame delta
+        let ptr_frame_delta_copy = ptr_flipper.offset(FPS_OFFSETS.frame_delta_copy) as *mut f32; // Current frames frame delta - Copy that's assigned differently than in ER, so we assign it manually too
 
         // Read target frame data, the current timestamp and then calculate the timestamp diff at stable FPS
         let target_frame_delta = std::ptr::read_volatile(ptr_target_frame_delta);
@@ -133,7 +166,7 @@ unsafe extern "win64" fn fps_history(registers: *mut Registers, _:usize)
     {
         let ptr_flipper = (*registers).rbx as *const u8; // Flipper struct - Contains all the stuff we need
 
-        let ptr_target_frame_delta = ptr_flipper.offset(0x18) as *mut f32; // Target frame delta - Set in a switch/case at the start
+        let ptr_target_frame_delta = ptr_flipper.offset(FPS_OFFSETS.target_frame_delta) as *mut f32; // Target frame delta - Set in a switch/case at the start
 
         // Read the target frame delta and write back the calculated frame delta timestamp
         let target_frame_delta = std::ptr::read_volatile(ptr_target_frame_delta);
@@ -151,7 +184,7 @@ unsafe extern "win64" fn fps_custom_limit(registers: *mut Registers, _:usize)
     {
         let ptr_flipper = (*registers).rbx as *const u8; // Flipper struct - Contains all the stuff we need
 
-        let ptr_target_frame_delta = ptr_flipper.offset(0x18) as *mut f32; // Target frame delta - Set in a switch/case at the start
+        let ptr_target_frame_delta = ptr_flipper.offset(FPS_OFFSETS.target_frame_delta) as *mut f32; // Target frame delta - Set in a switch/case at the start
 
         // Read the stock target frame delta and calculate the custom target frame delta
         let target_frame_delta = std::ptr::read_volatile(ptr_target_frame_delta);
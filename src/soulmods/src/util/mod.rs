@@ -1,4 +1,5 @@
-mod version;
 mod globals;
-pub use version::*;
+mod scan;
 pub use globals::*;
+pub use scan::*;
+pub use soulmemory_common::version::Version;
@@ -0,0 +1,102 @@
+// This file is part of the SoulSplitter distribution (https://github.com/FrankvdStam/SoulSplitter).
+// Copyright (c) 2022 Frank van der Stam.
+// https://github.com/FrankvdStam/SoulSplitter/blob/main/LICENSE
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful, but
+// WITHOUT ANY WARRANTY without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+// General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::ffi::c_void;
+use std::mem;
+use std::path::PathBuf;
+use log::{error, info, warn};
+use soulmemory_common::scanner::{self, ScanReport, Symbol};
+use soulmemory_common::tables::PatternTable;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::HMODULE;
+use windows::Win32::System::Diagnostics::Debug::ReadProcessMemory;
+use windows::Win32::System::LibraryLoader::{GetModuleFileNameW, GetModuleHandleW};
+use windows::Win32::System::ProcessStatus::{GetModuleInformation, MODULEINFO};
+use windows::Win32::System::Threading::GetCurrentProcess;
+use crate::util::{Version, GLOBAL_HMODULE};
+
+///Base address and a copy of the image of the game's executable
+pub fn main_module_image() -> Option<(usize, Vec<u8>)>
+{
+    unsafe
+    {
+        let module = GetModuleHandleW(PCWSTR::null()).ok()?;
+        let mut info = MODULEINFO::default();
+        GetModuleInformation(GetCurrentProcess(), module, &mut info, mem::size_of::<MODULEINFO>() as u32).ok()?;
+
+        let base = info.lpBaseOfDll as usize;
+        let mut image = vec![0u8; info.SizeOfImage as usize];
+        ReadProcessMemory(GetCurrentProcess(), base as *const c_void, image.as_mut_ptr() as *mut c_void, image.len(), None).ok()?;
+        return Some((base, image));
+    }
+}
+
+///Path of soulmods.dll
+pub fn dll_path() -> Option<PathBuf>
+{
+    unsafe
+    {
+        let mut buffer = [0u16; 1024];
+        let length = GetModuleFileNameW(HMODULE(GLOBAL_HMODULE.0), &mut buffer) as usize;
+        if length == 0 || length == buffer.len()
+        {
+            return None;
+        }
+        return Some(PathBuf::from(String::from_utf16_lossy(&buffer[..length])));
+    }
+}
+
+///Pattern table for a game, built into the dll and patched by <dll name>.<game>.json next to the dll
+pub fn load_table(game: &str, builtin: &str) -> Result<PatternTable<Version>, String>
+{
+    let override_path = dll_path().map(|path| PatternTable::<Version>::override_path(&path, game)).unwrap_or_default();
+    if override_path.exists()
+    {
+        info!("pattern overrides from {}", override_path.display());
+    }
+    return PatternTable::load(builtin, &override_path);
+}
+
+///Scan the game's executable for all symbols at once, using the game version to pick pattern variants.
+///Logs every symbol that is missing, not just the first one.
+pub fn scan_symbols(version: &Version, symbols: &[Symbol<Version>]) -> ScanReport
+{
+    let (base, image) = match main_module_image()
+    {
+        Some(image) => image,
+        None =>
+        {
+            error!("failed to read the main module");
+            return ScanReport { found: Vec::new(), missing: symbols.iter().map(|s| s.name.clone()).collect() };
+        }
+    };
+
+    let report = scanner::scan(&image, base, Some(version), symbols);
+    for found in &report.found
+    {
+        info!("{} at 0x{:x}, variant {}", found.name, found.address, found.variant);
+        if found.match_count > 1
+        {
+            warn!("{} matched {} times, using the first match", found.name, found.match_count);
+        }
+    }
+    for missing in &report.missing
+    {
+        error!("{} not found", missing);
+    }
+    return report;
+}